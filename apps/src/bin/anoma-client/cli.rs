@@ -1,7 +1,7 @@
 use std::fs::File;
 use std::io::Write;
 
-use anoma::client::tx;
+use anoma::client::{rpc, tx};
 use anoma::proto::services::rpc_service_client::RpcServiceClient;
 use anoma::proto::{self, services, RpcMessage};
 use anoma::{cli, wallet};
@@ -32,7 +32,7 @@ pub async fn main() -> Result<()> {
             let source = cli::parse_string_req(args, cli::SOURCE_ARG);
             let target = cli::parse_string_req(args, cli::TARGET_ARG);
             let token = cli::parse_string_req(args, cli::TOKEN_ARG);
-            let amount: f64 = cli::parse_req(args, cli::AMOUNT_ARG);
+            let amount = cli::parse_string_req(args, cli::AMOUNT_ARG);
             let tx_code_path = cli::parse_string_req(args, cli::CODE_ARG);
             let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
             let ledger_address =
@@ -49,6 +49,241 @@ pub async fn main() -> Result<()> {
             .await;
             Ok(())
         }
+        Some((cli::TX_BATCH_TRANSFER_COMMAND, args)) => {
+            let file = cli::parse_string_req(args, cli::FILE_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_batch_transfer(file, dry_run, ledger_address).await;
+            Ok(())
+        }
+        Some((cli::TX_HTLC_LOCK_COMMAND, args)) => {
+            let source = cli::parse_string_req(args, cli::SOURCE_ARG);
+            let beneficiary = cli::parse_string_req(args, cli::BENEFICIARY_ARG);
+            let token = cli::parse_string_req(args, cli::TOKEN_ARG);
+            let amount = cli::parse_string_req(args, cli::AMOUNT_ARG);
+            let hash_lock = cli::parse_string_req(args, cli::HASH_LOCK_ARG);
+            let deadline: u64 = cli::parse_req(args, cli::DEADLINE_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_htlc_lock(
+                source,
+                beneficiary,
+                token,
+                amount,
+                hash_lock,
+                deadline,
+                dry_run,
+                ledger_address,
+            )
+            .await;
+            Ok(())
+        }
+        Some((cli::TX_HTLC_CLAIM_COMMAND, args)) => {
+            let escrow = cli::parse_string_req(args, cli::ESCROW_ARG);
+            let preimage = cli::parse_string_req(args, cli::PREIMAGE_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_htlc_claim(escrow, preimage, dry_run, ledger_address)
+                .await;
+            Ok(())
+        }
+        Some((cli::TX_HTLC_REFUND_COMMAND, args)) => {
+            let escrow = cli::parse_string_req(args, cli::ESCROW_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_htlc_refund(escrow, dry_run, ledger_address).await;
+            Ok(())
+        }
+        Some((cli::TX_REGISTER_VALIDATOR_COMMAND, args)) => {
+            let validator = cli::parse_string_req(args, cli::VALIDATOR_ARG);
+            let consensus_key =
+                cli::parse_string_req(args, cli::CONSENSUS_KEY_ARG);
+            let commission_rate: u16 =
+                cli::parse_req(args, cli::COMMISSION_RATE_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_register_validator(
+                validator,
+                consensus_key,
+                commission_rate,
+                dry_run,
+                ledger_address,
+            )
+            .await;
+            Ok(())
+        }
+        Some((cli::TX_BOND_COMMAND, args)) => {
+            let source = cli::parse_string_req(args, cli::SOURCE_ARG);
+            let validator = cli::parse_string_req(args, cli::VALIDATOR_ARG);
+            let amount = cli::parse_string_req(args, cli::AMOUNT_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_bond(source, validator, amount, dry_run, ledger_address)
+                .await;
+            Ok(())
+        }
+        Some((cli::TX_UNBOND_COMMAND, args)) => {
+            let source = cli::parse_string_req(args, cli::SOURCE_ARG);
+            let validator = cli::parse_string_req(args, cli::VALIDATOR_ARG);
+            let amount = cli::parse_string_req(args, cli::AMOUNT_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_unbond(
+                source,
+                validator,
+                amount,
+                dry_run,
+                ledger_address,
+            )
+            .await;
+            Ok(())
+        }
+        Some((cli::TX_WITHDRAW_COMMAND, args)) => {
+            let source = cli::parse_string_req(args, cli::SOURCE_ARG);
+            let validator = cli::parse_string_req(args, cli::VALIDATOR_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_withdraw(source, validator, dry_run, ledger_address)
+                .await;
+            Ok(())
+        }
+        Some((cli::TX_CHANGE_COMMISSION_COMMAND, args)) => {
+            let validator = cli::parse_string_req(args, cli::VALIDATOR_ARG);
+            let commission_rate: u16 =
+                cli::parse_req(args, cli::COMMISSION_RATE_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_change_commission(
+                validator,
+                commission_rate,
+                dry_run,
+                ledger_address,
+            )
+            .await;
+            Ok(())
+        }
+        Some((cli::TX_CLAIM_REWARDS_COMMAND, args)) => {
+            let owner = cli::parse_string_req(args, cli::OWNER_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_claim_rewards(owner, dry_run, ledger_address).await;
+            Ok(())
+        }
+        Some((cli::TX_UNJAIL_COMMAND, args)) => {
+            let validator = cli::parse_string_req(args, cli::VALIDATOR_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_unjail(validator, dry_run, ledger_address).await;
+            Ok(())
+        }
+        Some((cli::TX_SUBMIT_PROPOSAL_COMMAND, args)) => {
+            let author = cli::parse_string_req(args, cli::AUTHOR_ARG);
+            let title = cli::parse_string_req(args, cli::TITLE_ARG);
+            let content = if let Some(path) =
+                cli::parse_string_opt(args, cli::CODE_ARG)
+            {
+                tx::ProposalContentPath::Code(path)
+            } else if let Some(path) =
+                cli::parse_string_opt(args, cli::PARAMETERS_PATH_ARG)
+            {
+                tx::ProposalContentPath::Parameters(path)
+            } else if let Some(path) =
+                cli::parse_string_opt(args, cli::POS_PARAMS_PATH_ARG)
+            {
+                tx::ProposalContentPath::PosParams(path)
+            } else if let Some(path) =
+                cli::parse_string_opt(args, cli::UPGRADE_PLAN_PATH_ARG)
+            {
+                tx::ProposalContentPath::Upgrade(path)
+            } else {
+                tx::ProposalContentPath::Text
+            };
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_proposal(
+                author,
+                title,
+                content,
+                dry_run,
+                ledger_address,
+            )
+            .await;
+            Ok(())
+        }
+        Some((cli::TX_VOTE_COMMAND, args)) => {
+            let voter = cli::parse_string_req(args, cli::VOTER_ARG);
+            let proposal_id = cli::parse_req(args, cli::PROPOSAL_ID_ARG);
+            let vote = cli::parse_req(args, cli::VOTE_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_vote(voter, proposal_id, vote, dry_run, ledger_address)
+                .await;
+            Ok(())
+        }
+        Some((cli::QUERY_COMMAND, args)) => match args.subcommand() {
+            Some((cli::QUERY_TOKEN_COMMAND, args)) => {
+                let token = cli::parse_string_req(args, cli::TOKEN_ARG);
+                let ledger_address =
+                    cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+                rpc::query_token(token, ledger_address).await;
+                Ok(())
+            }
+            Some((cli::QUERY_VALIDATORS_COMMAND, args)) => {
+                let ledger_address =
+                    cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+                rpc::query_validators(ledger_address).await;
+                Ok(())
+            }
+            Some((cli::QUERY_BONDS_COMMAND, args)) => {
+                let source = cli::parse_string_req(args, cli::SOURCE_ARG);
+                let ledger_address =
+                    cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+                rpc::query_bonds(source, ledger_address).await;
+                Ok(())
+            }
+            Some((cli::QUERY_REWARDS_COMMAND, args)) => {
+                let owner = cli::parse_string_req(args, cli::OWNER_ARG);
+                let ledger_address =
+                    cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+                rpc::query_rewards(owner, ledger_address).await;
+                Ok(())
+            }
+            Some((cli::QUERY_STORAGE_USAGE_COMMAND, args)) => {
+                let owner = cli::parse_string_req(args, cli::OWNER_ARG);
+                let ledger_address =
+                    cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+                rpc::query_storage_usage(owner, ledger_address).await;
+                Ok(())
+            }
+            Some((cli::QUERY_SLASHES_COMMAND, args)) => {
+                let validator = cli::parse_string_req(args, cli::VALIDATOR_ARG);
+                let ledger_address =
+                    cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+                rpc::query_slashes(validator, ledger_address).await;
+                Ok(())
+            }
+            Some((cli::QUERY_PROPOSAL_COMMAND, args)) => {
+                let proposal_id = cli::parse_req(args, cli::PROPOSAL_ID_ARG);
+                let ledger_address =
+                    cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+                rpc::query_proposal(proposal_id, ledger_address).await;
+                Ok(())
+            }
+            _ => app.print_help().wrap_err("Can't display help."),
+        },
         Some((cli::TX_UPDATE_COMMAND, args)) => {
             let addr = cli::parse_string_req(args, cli::ADDRESS_ARG);
             let vp_code_path = cli::parse_string_req(args, cli::CODE_ARG);
@@ -59,6 +294,137 @@ pub async fn main() -> Result<()> {
                 .await;
             Ok(())
         }
+        Some((cli::TX_UPDATE_KEY_COMMAND, args)) => {
+            let addr = cli::parse_string_req(args, cli::ADDRESS_ARG);
+            let pk = cli::parse_string_opt(args, cli::PUBLIC_KEY_ARG);
+            let recovery_pk =
+                cli::parse_string_opt(args, cli::RECOVERY_PUBLIC_KEY_ARG);
+            let signer = cli::parse_string_opt(args, cli::SIGNER_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_update_key(
+                addr,
+                pk,
+                recovery_pk,
+                signer,
+                dry_run,
+                ledger_address,
+            )
+            .await;
+            Ok(())
+        }
+        Some((cli::TX_INIT_ACCOUNT_COMMAND, args)) => {
+            let source = cli::parse_string_req(args, cli::SOURCE_ARG);
+            let vp_code_path = cli::parse_string_req(args, cli::VP_ARG);
+            let public_key = cli::parse_string_req(args, cli::PUBLIC_KEY_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_init_account(
+                source,
+                vp_code_path,
+                public_key,
+                dry_run,
+                ledger_address,
+            )
+            .await;
+            Ok(())
+        }
+        Some((cli::TX_INIT_MULTISIG_COMMAND, args)) => {
+            let vp_code_path = cli::parse_string_req(args, cli::VP_ARG);
+            let public_keys = cli::parse_vec_req(args, cli::PUBLIC_KEYS_ARG);
+            let threshold: u8 = cli::parse_req(args, cli::THRESHOLD_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_init_multisig(
+                vp_code_path,
+                public_keys,
+                threshold,
+                dry_run,
+                ledger_address,
+            )
+            .await;
+            Ok(())
+        }
+        Some((cli::TX_INIT_TOKEN_COMMAND, args)) => {
+            let vp_code_path = cli::parse_string_req(args, cli::VP_ARG);
+            let mint_authority =
+                cli::parse_string_req(args, cli::MINT_AUTHORITY_ARG);
+            let symbol = cli::parse_string_req(args, cli::SYMBOL_ARG);
+            let name = cli::parse_string_req(args, cli::NAME_ARG);
+            let decimals: u8 = cli::parse_req(args, cli::DECIMALS_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_init_token(
+                vp_code_path,
+                mint_authority,
+                symbol,
+                name,
+                decimals,
+                dry_run,
+                ledger_address,
+            )
+            .await;
+            Ok(())
+        }
+        Some((cli::TX_MINT_COMMAND, args)) => {
+            let token = cli::parse_string_req(args, cli::TOKEN_ARG);
+            let target = cli::parse_string_req(args, cli::TARGET_ARG);
+            let amount = cli::parse_string_req(args, cli::AMOUNT_ARG);
+            let signer = cli::parse_string_req(args, cli::SIGNER_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_mint(
+                token,
+                target,
+                amount,
+                signer,
+                dry_run,
+                ledger_address,
+            )
+            .await;
+            Ok(())
+        }
+        Some((cli::TX_BURN_COMMAND, args)) => {
+            let token = cli::parse_string_req(args, cli::TOKEN_ARG);
+            let source = cli::parse_string_req(args, cli::SOURCE_ARG);
+            let amount = cli::parse_string_req(args, cli::AMOUNT_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_burn(token, source, amount, dry_run, ledger_address)
+                .await;
+            Ok(())
+        }
+        Some((cli::SIGN_TX_COMMAND, args)) => {
+            let tx_code_path = cli::parse_string_req(args, cli::CODE_ARG);
+            let data_path = cli::parse_string_req(args, cli::DATA_ARG);
+            let signer = cli::parse_string_req(args, cli::SIGNER_ARG);
+            let file = cli::parse_string_req(args, cli::FILE_ARG);
+            tx::sign_tx(tx_code_path, data_path, signer, file);
+            Ok(())
+        }
+        Some((cli::TX_MULTISIG_COMMAND, args)) => {
+            let tx_code_path = cli::parse_string_req(args, cli::CODE_ARG);
+            let data_path = cli::parse_string_req(args, cli::DATA_ARG);
+            let sig_paths = cli::parse_vec_req(args, cli::SIGNATURES_ARG);
+            let dry_run = args.is_present(cli::DRY_RUN_TX_ARG);
+            let ledger_address =
+                cli::parse_string_req(args, cli::LEDGER_ADDRESS_ARG);
+            tx::submit_multisig(
+                tx_code_path,
+                data_path,
+                sig_paths,
+                dry_run,
+                ledger_address,
+            )
+            .await;
+            Ok(())
+        }
         Some((cli::INTENT_COMMAND, args)) => {
             let node = cli::parse_string_req(args, cli::NODE_INTENT_ARG);
             let data = cli::parse_string_req(args, cli::DATA_INTENT_ARG);
@@ -120,19 +486,17 @@ async fn subscribe_topic(node_addr: String, topic: String) {
 fn craft_intent(
     addr: String,
     token_sell: String,
-    amount_sell: f64,
+    amount_sell: token::Amount,
     token_buy: String,
-    amount_buy: f64,
+    amount_buy: token::Amount,
     file: String,
 ) {
     let source_keypair = wallet::key_of(&addr);
     let addr = Address::decode(addr).expect("Source address is not valid");
     let token_sell = Address::decode(token_sell)
         .expect("Token to sell address is not valid");
-    let amount_sell = token::Amount::from(amount_sell);
     let token_buy =
         Address::decode(token_buy).expect("Token to buy address is not valid");
-    let amount_buy = token::Amount::from(amount_buy);
 
     let intent = Intent {
         addr,
@@ -1,8 +1,10 @@
 //! The docstrings on types and their fields with `derive(Clap)` are displayed
 //! in the CLI `--help`.
+use std::path::PathBuf;
+
 use anoma::config::Config;
 use anoma::node::{gossip, ledger};
-use anoma::{cli, config};
+use anoma::{cli, config, genesis};
 use eyre::{Context, Result};
 
 pub fn main() -> Result<()> {
@@ -22,9 +24,12 @@ pub fn main() -> Result<()> {
                 .expect("failed to update config with cli option");
             gossip::run(gossip_cfg).wrap_err("Failed to run gossip service")
         }
-        Some((cli::RUN_LEDGER_COMMAND, _)) => {
+        Some((cli::RUN_LEDGER_COMMAND, args)) => {
             let config = get_cfg(home);
-            let ledger_cfg = config.ledger.unwrap_or_default();
+            let mut ledger_cfg = config.ledger.unwrap_or_default();
+            if args.is_present(cli::IN_MEMORY_ARG) {
+                ledger_cfg.in_memory = true;
+            }
             ledger::run(ledger_cfg).wrap_err("Failed to run Anoma node")
         }
         Some((cli::RESET_LEDGER_COMMAND, _)) => {
@@ -38,6 +43,30 @@ pub fn main() -> Result<()> {
             tracing::debug!("generated config {:?}", gen_config);
             Ok(())
         }
+        Some((cli::VALIDATE_GENESIS_COMMAND, args)) => {
+            let path = match cli::parse_string_opt(args, cli::GENESIS_PATH_ARG)
+            {
+                Some(path) => PathBuf::from(path),
+                None => get_cfg(home).ledger.unwrap_or_default().genesis,
+            };
+            let config = genesis::GenesisConfig::load(&path)
+                .wrap_err("Invalid genesis file")?;
+            println!(
+                "The genesis file {} of the chain {} is valid",
+                path.to_string_lossy(),
+                config.chain_id
+            );
+            Ok(())
+        }
+        Some((cli::INIT_NETWORK_COMMAND, args)) => {
+            let validators = cli::parse_string_req(args, cli::VALIDATORS_ARG)
+                .parse()
+                .wrap_err("The number of validators is not valid")?;
+            let out_dir = cli::parse_string_req(args, cli::OUT_ARG);
+            let chain_id = cli::parse_string_req(args, cli::CHAIN_ID_ARG);
+            ledger::network::init_network(validators, out_dir, chain_id)
+                .wrap_err("Failed to initialize the network")
+        }
         _ => app.print_help().wrap_err("Can't display help."),
     }
 }
@@ -27,12 +27,43 @@ pub const RUN_GOSSIP_COMMAND: &str = "run-gossip";
 pub const RUN_LEDGER_COMMAND: &str = "run-ledger";
 pub const RESET_LEDGER_COMMAND: &str = "reset-ledger";
 pub const GENERATE_CONFIG_COMMAND: &str = "generate-config";
+pub const VALIDATE_GENESIS_COMMAND: &str = "validate-genesis";
+pub const INIT_NETWORK_COMMAND: &str = "init-network";
 pub const INTENT_COMMAND: &str = "intent";
 pub const SUBSCRIBE_TOPIC_COMMAND: &str = "subscribe-topic";
 pub const CRAFT_INTENT_COMMAND: &str = "craft-intent";
 pub const TX_COMMAND: &str = "tx";
 pub const TX_TRANSFER_COMMAND: &str = "transfer";
 pub const TX_UPDATE_COMMAND: &str = "update";
+pub const TX_UPDATE_KEY_COMMAND: &str = "update-key";
+pub const TX_INIT_ACCOUNT_COMMAND: &str = "init-account";
+pub const TX_INIT_MULTISIG_COMMAND: &str = "init-multisig-account";
+pub const TX_INIT_TOKEN_COMMAND: &str = "init-token";
+pub const TX_MINT_COMMAND: &str = "mint";
+pub const TX_BURN_COMMAND: &str = "burn";
+pub const TX_BATCH_TRANSFER_COMMAND: &str = "batch-transfer";
+pub const TX_HTLC_LOCK_COMMAND: &str = "htlc-lock";
+pub const TX_HTLC_CLAIM_COMMAND: &str = "htlc-claim";
+pub const TX_HTLC_REFUND_COMMAND: &str = "htlc-refund";
+pub const TX_REGISTER_VALIDATOR_COMMAND: &str = "register-validator";
+pub const TX_BOND_COMMAND: &str = "bond";
+pub const TX_UNBOND_COMMAND: &str = "unbond";
+pub const TX_WITHDRAW_COMMAND: &str = "withdraw";
+pub const TX_CHANGE_COMMISSION_COMMAND: &str = "change-commission";
+pub const TX_CLAIM_REWARDS_COMMAND: &str = "claim-rewards";
+pub const TX_UNJAIL_COMMAND: &str = "unjail";
+pub const TX_SUBMIT_PROPOSAL_COMMAND: &str = "submit-proposal";
+pub const TX_VOTE_COMMAND: &str = "vote";
+pub const QUERY_COMMAND: &str = "query";
+pub const QUERY_TOKEN_COMMAND: &str = "token";
+pub const QUERY_VALIDATORS_COMMAND: &str = "validators";
+pub const QUERY_BONDS_COMMAND: &str = "bonds";
+pub const QUERY_REWARDS_COMMAND: &str = "rewards";
+pub const QUERY_SLASHES_COMMAND: &str = "slashes";
+pub const QUERY_PROPOSAL_COMMAND: &str = "proposal";
+pub const QUERY_STORAGE_USAGE_COMMAND: &str = "storage-usage";
+pub const SIGN_TX_COMMAND: &str = "sign-tx";
+pub const TX_MULTISIG_COMMAND: &str = "multisig-tx";
 
 // gossip args
 pub const BASE_ARG: &str = "base-dir";
@@ -44,6 +75,7 @@ pub const MATCHMAKER_ARG: &str = "matchmaker-path";
 pub const TX_CODE_ARG: &str = "tx-code-path";
 pub const LEDGER_ADDRESS_ARG: &str = "ledger-address";
 pub const FILTER_ARG: &str = "filter";
+pub const IN_MEMORY_ARG: &str = "in-memory";
 
 // client args
 pub const DATA_ARG: &str = "data-path";
@@ -60,6 +92,38 @@ pub const SOURCE_ARG: &str = "source";
 pub const TARGET_ARG: &str = "target";
 pub const TOKEN_ARG: &str = "token";
 pub const AMOUNT_ARG: &str = "amount";
+pub const SIGNER_ARG: &str = "signer";
+pub const SIGNATURES_ARG: &str = "signatures";
+pub const PUBLIC_KEY_ARG: &str = "public-key";
+pub const PUBLIC_KEYS_ARG: &str = "public-keys";
+pub const THRESHOLD_ARG: &str = "threshold";
+pub const RECOVERY_PUBLIC_KEY_ARG: &str = "recovery-public-key";
+pub const VP_ARG: &str = "vp";
+pub const MINT_AUTHORITY_ARG: &str = "mint-authority";
+pub const SYMBOL_ARG: &str = "symbol";
+pub const NAME_ARG: &str = "name";
+pub const DECIMALS_ARG: &str = "decimals";
+pub const BENEFICIARY_ARG: &str = "beneficiary";
+pub const HASH_LOCK_ARG: &str = "hash-lock";
+pub const DEADLINE_ARG: &str = "deadline";
+pub const ESCROW_ARG: &str = "escrow";
+pub const PREIMAGE_ARG: &str = "preimage";
+pub const GENESIS_PATH_ARG: &str = "genesis-path";
+pub const VALIDATORS_ARG: &str = "validators";
+pub const OUT_ARG: &str = "out";
+pub const CHAIN_ID_ARG: &str = "chain-id";
+pub const VALIDATOR_ARG: &str = "validator";
+pub const CONSENSUS_KEY_ARG: &str = "consensus-key";
+pub const COMMISSION_RATE_ARG: &str = "commission-rate";
+pub const OWNER_ARG: &str = "owner";
+pub const AUTHOR_ARG: &str = "author";
+pub const TITLE_ARG: &str = "title";
+pub const PARAMETERS_PATH_ARG: &str = "parameters-path";
+pub const POS_PARAMS_PATH_ARG: &str = "pos-params-path";
+pub const UPGRADE_PLAN_PATH_ARG: &str = "upgrade-plan-path";
+pub const VOTER_ARG: &str = "voter";
+pub const PROPOSAL_ID_ARG: &str = "proposal-id";
+pub const VOTE_ARG: &str = "vote";
 
 type App = clap::App<'static>;
 
@@ -99,6 +163,28 @@ fn add_client_commands(app: App) -> App {
     app.subcommand(client_tx_subcommand())
         .subcommand(client_tx_transfer_subcommand())
         .subcommand(client_tx_update_subcommand())
+        .subcommand(client_tx_update_key_subcommand())
+        .subcommand(client_tx_init_account_subcommand())
+        .subcommand(client_tx_init_multisig_subcommand())
+        .subcommand(client_tx_init_token_subcommand())
+        .subcommand(client_tx_mint_subcommand())
+        .subcommand(client_tx_burn_subcommand())
+        .subcommand(client_tx_batch_transfer_subcommand())
+        .subcommand(client_tx_htlc_lock_subcommand())
+        .subcommand(client_tx_htlc_claim_subcommand())
+        .subcommand(client_tx_htlc_refund_subcommand())
+        .subcommand(client_tx_register_validator_subcommand())
+        .subcommand(client_tx_bond_subcommand())
+        .subcommand(client_tx_unbond_subcommand())
+        .subcommand(client_tx_withdraw_subcommand())
+        .subcommand(client_tx_change_commission_subcommand())
+        .subcommand(client_tx_claim_rewards_subcommand())
+        .subcommand(client_tx_unjail_subcommand())
+        .subcommand(client_tx_submit_proposal_subcommand())
+        .subcommand(client_tx_vote_subcommand())
+        .subcommand(client_query_subcommand())
+        .subcommand(client_sign_tx_subcommand())
+        .subcommand(client_tx_multisig_subcommand())
         .subcommand(client_intent_subcommand())
         .subcommand(client_craft_intent_subcommand())
         .subcommand(client_subscribe_topic_subcommand())
@@ -127,6 +213,8 @@ fn add_node_commands(app: App) -> App {
         .subcommand(run_ledger_subcommand())
         .subcommand(reset_ledger_subcommand())
         .subcommand(generate_config())
+        .subcommand(validate_genesis_subcommand())
+        .subcommand(init_network_subcommand())
 }
 
 fn client_tx_subcommand() -> App {
@@ -305,7 +393,791 @@ fn client_tx_transfer_subcommand() -> App {
                 .long(AMOUNT_ARG)
                 .takes_value(true)
                 .required(true)
-                .about("The amount to transfer in decimal."),
+                .about(
+                    "The amount to transfer in decimal, with at most the \
+                     token's decimal places.",
+                ),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_update_subcommand() -> App {
+    App::new(TX_UPDATE_COMMAND)
+        .about("Send a transaction to update account's validity predicate")
+        .arg(
+            Arg::new(ADDRESS_ARG)
+                .long(ADDRESS_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The account's address. It's key is used to produce the \
+                     signature.",
+                ),
+        )
+        .arg(
+            Arg::new(CODE_ARG)
+                .long(CODE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The path to the validity predicate wasm code."),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_update_key_subcommand() -> App {
+    App::new(TX_UPDATE_KEY_COMMAND)
+        .about(
+            "Send a transaction to rotate account's public key and/or its \
+             recovery public key",
+        )
+        .arg(
+            Arg::new(ADDRESS_ARG)
+                .long(ADDRESS_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The account's address."),
+        )
+        .arg(
+            Arg::new(PUBLIC_KEY_ARG)
+                .long(PUBLIC_KEY_ARG)
+                .takes_value(true)
+                .required_unless_present(RECOVERY_PUBLIC_KEY_ARG)
+                .about("The new public key as a hex string."),
+        )
+        .arg(
+            Arg::new(RECOVERY_PUBLIC_KEY_ARG)
+                .long(RECOVERY_PUBLIC_KEY_ARG)
+                .takes_value(true)
+                .required(false)
+                .about("The new recovery public key as a hex string."),
+        )
+        .arg(
+            Arg::new(SIGNER_ARG)
+                .long(SIGNER_ARG)
+                .takes_value(true)
+                .required(false)
+                .about(
+                    "The address whose key is used to produce the signature. \
+                     Use it to sign with the recovery key. Defaults to the \
+                     account's address.",
+                ),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_init_account_subcommand() -> App {
+    App::new(TX_INIT_ACCOUNT_COMMAND)
+        .about(
+            "Send a transaction to initialize a new established account with \
+             a public key. The new account's address is printed on success.",
+        )
+        .arg(
+            Arg::new(SOURCE_ARG)
+                .long(SOURCE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The source account address. The source's key is used to \
+                     produce the signature and the source pays the new \
+                     account's storage deposit.",
+                ),
+        )
+        .arg(
+            Arg::new(VP_ARG)
+                .long(VP_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The path to the account's validity predicate wasm code.",
+                ),
+        )
+        .arg(
+            Arg::new(PUBLIC_KEY_ARG)
+                .long(PUBLIC_KEY_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The account's public key as a hex string."),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_init_multisig_subcommand() -> App {
+    App::new(TX_INIT_MULTISIG_COMMAND)
+        .about(
+            "Send a transaction to initialize a new established \
+             multi-signature account with a set of public keys. The new \
+             account's address is printed on success.",
+        )
+        .arg(
+            Arg::new(VP_ARG)
+                .long(VP_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The path to the account's validity predicate wasm code.",
+                ),
+        )
+        .arg(
+            Arg::new(PUBLIC_KEYS_ARG)
+                .long(PUBLIC_KEYS_ARG)
+                .multiple(true)
+                .takes_value(true)
+                .required(true)
+                .about("The account's public keys as hex strings."),
+        )
+        .arg(
+            Arg::new(THRESHOLD_ARG)
+                .long(THRESHOLD_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The number of the public keys that have to sign a \
+                     transaction to authorize it.",
+                ),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_init_token_subcommand() -> App {
+    App::new(TX_INIT_TOKEN_COMMAND)
+        .about(
+            "Send a transaction to initialize a new token with zero supply. \
+             The new token's address is printed on success.",
+        )
+        .arg(
+            Arg::new(VP_ARG)
+                .long(VP_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The path to the token's validity predicate wasm code."),
+        )
+        .arg(
+            Arg::new(MINT_AUTHORITY_ARG)
+                .long(MINT_AUTHORITY_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The address of the account that can mint the token."),
+        )
+        .arg(
+            Arg::new(SYMBOL_ARG)
+                .long(SYMBOL_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The token's ticker symbol."),
+        )
+        .arg(
+            Arg::new(NAME_ARG)
+                .long(NAME_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The token's full name."),
+        )
+        .arg(
+            Arg::new(DECIMALS_ARG)
+                .long(DECIMALS_ARG)
+                .takes_value(true)
+                .required(false)
+                .default_value("6")
+                .about("The number of decimal places of the token's amounts."),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_mint_subcommand() -> App {
+    App::new(TX_MINT_COMMAND)
+        .about(
+            "Send a transaction to mint new tokens, signed by the token's \
+             mint authority",
+        )
+        .arg(
+            Arg::new(TOKEN_ARG)
+                .long(TOKEN_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The token to mint."),
+        )
+        .arg(
+            Arg::new(TARGET_ARG)
+                .long(TARGET_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The address that receives the new tokens."),
+        )
+        .arg(
+            Arg::new(AMOUNT_ARG)
+                .long(AMOUNT_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The amount to mint in decimal, with at most the token's \
+                     decimal places.",
+                ),
+        )
+        .arg(
+            Arg::new(SIGNER_ARG)
+                .long(SIGNER_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The mint authority's address. Its key is used to produce \
+                     the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_burn_subcommand() -> App {
+    App::new(TX_BURN_COMMAND)
+        .about("Send a transaction to burn tokens from the source's balance")
+        .arg(
+            Arg::new(TOKEN_ARG)
+                .long(TOKEN_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The token to burn."),
+        )
+        .arg(
+            Arg::new(SOURCE_ARG)
+                .long(SOURCE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The address whose tokens are burned. Its key is used to \
+                     produce the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(AMOUNT_ARG)
+                .long(AMOUNT_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The amount to burn in decimal, with at most the token's \
+                     decimal places.",
+                ),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_batch_transfer_subcommand() -> App {
+    App::new(TX_BATCH_TRANSFER_COMMAND)
+        .about(
+            "Send a transaction with multiple transfers, which are applied \
+             atomically. The transaction is signed by every source.",
+        )
+        .arg(
+            Arg::new(FILE_ARG)
+                .long(FILE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The path to a JSON file with the transfers, e.g. \
+                     {\"transfers\": [{\"source\": \"a1...\", \"target\": \
+                     \"a1...\", \"token\": \"a1...\", \"amount\": \"1.5\"}]}",
+                ),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_htlc_lock_subcommand() -> App {
+    App::new(TX_HTLC_LOCK_COMMAND)
+        .about(
+            "Send a transaction to lock tokens in a new escrow account, which \
+             releases them to the beneficiary on presentation of a preimage \
+             of the hash lock before the deadline, or refunds them after. \
+             The escrow's address is printed on success.",
+        )
+        .arg(
+            Arg::new(SOURCE_ARG)
+                .long(SOURCE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The sender's address. The sender's key is used to \
+                     produce the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(BENEFICIARY_ARG)
+                .long(BENEFICIARY_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The beneficiary's address."),
+        )
+        .arg(
+            Arg::new(TOKEN_ARG)
+                .long(TOKEN_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The locked token."),
+        )
+        .arg(
+            Arg::new(AMOUNT_ARG)
+                .long(AMOUNT_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The amount to lock in decimal, with at most the token's \
+                     decimal places.",
+                ),
+        )
+        .arg(
+            Arg::new(HASH_LOCK_ARG)
+                .long(HASH_LOCK_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The SHA-256 hash of the preimage as a hex string."),
+        )
+        .arg(
+            Arg::new(DEADLINE_ARG)
+                .long(DEADLINE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The block height from which the tokens can no longer be \
+                     claimed and can be refunded instead.",
+                ),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_htlc_claim_subcommand() -> App {
+    App::new(TX_HTLC_CLAIM_COMMAND)
+        .about(
+            "Send a transaction to release the tokens locked in an escrow \
+             account to its beneficiary",
+        )
+        .arg(
+            Arg::new(ESCROW_ARG)
+                .long(ESCROW_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The escrow account's address."),
+        )
+        .arg(
+            Arg::new(PREIMAGE_ARG)
+                .long(PREIMAGE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The preimage of the hash lock as a hex string."),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_htlc_refund_subcommand() -> App {
+    App::new(TX_HTLC_REFUND_COMMAND)
+        .about(
+            "Send a transaction to refund the tokens locked in an escrow \
+             account to its sender after the deadline",
+        )
+        .arg(
+            Arg::new(ESCROW_ARG)
+                .long(ESCROW_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The escrow account's address."),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_register_validator_subcommand() -> App {
+    App::new(TX_REGISTER_VALIDATOR_COMMAND)
+        .about(
+            "Send a transaction to register a proof-of-stake validator with \
+             its consensus key, signed by the validator",
+        )
+        .arg(
+            Arg::new(VALIDATOR_ARG)
+                .long(VALIDATOR_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The validator's account address. Its key is used to \
+                     produce the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(CONSENSUS_KEY_ARG)
+                .long(CONSENSUS_KEY_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The validator's consensus public key as a hex string of \
+                     its bytes.",
+                ),
+        )
+        .arg(
+            Arg::new(COMMISSION_RATE_ARG)
+                .long(COMMISSION_RATE_ARG)
+                .takes_value(true)
+                .required(false)
+                .default_value("0")
+                .about(
+                    "The share of the validator's rewards that it takes as \
+                     its commission in basis points, at most 10000.",
+                ),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_bond_subcommand() -> App {
+    App::new(TX_BOND_COMMAND)
+        .about(
+            "Send a transaction to bond staking tokens to a validator. The \
+             bond counts towards the validator's voting power from the next \
+             epoch.",
+        )
+        .arg(
+            Arg::new(SOURCE_ARG)
+                .long(SOURCE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The owner of the bonded tokens. Its key is used to \
+                     produce the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(VALIDATOR_ARG)
+                .long(VALIDATOR_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The validator's address."),
+        )
+        .arg(
+            Arg::new(AMOUNT_ARG)
+                .long(AMOUNT_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The amount to bond in decimal."),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_unbond_subcommand() -> App {
+    App::new(TX_UNBOND_COMMAND)
+        .about(
+            "Send a transaction to unbond tokens from a validator. The tokens \
+             can be withdrawn after the unbonding period.",
+        )
+        .arg(
+            Arg::new(SOURCE_ARG)
+                .long(SOURCE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The owner of the bonded tokens. Its key is used to \
+                     produce the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(VALIDATOR_ARG)
+                .long(VALIDATOR_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The validator's address."),
+        )
+        .arg(
+            Arg::new(AMOUNT_ARG)
+                .long(AMOUNT_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The amount to unbond in decimal."),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_withdraw_subcommand() -> App {
+    App::new(TX_WITHDRAW_COMMAND)
+        .about(
+            "Send a transaction to withdraw the unbonded tokens whose \
+             unbonding period is over",
+        )
+        .arg(
+            Arg::new(SOURCE_ARG)
+                .long(SOURCE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The owner of the bonded tokens. Its key is used to \
+                     produce the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(VALIDATOR_ARG)
+                .long(VALIDATOR_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The validator's address."),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_change_commission_subcommand() -> App {
+    App::new(TX_CHANGE_COMMISSION_COMMAND)
+        .about(
+            "Send a transaction to change a validator's commission rate, \
+             signed by the validator",
+        )
+        .arg(
+            Arg::new(VALIDATOR_ARG)
+                .long(VALIDATOR_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The validator's account address. Its key is used to \
+                     produce the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(COMMISSION_RATE_ARG)
+                .long(COMMISSION_RATE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The share of the validator's rewards that it takes as \
+                     its commission in basis points, at most 10000.",
+                ),
         )
         .arg(
             Arg::new(DRY_RUN_TX_ARG)
@@ -325,25 +1197,424 @@ fn client_tx_transfer_subcommand() -> App {
         )
 }
 
-fn client_tx_update_subcommand() -> App {
-    App::new(TX_UPDATE_COMMAND)
-        .about("Send a transaction to update account's validity predicate")
+fn client_tx_claim_rewards_subcommand() -> App {
+    App::new(TX_CLAIM_REWARDS_COMMAND)
+        .about(
+            "Send a transaction to transfer all the account's proof-of-stake \
+             rewards to the account",
+        )
         .arg(
-            Arg::new(ADDRESS_ARG)
-                .long(ADDRESS_ARG)
+            Arg::new(OWNER_ARG)
+                .long(OWNER_ARG)
                 .takes_value(true)
                 .required(true)
                 .about(
-                    "The account's address. It's key is used to produce the \
-                     signature.",
+                    "The owner of the rewards. Its key is used to produce \
+                     the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_unjail_subcommand() -> App {
+    App::new(TX_UNJAIL_COMMAND)
+        .about(
+            "Send a transaction to unjail a slashed validator after its jail \
+             period, signed by the validator",
+        )
+        .arg(
+            Arg::new(VALIDATOR_ARG)
+                .long(VALIDATOR_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The validator's account address. Its key is used to \
+                     produce the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_submit_proposal_subcommand() -> App {
+    App::new(TX_SUBMIT_PROPOSAL_COMMAND)
+        .about(
+            "Send a transaction to submit a governance proposal, locking the \
+             proposal deposit from the author's balance. Without any of the \
+             proposal's code or parameters, the proposal has no effect.",
+        )
+        .arg(
+            Arg::new(AUTHOR_ARG)
+                .long(AUTHOR_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The author's account address. Its key is used to \
+                     produce the signature.",
                 ),
         )
+        .arg(
+            Arg::new(TITLE_ARG)
+                .long(TITLE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("A short description of the proposal."),
+        )
         .arg(
             Arg::new(CODE_ARG)
                 .long(CODE_ARG)
                 .takes_value(true)
+                .required(false)
+                .conflicts_with_all(&[
+                    PARAMETERS_PATH_ARG,
+                    POS_PARAMS_PATH_ARG,
+                    UPGRADE_PLAN_PATH_ARG,
+                ])
+                .about(
+                    "The path to the wasm code to run when the proposal \
+                     passes.",
+                ),
+        )
+        .arg(
+            Arg::new(PARAMETERS_PATH_ARG)
+                .long(PARAMETERS_PATH_ARG)
+                .takes_value(true)
+                .required(false)
+                .conflicts_with_all(&[POS_PARAMS_PATH_ARG, UPGRADE_PLAN_PATH_ARG])
+                .about(
+                    "The path to a TOML file with the protocol parameters to \
+                     set when the proposal passes.",
+                ),
+        )
+        .arg(
+            Arg::new(POS_PARAMS_PATH_ARG)
+                .long(POS_PARAMS_PATH_ARG)
+                .takes_value(true)
+                .required(false)
+                .conflicts_with(UPGRADE_PLAN_PATH_ARG)
+                .about(
+                    "The path to a TOML file with the proof-of-stake \
+                     parameters to set when the proposal passes.",
+                ),
+        )
+        .arg(
+            Arg::new(UPGRADE_PLAN_PATH_ARG)
+                .long(UPGRADE_PLAN_PATH_ARG)
+                .takes_value(true)
+                .required(false)
+                .about(
+                    "The path to a TOML file with the upgrade plan to schedule \
+                     when the proposal passes.",
+                ),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_tx_vote_subcommand() -> App {
+    App::new(TX_VOTE_COMMAND)
+        .about(
+            "Send a transaction to vote on a governance proposal in its \
+             voting period. The vote is weighted by the voter's bonds.",
+        )
+        .arg(
+            Arg::new(VOTER_ARG)
+                .long(VOTER_ARG)
+                .takes_value(true)
                 .required(true)
-                .about("The path to the validity predicate wasm code."),
+                .about(
+                    "The voter's account address. Its key is used to produce \
+                     the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(PROPOSAL_ID_ARG)
+                .long(PROPOSAL_ID_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The ID of the proposal."),
+        )
+        .arg(
+            Arg::new(VOTE_ARG)
+                .long(VOTE_ARG)
+                .takes_value(true)
+                .required(true)
+                .possible_values(&["yay", "nay", "abstain"])
+                .about("The vote."),
+        )
+        .arg(
+            Arg::new(DRY_RUN_TX_ARG)
+                .long(DRY_RUN_TX_ARG)
+                .takes_value(false)
+                .required(false)
+                .about("Dry run the transaction."),
+        )
+        .arg(
+            Arg::new(LEDGER_ADDRESS_ARG)
+                .long(LEDGER_ADDRESS_ARG)
+                .multiple(false)
+                .takes_value(true)
+                .required(false)
+                .default_value("127.0.0.1:26657")
+                .about("Address of a ledger node as host:port"),
+        )
+}
+
+fn client_query_subcommand() -> App {
+    App::new(QUERY_COMMAND)
+        .about("Query the ledger's state")
+        .subcommand(
+            App::new(QUERY_TOKEN_COMMAND)
+                .about("Query a token's metadata, supply and mint authority")
+                .arg(
+                    Arg::new(TOKEN_ARG)
+                        .long(TOKEN_ARG)
+                        .takes_value(true)
+                        .required(true)
+                        .about("The token address."),
+                )
+                .arg(
+                    Arg::new(LEDGER_ADDRESS_ARG)
+                        .long(LEDGER_ADDRESS_ARG)
+                        .multiple(false)
+                        .takes_value(true)
+                        .required(false)
+                        .default_value("127.0.0.1:26657")
+                        .about("Address of a ledger node as host:port"),
+                ),
+        )
+        .subcommand(
+            App::new(QUERY_VALIDATORS_COMMAND)
+                .about("Query the current epoch and validator set")
+                .arg(
+                    Arg::new(LEDGER_ADDRESS_ARG)
+                        .long(LEDGER_ADDRESS_ARG)
+                        .multiple(false)
+                        .takes_value(true)
+                        .required(false)
+                        .default_value("127.0.0.1:26657")
+                        .about("Address of a ledger node as host:port"),
+                ),
+        )
+        .subcommand(
+            App::new(QUERY_BONDS_COMMAND)
+                .about("Query an account's bonds and unbonds")
+                .arg(
+                    Arg::new(SOURCE_ARG)
+                        .long(SOURCE_ARG)
+                        .takes_value(true)
+                        .required(true)
+                        .about("The owner of the bonds."),
+                )
+                .arg(
+                    Arg::new(LEDGER_ADDRESS_ARG)
+                        .long(LEDGER_ADDRESS_ARG)
+                        .multiple(false)
+                        .takes_value(true)
+                        .required(false)
+                        .default_value("127.0.0.1:26657")
+                        .about("Address of a ledger node as host:port"),
+                ),
+        )
+        .subcommand(
+            App::new(QUERY_REWARDS_COMMAND)
+                .about("Query an account's unclaimed proof-of-stake rewards")
+                .arg(
+                    Arg::new(OWNER_ARG)
+                        .long(OWNER_ARG)
+                        .takes_value(true)
+                        .required(true)
+                        .about("The owner of the rewards."),
+                )
+                .arg(
+                    Arg::new(LEDGER_ADDRESS_ARG)
+                        .long(LEDGER_ADDRESS_ARG)
+                        .multiple(false)
+                        .takes_value(true)
+                        .required(false)
+                        .default_value("127.0.0.1:26657")
+                        .about("Address of a ledger node as host:port"),
+                ),
+        )
+        .subcommand(
+            App::new(QUERY_STORAGE_USAGE_COMMAND)
+                .about("Query the number of bytes stored by an account")
+                .arg(
+                    Arg::new(OWNER_ARG)
+                        .long(OWNER_ARG)
+                        .takes_value(true)
+                        .required(true)
+                        .about("The owner of the storage."),
+                )
+                .arg(
+                    Arg::new(LEDGER_ADDRESS_ARG)
+                        .long(LEDGER_ADDRESS_ARG)
+                        .multiple(false)
+                        .takes_value(true)
+                        .required(false)
+                        .default_value("127.0.0.1:26657")
+                        .about("Address of a ledger node as host:port"),
+                ),
+        )
+        .subcommand(
+            App::new(QUERY_SLASHES_COMMAND)
+                .about("Query a validator's slashes and whether it's jailed")
+                .arg(
+                    Arg::new(VALIDATOR_ARG)
+                        .long(VALIDATOR_ARG)
+                        .takes_value(true)
+                        .required(true)
+                        .about("The validator's address."),
+                )
+                .arg(
+                    Arg::new(LEDGER_ADDRESS_ARG)
+                        .long(LEDGER_ADDRESS_ARG)
+                        .multiple(false)
+                        .takes_value(true)
+                        .required(false)
+                        .default_value("127.0.0.1:26657")
+                        .about("Address of a ledger node as host:port"),
+                ),
+        )
+        .subcommand(
+            App::new(QUERY_PROPOSAL_COMMAND)
+                .about("Query a governance proposal and its result")
+                .arg(
+                    Arg::new(PROPOSAL_ID_ARG)
+                        .long(PROPOSAL_ID_ARG)
+                        .takes_value(true)
+                        .required(true)
+                        .about("The ID of the proposal."),
+                )
+                .arg(
+                    Arg::new(LEDGER_ADDRESS_ARG)
+                        .long(LEDGER_ADDRESS_ARG)
+                        .multiple(false)
+                        .takes_value(true)
+                        .required(false)
+                        .default_value("127.0.0.1:26657")
+                        .about("Address of a ledger node as host:port"),
+                ),
+        )
+}
+
+fn client_sign_tx_subcommand() -> App {
+    App::new(SIGN_TX_COMMAND)
+        .about(
+            "Sign a transaction for a multi-signature account. The signature \
+             is written into a file, to be submitted with the other signers' \
+             signatures.",
+        )
+        .arg(
+            Arg::new(CODE_ARG)
+                .long(CODE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The path to the transaction wasm code. It will be signed \
+                     together with the transaction data.",
+                ),
+        )
+        .arg(
+            Arg::new(DATA_ARG)
+                .long(DATA_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The path to the transaction data to be signed."),
+        )
+        .arg(
+            Arg::new(SIGNER_ARG)
+                .long(SIGNER_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The address of the signer. Its key is used to produce \
+                     the signature.",
+                ),
+        )
+        .arg(
+            Arg::new(FILE_ARG)
+                .long(FILE_ARG)
+                .takes_value(true)
+                .required(false)
+                .default_value("tx.sig")
+                .about("The output file"),
+        )
+}
+
+fn client_tx_multisig_subcommand() -> App {
+    App::new(TX_MULTISIG_COMMAND)
+        .about(
+            "Send a transaction for a multi-signature account with the \
+             signatures collected from its signers",
+        )
+        .arg(
+            Arg::new(CODE_ARG)
+                .long(CODE_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The path to the transaction wasm code."),
+        )
+        .arg(
+            Arg::new(DATA_ARG)
+                .long(DATA_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The path to the signed transaction data."),
+        )
+        .arg(
+            Arg::new(SIGNATURES_ARG)
+                .long(SIGNATURES_ARG)
+                .multiple(true)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The paths to the signatures files produced with the \
+                     `sign-tx` command.",
+                ),
         )
         .arg(
             Arg::new(DRY_RUN_TX_ARG)
@@ -429,7 +1700,18 @@ fn run_gossip_subcommand() -> App {
 }
 
 fn run_ledger_subcommand() -> App {
-    App::new(RUN_LEDGER_COMMAND).about("Run Anoma node service.")
+    App::new(RUN_LEDGER_COMMAND)
+        .about("Run Anoma node service.")
+        .arg(
+            Arg::new(IN_MEMORY_ARG)
+                .long(IN_MEMORY_ARG)
+                .takes_value(false)
+                .required(false)
+                .about(
+                    "Keep the ledger's state in memory only. The state is \
+                     wiped when the node starts.",
+                ),
+        )
 }
 
 fn reset_ledger_subcommand() -> App {
@@ -440,6 +1722,53 @@ fn generate_config() -> App {
     App::new(GENERATE_CONFIG_COMMAND).about("Generate default node config.")
 }
 
+fn validate_genesis_subcommand() -> App {
+    App::new(VALIDATE_GENESIS_COMMAND)
+        .about("Check a genesis file and report all the errors found in it.")
+        .arg(
+            Arg::new(GENESIS_PATH_ARG)
+                .long(GENESIS_PATH_ARG)
+                .takes_value(true)
+                .required(false)
+                .about(
+                    "The path to the genesis file. Defaults to the genesis \
+                     file set in the node's config.",
+                ),
+        )
+}
+
+fn init_network_subcommand() -> App {
+    App::new(INIT_NETWORK_COMMAND)
+        .about(
+            "Generate the base directories of a network of validator nodes \
+             running on this machine, with a shared genesis.",
+        )
+        .arg(
+            Arg::new(VALIDATORS_ARG)
+                .long(VALIDATORS_ARG)
+                .takes_value(true)
+                .required(true)
+                .about("The number of validator nodes."),
+        )
+        .arg(
+            Arg::new(OUT_ARG)
+                .long(OUT_ARG)
+                .takes_value(true)
+                .required(true)
+                .about(
+                    "The directory to write the nodes' base directories to.",
+                ),
+        )
+        .arg(
+            Arg::new(CHAIN_ID_ARG)
+                .long(CHAIN_ID_ARG)
+                .takes_value(true)
+                .required(false)
+                .default_value("anoma-localnet")
+                .about("The ID of the chain."),
+        )
+}
+
 pub fn parse_hashset_opt(
     args: &ArgMatches,
     field: &str,
@@ -451,6 +1780,13 @@ pub fn parse_hashset_opt(
     })
 }
 
+pub fn parse_vec_req(args: &ArgMatches, field: &str) -> Vec<String> {
+    args.values_of(field)
+        .expect("field is mandatory")
+        .map(|value| value.to_string())
+        .collect()
+}
+
 pub fn parse_opt<F>(args: &ArgMatches, field: &str) -> Option<F>
 where
     F: FromStr,
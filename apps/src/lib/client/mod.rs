@@ -1 +1,2 @@
+pub mod rpc;
 pub mod tx;
@@ -0,0 +1,363 @@
+//! Client queries of the ledger's state.
+
+use std::str::FromStr;
+
+use anoma_shared::ledger::parameters::{self, Parameters};
+use anoma_shared::ledger::storage_usage;
+use anoma_shared::types::governance::{
+    self, Proposal, ProposalContent, ProposalId, ProposalResult,
+};
+use anoma_shared::types::pos::{
+    self, Bonds, CommissionRate, Epoch, EpochState, Slashes, Unbonds,
+    ValidatorSet,
+};
+use anoma_shared::types::token::{self, TokenMetadata};
+use anoma_shared::types::{Address, Key};
+use borsh::BorshDeserialize;
+use tendermint::abci::Code;
+use tendermint_rpc::{Client, HttpClient};
+use thiserror::Error;
+
+use crate::node::ledger::QUERY_NO_VALUE_CODE;
+
+/// An error of a storage value query
+#[derive(Error, Debug)]
+pub enum QueryError {
+    #[error(
+        "The query of the storage key {key} failed with the code {code}: {log}"
+    )]
+    Failed { key: Key, code: u32, log: String },
+    #[error("Error decoding the value of the storage key {0}: {1}")]
+    Decoding(Key, std::io::Error),
+}
+
+/// Query and print the token's metadata, supply and mint authority.
+pub async fn query_token(token: String, ledger_address: String) {
+    let token = Address::decode(token).expect("Token address is not valid");
+
+    let metadata: Option<TokenMetadata> =
+        query_storage_value(&token::metadata_key(&token), &ledger_address)
+            .await
+            .expect("Querying the storage value shouldn't fail");
+    let decimals = match &metadata {
+        Some(metadata) => {
+            println!("Symbol: {}", metadata.symbol);
+            println!("Name: {}", metadata.name);
+            println!("Decimals: {}", metadata.decimals);
+            metadata.decimals
+        }
+        None => {
+            println!("The token has no metadata");
+            token::DEFAULT_DECIMALS
+        }
+    };
+    let supply: Option<token::Amount> =
+        query_storage_value(&token::supply_key(&token), &ledger_address)
+            .await
+            .expect("Querying the storage value shouldn't fail");
+    match supply {
+        Some(supply) => println!(
+            "Supply: {}",
+            supply
+                .to_string_with_decimals(decimals)
+                .expect("The token decimals should be valid")
+        ),
+        None => println!("The token has no supply recorded"),
+    }
+    let mint_authority: Option<Address> = query_storage_value(
+        &token::mint_authority_key(&token),
+        &ledger_address,
+    )
+    .await
+    .expect("Querying the storage value shouldn't fail");
+    match mint_authority {
+        Some(mint_authority) => println!("Mint authority: {}", mint_authority),
+        None => println!("The token has no mint authority"),
+    }
+}
+
+/// Query and print the current epoch and validator set with the validators'
+/// commission rates.
+pub async fn query_validators(ledger_address: String) {
+    let epoch: Option<EpochState> =
+        query_storage_value(&pos::epoch_key(), &ledger_address)
+            .await
+            .expect("Querying the storage value shouldn't fail");
+    if let Some(epoch) = epoch {
+        println!(
+            "Epoch {}, started at the height {}",
+            epoch.epoch, epoch.start_height.0
+        );
+    }
+    let validator_set: Option<ValidatorSet> =
+        query_storage_value(&pos::validator_set_key(), &ledger_address)
+            .await
+            .expect("Querying the storage value shouldn't fail");
+    match validator_set {
+        Some(validator_set) if !validator_set.is_empty() => {
+            for (validator, active) in validator_set {
+                let commission_rate: CommissionRate = query_storage_value(
+                    &pos::commission_rate_key(&validator),
+                    &ledger_address,
+                )
+                .await
+                .expect("Querying the storage value shouldn't fail")
+                .unwrap_or_default();
+                println!(
+                    "{}: voting power {}, commission rate {}",
+                    validator, active.voting_power, commission_rate
+                );
+            }
+        }
+        _ => println!("The validator set is empty"),
+    }
+}
+
+/// Query and print the source's bonds and unbonds of the staking token.
+pub async fn query_bonds(source: String, ledger_address: String) {
+    let source = Address::decode(source).expect("Source address is not valid");
+    let decimals =
+        query_token_decimals(&pos::staking_token(), &ledger_address).await;
+    let format = |amount: token::Amount| {
+        amount
+            .to_string_with_decimals(decimals)
+            .expect("The token decimals should be valid")
+    };
+
+    let bonds: Bonds =
+        query_storage_value(&pos::bond_key(&source), &ledger_address)
+            .await
+            .expect("Querying the storage value shouldn't fail")
+            .unwrap_or_default();
+    if bonds.is_empty() {
+        println!("No bonds");
+    }
+    for (validator, amount) in bonds {
+        println!("Bonded to {}: {}", validator, format(amount));
+    }
+    let unbonds: Unbonds =
+        query_storage_value(&pos::unbond_key(&source), &ledger_address)
+            .await
+            .expect("Querying the storage value shouldn't fail")
+            .unwrap_or_default();
+    for (validator, unbonds) in unbonds {
+        for (epoch, amount) in unbonds {
+            println!(
+                "Unbonded from {}: {}, withdrawable from the epoch {}",
+                validator,
+                format(amount),
+                epoch
+            );
+        }
+    }
+}
+
+/// Query and print the owner's unclaimed rewards of the staking token.
+pub async fn query_rewards(owner: String, ledger_address: String) {
+    let owner = Address::decode(owner).expect("Owner address is not valid");
+    let decimals =
+        query_token_decimals(&pos::staking_token(), &ledger_address).await;
+    let rewards: token::Amount =
+        query_storage_value(&pos::rewards_key(&owner), &ledger_address)
+            .await
+            .expect("Querying the storage value shouldn't fail")
+            .unwrap_or_default();
+    println!(
+        "Unclaimed rewards: {}",
+        rewards
+            .to_string_with_decimals(decimals)
+            .expect("The token decimals should be valid")
+    );
+}
+
+/// Query and print the number of bytes stored by an account and its storage
+/// deposit, if the deposits are enabled.
+pub async fn query_storage_usage(owner: String, ledger_address: String) {
+    let owner = Address::decode(owner).expect("Owner address is not valid");
+    let usage: u64 =
+        query_storage_value(&storage_usage::usage_key(&owner), &ledger_address)
+            .await
+            .expect("Querying the storage value shouldn't fail")
+            .unwrap_or_default();
+    println!("Storage usage: {} bytes", usage);
+    let parameters: Parameters =
+        query_storage_value(&parameters::parameters_key(), &ledger_address)
+            .await
+            .expect("Querying the storage value shouldn't fail")
+            .unwrap_or_default();
+    if parameters.is_storage_deposit_enabled()
+        && !matches!(owner, Address::Internal(_))
+    {
+        let token = &parameters.storage_deposit_token;
+        let decimals = query_token_decimals(token, &ledger_address).await;
+        let deposit = u64::from(parameters.storage_deposit_per_byte)
+            .checked_mul(usage)
+            .map(token::Amount::from)
+            .expect("The storage deposit shouldn't overflow");
+        println!(
+            "Required storage deposit: {} of the token {}",
+            deposit
+                .to_string_with_decimals(decimals)
+                .expect("The token decimals should be valid"),
+            token
+        );
+    }
+}
+
+/// Query and print the validator's slashes and the epoch until which it's
+/// jailed, if it is.
+pub async fn query_slashes(validator: String, ledger_address: String) {
+    let validator =
+        Address::decode(validator).expect("Validator address is not valid");
+    let decimals =
+        query_token_decimals(&pos::staking_token(), &ledger_address).await;
+    let jailed_until: Option<Epoch> = query_storage_value(
+        &pos::jailed_until_key(&validator),
+        &ledger_address,
+    )
+    .await
+    .expect("Querying the storage value shouldn't fail");
+    match jailed_until {
+        Some(epoch) => {
+            println!("Jailed, can be unjailed from the epoch {}", epoch)
+        }
+        None => println!("Not jailed"),
+    }
+    let slashes: Slashes =
+        query_storage_value(&pos::slashes_key(&validator), &ledger_address)
+            .await
+            .expect("Querying the storage value shouldn't fail")
+            .unwrap_or_default();
+    if slashes.is_empty() {
+        println!("No slashes");
+    }
+    for slash in slashes {
+        println!(
+            "Slashed {} ({} basis points) for {} in the epoch {} at the \
+             height {}",
+            slash
+                .amount
+                .to_string_with_decimals(decimals)
+                .expect("The token decimals should be valid"),
+            slash.rate,
+            slash.slash_type,
+            slash.epoch,
+            slash.height.0
+        );
+    }
+}
+
+/// Query and print the governance proposal and its result, if it has been
+/// tallied.
+pub async fn query_proposal(proposal_id: ProposalId, ledger_address: String) {
+    let proposal: Option<Proposal> = query_storage_value(
+        &governance::proposal_key(proposal_id),
+        &ledger_address,
+    )
+    .await
+    .expect("Querying the storage value shouldn't fail");
+    let proposal = match proposal {
+        Some(proposal) => proposal,
+        None => {
+            println!("The proposal {} doesn't exist", proposal_id);
+            return;
+        }
+    };
+    let decimals =
+        query_token_decimals(&pos::staking_token(), &ledger_address).await;
+    let format = |amount: token::Amount| {
+        amount
+            .to_string_with_decimals(decimals)
+            .expect("The token decimals should be valid")
+    };
+    println!("Title: {}", proposal.title);
+    println!("Author: {}", proposal.author);
+    let content = match &proposal.content {
+        ProposalContent::Text => "text".to_owned(),
+        ProposalContent::Parameters(params) => format!("{:#?}", params),
+        ProposalContent::PosParams(params) => format!("{:#?}", params),
+        ProposalContent::Code(code) => format!("code of {} bytes", code.len()),
+        ProposalContent::Upgrade(plan) => format!("{:#?}", plan),
+    };
+    println!("Content: {}", content);
+    println!("Deposit: {}", format(proposal.deposit));
+    println!(
+        "Voting from the epoch {} until the epoch {}",
+        proposal.start_epoch, proposal.end_epoch
+    );
+    let result: Option<ProposalResult> = query_storage_value(
+        &governance::result_key(proposal_id),
+        &ledger_address,
+    )
+    .await
+    .expect("Querying the storage value shouldn't fail");
+    match result {
+        Some(result) => {
+            println!("Result: {}", result.status);
+            println!(
+                "Yay: {}, nay: {}, abstain: {}",
+                format(result.tally.yay),
+                format(result.tally.nay),
+                format(result.tally.abstain)
+            );
+        }
+        None => println!("Not tallied yet"),
+    }
+}
+
+/// Get the number of decimal places of the given token from its metadata.
+/// Tokens without metadata use the [`token::DEFAULT_DECIMALS`]. Panics if the
+/// query fails for any other reason, rather than guessing the decimals.
+pub async fn query_token_decimals(token: &Address, ledger_address: &str) -> u8 {
+    let metadata: Option<TokenMetadata> =
+        query_storage_value(&token::metadata_key(token), ledger_address)
+            .await
+            .unwrap_or_else(|err| {
+                panic!(
+                    "Cannot query the decimals of the token {}: {}",
+                    token, err
+                )
+            });
+    metadata
+        .map(|metadata| metadata.decimals)
+        .unwrap_or(token::DEFAULT_DECIMALS)
+}
+
+/// Parse a decimal amount of the given token, according to its decimal
+/// places.
+pub async fn parse_token_amount(
+    token: &Address,
+    amount: &str,
+    ledger_address: &str,
+) -> token::Amount {
+    let decimals = query_token_decimals(token, ledger_address).await;
+    token::Amount::from_str_with_decimals(amount, decimals)
+        .expect("The amount is not valid for the token")
+}
+
+/// Query the committed value of a storage key. Returns `None` when the node
+/// reports that the key has no value and an error for any other failure.
+pub async fn query_storage_value<T: BorshDeserialize>(
+    key: &Key,
+    ledger_address: &str,
+) -> Result<Option<T>, QueryError> {
+    let address: tendermint::net::Address =
+        FromStr::from_str(&format!("tcp://{}", ledger_address)).unwrap();
+    let client = HttpClient::new(address).unwrap();
+    let path = FromStr::from_str("value").unwrap();
+    let response = client
+        .abci_query(Some(path), key.to_string().into_bytes(), None, false)
+        .await
+        .unwrap();
+    match response.code {
+        Code::Ok => T::try_from_slice(&response.value[..])
+            .map(Some)
+            .map_err(|err| QueryError::Decoding(key.clone(), err)),
+        Code::Err(QUERY_NO_VALUE_CODE) => Ok(None),
+        Code::Err(code) => Err(QueryError::Failed {
+            key: key.clone(),
+            code,
+            log: response.log.to_string(),
+        }),
+    }
+}
@@ -1,14 +1,59 @@
 use std::str::FromStr;
 
-use anoma_shared::types::key::ed25519::Keypair;
-use anoma_shared::types::{token, Address, UpdateVp};
-use borsh::BorshSerialize;
+use anoma_shared::ledger::parameters::Parameters;
+use anoma_shared::types::governance::{self, ProposalContent, ProposalId};
+use anoma_shared::types::key::ed25519::{Keypair, PublicKey, UpdateKey};
+use anoma_shared::types::key::multisig::{
+    InitMultisigAccount, MultiSignedTxData, Multisig, PartialSignature,
+};
+use anoma_shared::types::{
+    htlc, pos, token, Address, BlockHeight, InitAccount, UpdateVp,
+};
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::Deserialize;
 use tendermint_rpc::{Client, HttpClient};
 
+use super::rpc;
 use crate::proto::Tx;
 use crate::wallet;
 
 const TX_UPDATE_VP: &str = "wasm/txs/tx_update_vp/tx.wasm";
+const TX_UPDATE_KEY: &str = "wasm/txs/tx_update_key/tx.wasm";
+const TX_INIT_ACCOUNT: &str = "wasm/txs/tx_init_account/tx.wasm";
+const TX_INIT_MULTISIG: &str = "wasm/txs/tx_init_multisig/tx.wasm";
+const TX_INIT_TOKEN: &str = "wasm/txs/tx_init_token/tx.wasm";
+const TX_MINT: &str = "wasm/txs/tx_mint/tx.wasm";
+const TX_BURN: &str = "wasm/txs/tx_burn/tx.wasm";
+const TX_BATCH_TRANSFER: &str = "wasm/txs/tx_batch_transfer/tx.wasm";
+const TX_HTLC_LOCK: &str = "wasm/txs/tx_htlc_lock/tx.wasm";
+const TX_HTLC_CLAIM: &str = "wasm/txs/tx_htlc_claim/tx.wasm";
+const TX_HTLC_REFUND: &str = "wasm/txs/tx_htlc_refund/tx.wasm";
+const VP_HTLC: &str = "wasm/vps/vp_htlc/vp.wasm";
+const TX_REGISTER_VALIDATOR: &str = "wasm/txs/tx_register_validator/tx.wasm";
+const TX_BOND: &str = "wasm/txs/tx_bond/tx.wasm";
+const TX_UNBOND: &str = "wasm/txs/tx_unbond/tx.wasm";
+const TX_WITHDRAW: &str = "wasm/txs/tx_withdraw/tx.wasm";
+const TX_CHANGE_COMMISSION: &str = "wasm/txs/tx_change_commission/tx.wasm";
+const TX_CLAIM_REWARDS: &str = "wasm/txs/tx_claim_rewards/tx.wasm";
+const TX_UNJAIL: &str = "wasm/txs/tx_unjail/tx.wasm";
+const TX_SUBMIT_PROPOSAL: &str = "wasm/txs/tx_submit_proposal/tx.wasm";
+const TX_VOTE_PROPOSAL: &str = "wasm/txs/tx_vote_proposal/tx.wasm";
+
+/// A transfer in a batch transfer file, with the addresses in their string
+/// encoding and the amount in decimal
+#[derive(Debug, Deserialize)]
+struct BatchTransferEntry {
+    source: String,
+    target: String,
+    token: String,
+    amount: String,
+}
+
+/// The JSON format of a batch transfer file
+#[derive(Debug, Deserialize)]
+struct BatchTransferFile {
+    transfers: Vec<BatchTransferEntry>,
+}
 
 pub async fn submit_custom(
     tx_code_path: String,
@@ -22,7 +67,7 @@ pub async fn submit_custom(
         std::fs::read(data_path).expect("Expected a file at given data path")
     });
 
-    submit_tx(tx_code, data, dry_run, ledger_address).await
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
 }
 
 pub async fn submit_update_vp(
@@ -46,14 +91,212 @@ pub async fn submit_update_vp(
             .expect("Encoding transaction data shouldn't fail"),
     );
 
-    submit_tx(tx_code, data, dry_run, ledger_address).await
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+pub async fn submit_update_key(
+    addr: String,
+    pk: Option<String>,
+    recovery_pk: Option<String>,
+    signer: Option<String>,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    // the current key is used to sign, unless another signer (i.e. the owner
+    // of the recovery key) is given
+    let signer_key: Keypair = wallet::key_of(signer.as_ref().unwrap_or(&addr));
+    let addr = Address::decode(addr).expect("The address is not valid");
+    let pk = pk.map(parse_public_key);
+    let recovery_pk = recovery_pk.map(parse_public_key);
+    if pk.is_none() && recovery_pk.is_none() {
+        panic!("At least one of the public keys has to be given");
+    }
+    let tx_code = std::fs::read(TX_UPDATE_KEY)
+        .expect("Expected a file at given code path");
+
+    let update_key = UpdateKey {
+        addr,
+        pk,
+        recovery_pk,
+    };
+    let signed = update_key.sign(&tx_code, &signer_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+pub async fn submit_init_account(
+    source: String,
+    vp_code_path: String,
+    public_key: String,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let source_key: Keypair = wallet::key_of(&source);
+    let source = Address::decode(source).expect("Source address is not valid");
+    let public_key = parse_public_key(public_key);
+    let vp_code = std::fs::read(vp_code_path)
+        .expect("Expected a file at given code path");
+    let tx_code = std::fs::read(TX_INIT_ACCOUNT)
+        .expect("Expected a file at given code path");
+
+    let init_account = InitAccount {
+        source,
+        public_key,
+        vp_code,
+    };
+    let signed = init_account.sign(&tx_code, &source_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    let initialized_accounts =
+        submit_tx(tx_code, data, dry_run, ledger_address).await;
+    match initialized_accounts.first() {
+        Some(address) => println!("The new account's address: {}", address),
+        None => println!("No account has been initialized"),
+    }
+}
+
+pub async fn submit_init_multisig(
+    vp_code_path: String,
+    public_keys: Vec<String>,
+    threshold: u8,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let public_keys = public_keys.into_iter().map(parse_public_key).collect();
+    let multisig = Multisig::new(public_keys, threshold)
+        .expect("The multi-signature keys set is not valid");
+    let vp_code = std::fs::read(vp_code_path)
+        .expect("Expected a file at given code path");
+    let tx_code = std::fs::read(TX_INIT_MULTISIG)
+        .expect("Expected a file at given code path");
+
+    let init_account = InitMultisigAccount { multisig, vp_code };
+    let data = Some(
+        init_account
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    let initialized_accounts =
+        submit_tx(tx_code, data, dry_run, ledger_address).await;
+    match initialized_accounts.first() {
+        Some(address) => println!("The new account's address: {}", address),
+        None => println!("No account has been initialized"),
+    }
+}
+
+pub async fn submit_init_token(
+    vp_code_path: String,
+    mint_authority: String,
+    symbol: String,
+    name: String,
+    decimals: u8,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let mint_authority = Address::decode(mint_authority)
+        .expect("Mint authority address is not valid");
+    let metadata = token::TokenMetadata::new(symbol, name, decimals)
+        .expect("The token metadata is not valid");
+    let vp_code = std::fs::read(vp_code_path)
+        .expect("Expected a file at given code path");
+    let tx_code = std::fs::read(TX_INIT_TOKEN)
+        .expect("Expected a file at given code path");
+
+    let init_token = token::InitToken {
+        mint_authority,
+        metadata,
+        vp_code,
+    };
+    let data = Some(
+        init_token
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    let initialized_accounts =
+        submit_tx(tx_code, data, dry_run, ledger_address).await;
+    match initialized_accounts.first() {
+        Some(address) => println!("The new token's address: {}", address),
+        None => println!("No token has been initialized"),
+    }
+}
+
+pub async fn submit_mint(
+    token: String,
+    target: String,
+    amount: String,
+    signer: String,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let signer_key: Keypair = wallet::key_of(&signer);
+    let token = Address::decode(token).expect("Token address is not valid");
+    let amount =
+        rpc::parse_token_amount(&token, &amount, &ledger_address).await;
+    let target = Address::decode(target).expect("Target address is not valid");
+    let tx_code =
+        std::fs::read(TX_MINT).expect("Expected a file at given code path");
+
+    let mint = token::Mint {
+        token,
+        target,
+        amount,
+    };
+    let signed = mint.sign(&tx_code, &signer_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+pub async fn submit_burn(
+    token: String,
+    source: String,
+    amount: String,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let source_key: Keypair = wallet::key_of(&source);
+    let token = Address::decode(token).expect("Token address is not valid");
+    let amount =
+        rpc::parse_token_amount(&token, &amount, &ledger_address).await;
+    let source = Address::decode(source).expect("Source address is not valid");
+    let tx_code =
+        std::fs::read(TX_BURN).expect("Expected a file at given code path");
+
+    let burn = token::Burn {
+        token,
+        source,
+        amount,
+    };
+    let signed = burn.sign(&tx_code, &source_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
 }
 
 pub async fn submit_transfer(
     source: String,
     target: String,
     token: String,
-    amount: f64,
+    amount: String,
     tx_code_path: String,
     dry_run: bool,
     ledger_address: String,
@@ -62,7 +305,8 @@ pub async fn submit_transfer(
     let source = Address::decode(source).expect("Source address is not valid");
     let target = Address::decode(target).expect("Target address is not valid");
     let token = Address::decode(token).expect("Token address is not valid");
-    let amount = token::Amount::from(amount);
+    let amount =
+        rpc::parse_token_amount(&token, &amount, &ledger_address).await;
     let tx_code = std::fs::read(tx_code_path).unwrap();
 
     let transfer = token::Transfer {
@@ -78,15 +322,529 @@ pub async fn submit_transfer(
             .expect("Encoding transaction data shouldn't fail"),
     );
 
-    submit_tx(tx_code, data, dry_run, ledger_address).await
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+/// Submit the transfers from a JSON file in a single transaction, in which
+/// either all or none of them are applied. The transaction is signed by every
+/// source.
+pub async fn submit_batch_transfer(
+    file: String,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let file = std::fs::read(file).expect("Expected a file at given path");
+    let file: BatchTransferFile = serde_json::from_slice(&file[..])
+        .expect("The batch transfer file is not valid");
+    if file.transfers.is_empty() {
+        panic!("The batch transfer file contains no transfers");
+    }
+    let mut transfers = Vec::with_capacity(file.transfers.len());
+    for entry in file.transfers {
+        let source =
+            Address::decode(entry.source).expect("Source address is not valid");
+        let target =
+            Address::decode(entry.target).expect("Target address is not valid");
+        let token =
+            Address::decode(entry.token).expect("Token address is not valid");
+        let amount =
+            rpc::parse_token_amount(&token, &entry.amount, &ledger_address)
+                .await;
+        transfers.push(token::Transfer {
+            source,
+            target,
+            token,
+            amount,
+        });
+    }
+    let tx_code = std::fs::read(TX_BATCH_TRANSFER)
+        .expect("Expected a file at given code path");
+
+    let batch = token::BatchTransfer { transfers };
+    let data = batch
+        .try_to_vec()
+        .expect("Encoding unsigned batch transfer shouldn't fail");
+    let sigs = batch
+        .sources()
+        .into_iter()
+        .map(|source| {
+            let source_key: Keypair = wallet::key_of(source.encode());
+            PartialSignature::new(&source_key, &data, &tx_code)
+        })
+        .collect();
+    let signed = MultiSignedTxData::new(data, sigs);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+#[allow(clippy::too_many_arguments)]
+pub async fn submit_htlc_lock(
+    source: String,
+    beneficiary: String,
+    token: String,
+    amount: String,
+    hash_lock: String,
+    deadline: u64,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let source_key: Keypair = wallet::key_of(&source);
+    let sender = Address::decode(source).expect("Source address is not valid");
+    let beneficiary =
+        Address::decode(beneficiary).expect("Beneficiary address is not valid");
+    let token = Address::decode(token).expect("Token address is not valid");
+    let amount =
+        rpc::parse_token_amount(&token, &amount, &ledger_address).await;
+    let hash_lock_bytes = hex::decode(hash_lock)
+        .expect("The hash lock is not a valid hex string");
+    if hash_lock_bytes.len() != htlc::HASH_LOCK_LENGTH {
+        panic!(
+            "The hash lock must be {} bytes long, got {}",
+            htlc::HASH_LOCK_LENGTH,
+            hash_lock_bytes.len()
+        );
+    }
+    let mut hash_lock = [0; htlc::HASH_LOCK_LENGTH];
+    hash_lock.copy_from_slice(&hash_lock_bytes);
+    let vp_code =
+        std::fs::read(VP_HTLC).expect("Expected a file at given code path");
+    let tx_code = std::fs::read(TX_HTLC_LOCK)
+        .expect("Expected a file at given code path");
+
+    let lock = htlc::LockHtlc {
+        htlc: htlc::Htlc {
+            sender,
+            beneficiary,
+            token,
+            amount,
+            hash_lock,
+            deadline: BlockHeight(deadline),
+        },
+        vp_code,
+    };
+    let signed = lock.sign(&tx_code, &source_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    let initialized_accounts =
+        submit_tx(tx_code, data, dry_run, ledger_address).await;
+    match initialized_accounts.first() {
+        Some(address) => println!("The escrow's address: {}", address),
+        None => println!("No escrow has been initialized"),
+    }
+}
+
+pub async fn submit_htlc_claim(
+    escrow: String,
+    preimage: String,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let escrow = Address::decode(escrow).expect("Escrow address is not valid");
+    let preimage =
+        hex::decode(preimage).expect("The preimage is not a valid hex string");
+    let tx_code = std::fs::read(TX_HTLC_CLAIM)
+        .expect("Expected a file at given code path");
+
+    let claim = htlc::ClaimHtlc { escrow, preimage };
+    let data = Some(
+        claim
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+pub async fn submit_htlc_refund(
+    escrow: String,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let escrow = Address::decode(escrow).expect("Escrow address is not valid");
+    let tx_code = std::fs::read(TX_HTLC_REFUND)
+        .expect("Expected a file at given code path");
+
+    let refund = htlc::RefundHtlc { escrow };
+    let data = Some(
+        refund
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+pub async fn submit_register_validator(
+    validator: String,
+    consensus_key: String,
+    commission_rate: u16,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let validator_key: Keypair = wallet::key_of(&validator);
+    let validator =
+        Address::decode(validator).expect("Validator address is not valid");
+    let consensus_key = parse_public_key(consensus_key);
+    let commission_rate = pos::CommissionRate(commission_rate);
+    commission_rate
+        .validate()
+        .expect("Commission rate is not valid");
+    let tx_code = std::fs::read(TX_REGISTER_VALIDATOR)
+        .expect("Expected a file at given code path");
+
+    let registration = pos::RegisterValidator {
+        validator,
+        consensus_key,
+        commission_rate,
+    };
+    let signed = registration.sign(&tx_code, &validator_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+pub async fn submit_bond(
+    source: String,
+    validator: String,
+    amount: String,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let source_key: Keypair = wallet::key_of(&source);
+    let source = Address::decode(source).expect("Source address is not valid");
+    let validator =
+        Address::decode(validator).expect("Validator address is not valid");
+    let amount = rpc::parse_token_amount(
+        &pos::staking_token(),
+        &amount,
+        &ledger_address,
+    )
+    .await;
+    let tx_code =
+        std::fs::read(TX_BOND).expect("Expected a file at given code path");
+
+    let bond = pos::Bond {
+        source,
+        validator,
+        amount,
+    };
+    let signed = bond.sign(&tx_code, &source_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+pub async fn submit_unbond(
+    source: String,
+    validator: String,
+    amount: String,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let source_key: Keypair = wallet::key_of(&source);
+    let source = Address::decode(source).expect("Source address is not valid");
+    let validator =
+        Address::decode(validator).expect("Validator address is not valid");
+    let amount = rpc::parse_token_amount(
+        &pos::staking_token(),
+        &amount,
+        &ledger_address,
+    )
+    .await;
+    let tx_code =
+        std::fs::read(TX_UNBOND).expect("Expected a file at given code path");
+
+    let unbond = pos::Unbond {
+        source,
+        validator,
+        amount,
+    };
+    let signed = unbond.sign(&tx_code, &source_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+pub async fn submit_withdraw(
+    source: String,
+    validator: String,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let source_key: Keypair = wallet::key_of(&source);
+    let source = Address::decode(source).expect("Source address is not valid");
+    let validator =
+        Address::decode(validator).expect("Validator address is not valid");
+    let tx_code =
+        std::fs::read(TX_WITHDRAW).expect("Expected a file at given code path");
+
+    let withdraw = pos::Withdraw { source, validator };
+    let signed = withdraw.sign(&tx_code, &source_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+pub async fn submit_change_commission(
+    validator: String,
+    commission_rate: u16,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let validator_key: Keypair = wallet::key_of(&validator);
+    let validator =
+        Address::decode(validator).expect("Validator address is not valid");
+    let commission_rate = pos::CommissionRate(commission_rate);
+    commission_rate
+        .validate()
+        .expect("Commission rate is not valid");
+    let tx_code = std::fs::read(TX_CHANGE_COMMISSION)
+        .expect("Expected a file at given code path");
+
+    let change = pos::ChangeCommission {
+        validator,
+        commission_rate,
+    };
+    let signed = change.sign(&tx_code, &validator_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
 }
 
+pub async fn submit_claim_rewards(
+    owner: String,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let owner_key: Keypair = wallet::key_of(&owner);
+    let owner = Address::decode(owner).expect("Owner address is not valid");
+    let tx_code = std::fs::read(TX_CLAIM_REWARDS)
+        .expect("Expected a file at given code path");
+
+    let claim = pos::ClaimRewards { owner };
+    let signed = claim.sign(&tx_code, &owner_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+pub async fn submit_unjail(
+    validator: String,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let validator_key: Keypair = wallet::key_of(&validator);
+    let validator =
+        Address::decode(validator).expect("Validator address is not valid");
+    let tx_code =
+        std::fs::read(TX_UNJAIL).expect("Expected a file at given code path");
+
+    let unjail = pos::Unjail { validator };
+    let signed = unjail.sign(&tx_code, &validator_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+/// The proposed change of a governance proposal, given by the paths of the
+/// files with its content
+pub enum ProposalContentPath {
+    Text,
+    Code(String),
+    Parameters(String),
+    PosParams(String),
+    Upgrade(String),
+}
+
+pub async fn submit_proposal(
+    author: String,
+    title: String,
+    content: ProposalContentPath,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let author_key: Keypair = wallet::key_of(&author);
+    let author = Address::decode(author).expect("Author address is not valid");
+    let content = match content {
+        ProposalContentPath::Text => ProposalContent::Text,
+        ProposalContentPath::Code(path) => ProposalContent::Code(
+            std::fs::read(path).expect("Expected a file at given code path"),
+        ),
+        ProposalContentPath::Parameters(path) => {
+            let params: Parameters = read_toml(path);
+            params.validate().expect("Parameters are not valid");
+            ProposalContent::Parameters(params)
+        }
+        ProposalContentPath::PosParams(path) => {
+            let params: pos::PosParams = read_toml(path);
+            params
+                .validate()
+                .expect("Proof-of-stake parameters are not valid");
+            ProposalContent::PosParams(params)
+        }
+        ProposalContentPath::Upgrade(path) => {
+            ProposalContent::Upgrade(read_toml(path))
+        }
+    };
+    let tx_code = std::fs::read(TX_SUBMIT_PROPOSAL)
+        .expect("Expected a file at given code path");
+
+    let submit = governance::SubmitProposal {
+        author,
+        title,
+        content,
+    };
+    let signed = submit.sign(&tx_code, &author_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+pub async fn submit_vote(
+    voter: String,
+    proposal_id: ProposalId,
+    vote: governance::Vote,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let voter_key: Keypair = wallet::key_of(&voter);
+    let voter = Address::decode(voter).expect("Voter address is not valid");
+    let tx_code = std::fs::read(TX_VOTE_PROPOSAL)
+        .expect("Expected a file at given code path");
+
+    let vote = governance::VoteProposal {
+        voter,
+        proposal_id,
+        vote,
+    };
+    let signed = vote.sign(&tx_code, &voter_key);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+/// Read a value from a TOML file.
+fn read_toml<T: serde::de::DeserializeOwned>(path: String) -> T {
+    let content =
+        std::fs::read_to_string(&path).expect("Expected a file at given path");
+    toml::from_str(&content).expect("The file is not valid")
+}
+
+/// Sign the transaction data with the signer's key and write the signature
+/// into a file, so that it can be collected for a multi-signature transaction.
+pub fn sign_tx(
+    tx_code_path: String,
+    data_path: String,
+    signer: String,
+    file: String,
+) {
+    let signer_key: Keypair = wallet::key_of(&signer);
+    let tx_code = std::fs::read(tx_code_path)
+        .expect("Expected a file at given code path");
+    let data =
+        std::fs::read(data_path).expect("Expected a file at given data path");
+
+    let sig = PartialSignature::new(&signer_key, &data, &tx_code);
+    let sig_bytes = sig
+        .try_to_vec()
+        .expect("Encoding the signature shouldn't fail");
+    std::fs::write(&file, sig_bytes).expect("Couldn't write the signature");
+    println!("Signature written to {}", file);
+}
+
+pub async fn submit_multisig(
+    tx_code_path: String,
+    data_path: String,
+    sig_paths: Vec<String>,
+    dry_run: bool,
+    ledger_address: String,
+) {
+    let tx_code = std::fs::read(tx_code_path)
+        .expect("Expected a file at given code path");
+    let data =
+        std::fs::read(data_path).expect("Expected a file at given data path");
+    let sigs = sig_paths
+        .iter()
+        .map(|sig_path| {
+            let sig_bytes = std::fs::read(sig_path)
+                .expect("Expected a file at given signature path");
+            PartialSignature::try_from_slice(&sig_bytes[..])
+                .expect("Couldn't decode the signature")
+        })
+        .collect();
+
+    let signed = MultiSignedTxData::new(data, sigs);
+    let data = Some(
+        signed
+            .try_to_vec()
+            .expect("Encoding transaction data shouldn't fail"),
+    );
+
+    submit_tx(tx_code, data, dry_run, ledger_address).await;
+}
+
+/// Parse a public key from a hex string of its bytes.
+fn parse_public_key(pk: impl AsRef<str>) -> PublicKey {
+    let bytes = hex::decode(pk.as_ref())
+        .expect("The public key is not a valid hex string");
+    let pk = ed25519_dalek::PublicKey::from_bytes(&bytes)
+        .expect("The public key is not valid");
+    PublicKey::from(pk)
+}
+
+/// Submit a transaction and return the addresses of the accounts initialized
+/// by it, as reported by the ledger.
 async fn submit_tx(
     code: Vec<u8>,
     data: Option<Vec<u8>>,
     dry_run: bool,
     ledger_address: String,
-) {
+) -> Vec<Address> {
     let tx = Tx::new(code, data);
     let tx_bytes = tx.to_bytes();
 
@@ -112,9 +870,26 @@ async fn submit_tx(
             .await
             .unwrap();
         println!("{:#?}", response);
+        decode_initialized_accounts(&response.value)
     } else {
         let response =
             client.broadcast_tx_commit(tx_bytes.into()).await.unwrap();
         println!("{:#?}", response);
+        response
+            .deliver_tx
+            .data
+            .map(|data| decode_initialized_accounts(data.value()))
+            .unwrap_or_default()
+    }
+}
+
+/// Decode the addresses of the accounts initialized by a transaction from the
+/// data of its response. The data is empty if the transaction has failed or
+/// has been rejected by the validity predicates.
+fn decode_initialized_accounts(data: &[u8]) -> Vec<Address> {
+    if data.is_empty() {
+        return vec![];
     }
+    Vec::<Address>::try_from_slice(data)
+        .expect("Couldn't decode the initialized accounts")
 }
@@ -12,6 +12,7 @@ use serde::{Deserialize, Serialize};
 use tendermint::net;
 use thiserror::Error;
 
+use crate::genesis;
 use crate::gossiper::Gossiper;
 
 #[derive(Error, Debug)]
@@ -63,6 +64,11 @@ And this is correct
 pub struct Ledger {
     pub tendermint: PathBuf,
     pub db: PathBuf,
+    /// Keep the ledger's state in memory only instead of the DB. The state
+    /// and Tendermint's state are wiped when the ledger starts.
+    #[serde(default)]
+    pub in_memory: bool,
+    pub genesis: PathBuf,
     pub address: SocketAddr,
     pub network: String,
 }
@@ -70,10 +76,12 @@ pub struct Ledger {
 impl Default for Ledger {
     fn default() -> Self {
         Self {
-            // this two value are override when generating a default config in
+            // these paths are overridden when generating a default config in
             // config::generate(base_dir). There must be a better way ?
             tendermint: PathBuf::from(BASEDIR).join(TENDERMINT_DIR),
             db: PathBuf::from(BASEDIR).join(DB_DIR),
+            in_memory: false,
+            genesis: PathBuf::from(BASEDIR).join(genesis::FILENAME),
             address: SocketAddr::new(
                 IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)),
                 26658,
@@ -185,12 +193,13 @@ impl Config {
             .expect("safe because default has ledger");
         ledger_cfg.db = base_dir.join(DB_DIR);
         ledger_cfg.tendermint = base_dir.join(TENDERMINT_DIR);
+        ledger_cfg.genesis = base_dir.join(genesis::FILENAME);
         config.write(base_dir, replace)?;
         Ok(config)
     }
 
     // TODO add format in config instead and serialize it to that format
-    fn write(&self, base_dir: PathBuf, replace: bool) -> Result<()> {
+    pub fn write(&self, base_dir: PathBuf, replace: bool) -> Result<()> {
         create_dir_all(&base_dir).map_err(Error::FileError)?;
         let file_path = base_dir.join(FILENAME);
         if file_path.exists() && !replace {
@@ -1,25 +1,720 @@
 //! The parameters used for the chain's genesis
 
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use anoma_shared::ledger::parameters::{self, Parameters};
+use anoma_shared::types::address::{self, Address, ImplicitAddress};
+use anoma_shared::types::governance::{self, GovParams};
+use anoma_shared::types::key::ed25519::{self, PublicKey, PublicKeyHash};
+use anoma_shared::types::pos::{self, PosParams, ValidatorUpdate};
+use anoma_shared::types::token::{self, Amount, TokenMetadata};
+use anoma_shared::types::Key;
+use anoma_shared::vm;
+use borsh::BorshSerialize;
 #[cfg(feature = "dev")]
 use ed25519_dalek::Keypair;
-#[cfg(not(feature = "dev"))]
-use ed25519_dalek::PublicKey;
+#[cfg(feature = "dev")]
 use rand::prelude::ThreadRng;
+#[cfg(feature = "dev")]
 use rand::thread_rng;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
+use thiserror::Error;
 
-#[cfg(not(feature = "dev"))]
-#[derive(Debug)]
-pub struct Genesis {
-    pub validators: Vec<Validator>,
+use crate::node::ledger::storage::PersistentStorage;
+
+/// The default file name of the genesis file in the base directory
+pub const FILENAME: &str = "genesis.toml";
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Failed to read the genesis file {}: {1}", .0.to_string_lossy())]
+    ReadFile(PathBuf, std::io::Error),
+    #[error("Failed to parse the genesis file: {0}")]
+    Parse(toml::de::Error),
+    #[error("The genesis file {} doesn't exist", .0.to_string_lossy())]
+    MissingFile(PathBuf),
+    #[error("The genesis file is not valid:\n{}", format_errors(.0))]
+    Invalid(Vec<ValidationError>),
 }
 
-#[cfg(not(feature = "dev"))]
-#[derive(Debug)]
-pub struct Validator {
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// An error found in the genesis configuration
+#[derive(Error, Debug)]
+pub enum ValidationError {
+    #[error("The chain ID must not be empty")]
+    EmptyChainId,
+    #[error("Invalid protocol parameters: {0}")]
+    InvalidParameters(parameters::Error),
+    #[error("Invalid proof-of-stake parameters: {0}")]
+    InvalidPosParams(pos::Error),
+    #[error("Invalid governance parameters: {0}")]
+    InvalidGovParams(governance::Error),
+    #[error("There must be at least one validator")]
+    NoValidators,
+    #[error("Invalid public key {0}: {1}")]
+    InvalidPublicKey(String, String),
+    #[error("Invalid stake of the validator {0}: {1}")]
+    InvalidStake(String, token::Error),
+    #[error("The stake of the validator {0} must give it some voting power")]
+    ZeroVotingPower(String),
+    #[error("Invalid commission rate of the validator {0}: {1}")]
+    InvalidCommissionRate(String, pos::Error),
+    #[error("The validator {0} is declared more than once")]
+    DuplicateValidator(String),
+    #[error("The validator {0} is not a declared established account")]
+    UnknownValidatorAccount(String),
+    #[error("The staking token {0} must be declared")]
+    UndeclaredStakingToken(String),
+    #[error("Invalid address {0}: {1}")]
+    InvalidAddress(String, address::Error),
+    #[error("The address {0} is not an established address")]
+    NotEstablishedAddress(String),
+    #[error("The account {0} is declared more than once")]
+    DuplicateAccount(String),
+    #[error("Invalid validity predicate {0}: {1}")]
+    InvalidVpCode(String, String),
+    #[error("Invalid metadata of the token {0}: {1}")]
+    InvalidTokenMetadata(String, token::Error),
+    #[error("Invalid balance of the token {token} of {owner}: {error}")]
+    InvalidBalance {
+        token: String,
+        owner: String,
+        error: token::Error,
+    },
+    #[error(
+        "The owner {owner} of a balance of the token {token} is not a \
+         declared account"
+    )]
+    UnknownBalanceOwner { token: String, owner: String },
+    #[error("The supply of the token {0} overflows")]
+    SupplyOverflow(String),
+}
+
+fn format_errors(errors: &[ValidationError]) -> String {
+    errors
+        .iter()
+        .map(|error| format!("  - {}", error))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// The genesis file format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenesisConfig {
+    /// The ID of the chain
+    pub chain_id: String,
+    /// The initial protocol parameters
+    #[serde(default)]
+    pub parameters: Parameters,
+    /// The proof-of-stake system
+    #[serde(default)]
+    pub pos: PosConfig,
+    /// The governance system
+    #[serde(default)]
+    pub governance: GovConfig,
+    /// The initial validators with their self-bonded stake
+    #[serde(default)]
+    pub validators: Vec<ValidatorConfig>,
+    /// Established accounts with their validity predicates
+    #[serde(default)]
+    pub established_accounts: Vec<EstablishedAccountConfig>,
+    /// Implicit accounts, derived from their public keys
+    #[serde(default)]
+    pub implicit_accounts: Vec<ImplicitAccountConfig>,
+    /// Tokens with their balances
+    #[serde(default)]
+    pub tokens: Vec<TokenConfig>,
+}
+
+/// The genesis proof-of-stake configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PosConfig {
+    #[serde(default)]
+    pub params: PosParams,
+}
+
+/// The genesis governance configuration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GovConfig {
+    #[serde(default)]
+    pub params: GovParams,
+}
+
+/// A genesis validator
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidatorConfig {
+    /// Bech32m encoded address of the validator's established account
     pub address: String,
-    pub pk: PublicKey,
-    pub voting_power: u64,
+    /// Hex encoded ed25519 consensus public key
+    pub public_key: String,
+    /// Decimal amount of the staking token bonded by the validator to itself.
+    /// The validator's voting power is the number of whole tokens.
+    pub stake: String,
+    /// The share of the validator's rewards that it takes as its commission
+    /// in basis points
+    #[serde(default)]
+    pub commission_rate: u16,
+}
+
+/// A genesis established account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EstablishedAccountConfig {
+    /// Bech32m encoded established address
+    pub address: String,
+    /// Path to the account's validity predicate wasm
+    pub vp_code_path: String,
+    /// Hex encoded ed25519 public key of the account, if any
+    pub public_key: Option<String>,
+}
+
+/// A genesis implicit account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImplicitAccountConfig {
+    /// Hex encoded ed25519 public key from which the address is derived
+    pub public_key: String,
+    /// Path to the account's validity predicate wasm
+    pub vp_code_path: String,
+}
+
+/// A genesis token
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TokenConfig {
+    /// Bech32m encoded established address
+    pub address: String,
+    /// Path to the token's validity predicate wasm
+    pub vp_code_path: String,
+    pub symbol: String,
+    pub name: String,
+    #[serde(default = "default_decimals")]
+    pub decimals: u8,
+    /// Bech32m encoded address of the account allowed to mint the token, if
+    /// any
+    pub mint_authority: Option<String>,
+    /// Decimal balances of the token by their owners' addresses. The token's
+    /// supply is the sum of the balances.
+    #[serde(default)]
+    pub balances: BTreeMap<String, String>,
+}
+
+fn default_decimals() -> u8 {
+    token::DEFAULT_DECIMALS
+}
+
+/// Load the genesis configuration from the given file, if it exists.
+/// Otherwise, fall back to the development genesis.
+#[cfg(feature = "dev")]
+pub fn load(path: impl AsRef<Path>) -> Result<GenesisConfig> {
+    if path.as_ref().exists() {
+        GenesisConfig::load(path)
+    } else {
+        tracing::info!(
+            "No genesis file found in {}, using the development genesis",
+            path.as_ref().to_string_lossy()
+        );
+        Ok(dev_genesis())
+    }
+}
+
+/// Load the genesis configuration from the given file.
+#[cfg(not(feature = "dev"))]
+pub fn load(path: impl AsRef<Path>) -> Result<GenesisConfig> {
+    if path.as_ref().exists() {
+        GenesisConfig::load(path)
+    } else {
+        Err(Error::MissingFile(path.as_ref().to_path_buf()))
+    }
+}
+
+impl GenesisConfig {
+    /// Read the genesis configuration from a TOML file and validate it.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        let path = path.as_ref();
+        let contents = std::fs::read_to_string(path)
+            .map_err(|err| Error::ReadFile(path.to_path_buf(), err))?;
+        let config: Self = toml::from_str(&contents).map_err(Error::Parse)?;
+        let errors = config.validate();
+        if errors.is_empty() {
+            Ok(config)
+        } else {
+            Err(Error::Invalid(errors))
+        }
+    }
+
+    /// Check the genesis configuration and return all the errors found in it.
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = vec![];
+
+        if self.chain_id.trim().is_empty() {
+            errors.push(ValidationError::EmptyChainId);
+        }
+        if let Err(err) = self.parameters.validate() {
+            errors.push(ValidationError::InvalidParameters(err));
+        }
+
+        if let Err(err) = self.pos.params.validate() {
+            errors.push(ValidationError::InvalidPosParams(err));
+        }
+
+        if let Err(err) = self.governance.params.validate() {
+            errors.push(ValidationError::InvalidGovParams(err));
+        }
+
+        if self.validators.is_empty() {
+            errors.push(ValidationError::NoValidators);
+        }
+        let staking_token = self.staking_token();
+        let decimals = staking_token
+            .map(|config| config.decimals)
+            .unwrap_or(token::DEFAULT_DECIMALS);
+        let mut validators = HashSet::new();
+        for validator in &self.validators {
+            let is_account = self
+                .established_accounts
+                .iter()
+                .any(|account| account.address == validator.address);
+            if !is_account {
+                errors.push(ValidationError::UnknownValidatorAccount(
+                    validator.address.clone(),
+                ));
+            }
+            if let Err(err) = parse_public_key(&validator.public_key) {
+                errors.push(err);
+            }
+            match Amount::from_str_with_decimals(&validator.stake, decimals) {
+                Ok(stake) if pos::voting_power(stake) == 0 => errors.push(
+                    ValidationError::ZeroVotingPower(validator.address.clone()),
+                ),
+                Ok(_) => {}
+                Err(err) => errors.push(ValidationError::InvalidStake(
+                    validator.address.clone(),
+                    err,
+                )),
+            }
+            if let Err(err) =
+                pos::CommissionRate(validator.commission_rate).validate()
+            {
+                errors.push(ValidationError::InvalidCommissionRate(
+                    validator.address.clone(),
+                    err,
+                ));
+            }
+            if !validators.insert(&validator.address) {
+                errors.push(ValidationError::DuplicateValidator(
+                    validator.address.clone(),
+                ));
+            }
+        }
+        if !self.validators.is_empty() && staking_token.is_none() {
+            errors.push(ValidationError::UndeclaredStakingToken(
+                pos::staking_token().encode(),
+            ));
+        }
+
+        // All the declared accounts, which may own token balances
+        let mut accounts: HashSet<Address> = HashSet::new();
+        let mut declare =
+            |address: Address, errors: &mut Vec<ValidationError>| {
+                if !accounts.insert(address.clone()) {
+                    errors.push(ValidationError::DuplicateAccount(
+                        address.encode(),
+                    ));
+                }
+            };
+
+        for account in &self.established_accounts {
+            match parse_established_address(&account.address) {
+                Ok(address) => declare(address, &mut errors),
+                Err(err) => errors.push(err),
+            }
+            if let Err(err) = validate_vp_code(&account.vp_code_path) {
+                errors.push(err);
+            }
+            if let Some(pk) = &account.public_key {
+                if let Err(err) = parse_public_key(pk) {
+                    errors.push(err);
+                }
+            }
+        }
+
+        for account in &self.implicit_accounts {
+            match parse_public_key(&account.public_key) {
+                Ok(pk) => declare(implicit_address(pk), &mut errors),
+                Err(err) => errors.push(err),
+            }
+            if let Err(err) = validate_vp_code(&account.vp_code_path) {
+                errors.push(err);
+            }
+        }
+
+        for config in &self.tokens {
+            match parse_established_address(&config.address) {
+                Ok(address) => declare(address, &mut errors),
+                Err(err) => errors.push(err),
+            }
+            if let Err(err) = validate_vp_code(&config.vp_code_path) {
+                errors.push(err);
+            }
+            if let Err(err) = TokenMetadata::new(
+                &config.symbol,
+                &config.name,
+                config.decimals,
+            ) {
+                errors.push(ValidationError::InvalidTokenMetadata(
+                    config.address.clone(),
+                    err,
+                ));
+            }
+            if let Some(mint_authority) = &config.mint_authority {
+                if let Err(err) = Address::decode(mint_authority) {
+                    errors.push(ValidationError::InvalidAddress(
+                        mint_authority.clone(),
+                        err,
+                    ));
+                }
+            }
+        }
+
+        // The balances are checked once all the accounts are declared
+        for config in &self.tokens {
+            let mut supply = Some(Amount::default());
+            for (owner, amount) in &config.balances {
+                match Address::decode(owner) {
+                    Ok(address) if !accounts.contains(&address) => {
+                        errors.push(ValidationError::UnknownBalanceOwner {
+                            token: config.address.clone(),
+                            owner: owner.clone(),
+                        })
+                    }
+                    Ok(_) => {}
+                    Err(err) => errors.push(ValidationError::InvalidAddress(
+                        owner.clone(),
+                        err,
+                    )),
+                }
+                match Amount::from_str_with_decimals(amount, config.decimals) {
+                    Ok(amount) => {
+                        supply = supply
+                            .and_then(|supply| supply.checked_add(&amount))
+                    }
+                    Err(error) => {
+                        errors.push(ValidationError::InvalidBalance {
+                            token: config.address.clone(),
+                            owner: owner.clone(),
+                            error,
+                        })
+                    }
+                }
+            }
+            // the stakes are bonded tokens of the staking token
+            if staking_token.map(|token| &token.address)
+                == Some(&config.address)
+            {
+                for validator in &self.validators {
+                    if let Ok(stake) = Amount::from_str_with_decimals(
+                        &validator.stake,
+                        config.decimals,
+                    ) {
+                        supply = supply
+                            .and_then(|supply| supply.checked_add(&stake));
+                    }
+                }
+            }
+            if supply.is_none() {
+                errors.push(ValidationError::SupplyOverflow(
+                    config.address.clone(),
+                ));
+            }
+        }
+
+        errors
+    }
+
+    /// Get the configuration of the staking token, if it's declared.
+    fn staking_token(&self) -> Option<&TokenConfig> {
+        let staking_token = pos::staking_token();
+        self.tokens.iter().find(|config| {
+            Address::decode(&config.address).ok().as_ref()
+                == Some(&staking_token)
+        })
+    }
+
+    /// Write the genesis state into the storage and return the initial
+    /// validator set updates. The configuration must be valid.
+    pub fn init_storage(
+        &self,
+        storage: &mut PersistentStorage,
+    ) -> Vec<ValidatorUpdate> {
+        self.parameters.init_storage(storage);
+
+        // The validators' stakes are bonded to themselves
+        let decimals = self
+            .staking_token()
+            .map(|config| config.decimals)
+            .unwrap_or(token::DEFAULT_DECIMALS);
+        let mut total_stake = Amount::default();
+        let mut total_bonds = pos::TotalBonds::new();
+        let mut consensus_keys = BTreeMap::new();
+        for validator in &self.validators {
+            let address = Address::decode(&validator.address)
+                .expect("The genesis must be valid");
+            let consensus_key = parse_public_key(&validator.public_key)
+                .expect("The genesis must be valid");
+            let stake =
+                Amount::from_str_with_decimals(&validator.stake, decimals)
+                    .expect("The genesis must be valid");
+            total_stake = total_stake
+                .checked_add(&stake)
+                .expect("The genesis must be valid");
+            write(storage, &pos::consensus_key_key(&address), &consensus_key);
+            write(
+                storage,
+                &pos::commission_rate_key(&address),
+                pos::CommissionRate(validator.commission_rate),
+            );
+            let mut bonds = pos::Bonds::new();
+            bonds.insert(address.clone(), stake);
+            write(storage, &pos::bond_key(&address), bonds);
+            total_bonds.insert(address.clone(), stake);
+            consensus_keys.insert(address, consensus_key);
+        }
+
+        for account in &self.established_accounts {
+            let address = Address::decode(&account.address)
+                .expect("The genesis must be valid");
+            write_vp(storage, &address, &account.vp_code_path);
+            if let Some(pk) = &account.public_key {
+                let pk =
+                    parse_public_key(pk).expect("The genesis must be valid");
+                write(storage, &ed25519::pk_key(&address), pk);
+            }
+        }
+
+        for account in &self.implicit_accounts {
+            let pk = parse_public_key(&account.public_key)
+                .expect("The genesis must be valid");
+            let address = implicit_address(pk.clone());
+            write_vp(storage, &address, &account.vp_code_path);
+            write(storage, &ed25519::pk_key(&address), pk);
+        }
+
+        for config in &self.tokens {
+            let token = Address::decode(&config.address)
+                .expect("The genesis must be valid");
+            write_vp(storage, &token, &config.vp_code_path);
+            let metadata = TokenMetadata::new(
+                &config.symbol,
+                &config.name,
+                config.decimals,
+            )
+            .expect("The genesis must be valid");
+            write(storage, &token::registry_key(&token), &metadata);
+            write(storage, &token::metadata_key(&token), metadata);
+            if let Some(mint_authority) = &config.mint_authority {
+                let mint_authority = Address::decode(mint_authority)
+                    .expect("The genesis must be valid");
+                write(
+                    storage,
+                    &token::mint_authority_key(&token),
+                    mint_authority,
+                );
+            }
+
+            // the bonded stakes are a part of the staking token's supply
+            let mut supply = if token == pos::staking_token() {
+                total_stake
+            } else {
+                Amount::default()
+            };
+            for (owner, amount) in &config.balances {
+                let owner =
+                    Address::decode(owner).expect("The genesis must be valid");
+                let amount =
+                    Amount::from_str_with_decimals(amount, config.decimals)
+                        .expect("The genesis must be valid");
+                supply = supply
+                    .checked_add(&amount)
+                    .expect("The genesis must be valid");
+                write(storage, &token::balance_key(&token, &owner), amount);
+            }
+            write(storage, &token::supply_key(&token), supply);
+        }
+
+        write(storage, &pos::params_key(), &self.pos.params);
+        write(
+            storage,
+            &token::balance_key(&pos::staking_token(), &pos::pos_address()),
+            total_stake,
+        );
+        write(storage, &pos::total_bonds_key(), &total_bonds);
+        let validator_set = pos::compute_validator_set(
+            &self.pos.params,
+            &total_bonds,
+            |validator| consensus_keys.get(validator).cloned(),
+        );
+        write(storage, &pos::validator_set_key(), &validator_set);
+
+        write(storage, &governance::params_key(), &self.governance.params);
+
+        pos::validator_set_updates(&Default::default(), &validator_set)
+    }
+}
+
+fn write(
+    storage: &mut PersistentStorage,
+    key: &Key,
+    value: impl BorshSerialize,
+) {
+    let bytes = value
+        .try_to_vec()
+        .expect("Encoding a genesis value shouldn't fail");
+    storage.write(key, bytes).unwrap_or_else(|err| {
+        panic!("Unable to write genesis {}: {}", key, err)
+    });
+}
+
+fn write_vp(storage: &mut PersistentStorage, address: &Address, path: &str) {
+    let vp_code = std::fs::read(path)
+        .unwrap_or_else(|err| panic!("Cannot load the VP {}: {}", path, err));
+    let key = Key::validity_predicate(address).expect("expected VP key");
+    storage
+        .write(&key, vp_code)
+        .unwrap_or_else(|err| panic!("Unable to write genesis VP: {}", err));
+}
+
+fn parse_dalek_public_key(
+    pk: &str,
+) -> std::result::Result<ed25519_dalek::PublicKey, ValidationError> {
+    let invalid =
+        |err: String| ValidationError::InvalidPublicKey(pk.to_owned(), err);
+    let bytes = hex::decode(pk).map_err(|err| invalid(err.to_string()))?;
+    ed25519_dalek::PublicKey::from_bytes(&bytes)
+        .map_err(|err| invalid(err.to_string()))
+}
+
+pub(crate) fn parse_public_key(
+    pk: &str,
+) -> std::result::Result<PublicKey, ValidationError> {
+    parse_dalek_public_key(pk).map(PublicKey::from)
+}
+
+fn parse_established_address(
+    address: &str,
+) -> std::result::Result<Address, ValidationError> {
+    match Address::decode(address) {
+        Ok(address @ Address::Established(_)) => Ok(address),
+        Ok(_) => {
+            Err(ValidationError::NotEstablishedAddress(address.to_owned()))
+        }
+        Err(err) => {
+            Err(ValidationError::InvalidAddress(address.to_owned(), err))
+        }
+    }
+}
+
+pub(crate) fn implicit_address(pk: PublicKey) -> Address {
+    Address::Implicit(ImplicitAddress::Ed25519(PublicKeyHash::from(pk)))
+}
+
+fn validate_vp_code(path: &str) -> std::result::Result<(), ValidationError> {
+    let invalid =
+        |err: String| ValidationError::InvalidVpCode(path.to_owned(), err);
+    let vp_code =
+        std::fs::read(path).map_err(|err| invalid(err.to_string()))?;
+    vm::validate_untrusted_wasm(vp_code, vm::WasmKind::Vp)
+        .map_err(|err| invalid(err.to_string()))
+}
+
+/// The established account of the development validator
+#[cfg(feature = "dev")]
+pub const DEV_VALIDATOR: &str = "a1qq5qqqqqx3pnvwphxyunxv2px9zrvd6xxucrss6xg56rj33kgc6rv3phxerry3fexveyvvpndu4nar";
+
+/// The genesis used for development when there is no genesis file, with the
+/// testing accounts and tokens.
+#[cfg(feature = "dev")]
+pub fn dev_genesis() -> GenesisConfig {
+    use crate::wallet;
+
+    const TOKEN_VP: &str = "wasm/vps/vp_token/vp.wasm";
+    const USER_VP: &str = "wasm/vps/vp_user/vp.wasm";
+
+    let pk_hex = |pk: &ed25519_dalek::PublicKey| hex::encode(pk.to_bytes());
+
+    // temporary account addresses for testing, generated by the address.rs
+    // module
+    let users = [
+        "a1qq5qqqqqg4znssfsgcurjsfhgfpy2vjyxy6yg3z98pp5zvp5xgersvfjxvcnx3f4xycrzdfkak0xhx",
+        "a1qq5qqqqqxv6yydz9xc6ry33589q5x33eggcnjs2xx9znydj9xuens3phxppnwvzpg4rrqdpswve4n9",
+        "a1qq5qqqqqxsuygd2x8pq5yw2ygdryxs6xgsmrsdzx8pryxv34gfrrssfjgccyg3zpxezrqd2y2s3g5s",
+    ];
+    let mut established_accounts: Vec<EstablishedAccountConfig> = users
+        .iter()
+        .map(|user| EstablishedAccountConfig {
+            address: user.to_string(),
+            vp_code_path: USER_VP.to_owned(),
+            public_key: Some(pk_hex(&wallet::key_of(user).public)),
+        })
+        .collect();
+    // Temporary for testing, we have a fixed matchmaker account. This account
+    // has a public key for signing matchmaker txs and verifying their
+    // signatures in its VP. The VP is the same as the user's VP, which simply
+    // checks the signature. We could consider using the same key as the
+    // intent gossip's p2p key.
+    established_accounts.push(EstablishedAccountConfig {
+        address: address::matchmaker().encode(),
+        vp_code_path: USER_VP.to_owned(),
+        public_key: Some(pk_hex(&wallet::matchmaker_keypair().public)),
+    });
+    // The development validator's account uses its consensus key
+    let validator = genesis().validator;
+    let validator_account = DEV_VALIDATOR.to_owned();
+    established_accounts.push(EstablishedAccountConfig {
+        address: validator_account.clone(),
+        vp_code_path: USER_VP.to_owned(),
+        public_key: Some(pk_hex(&validator.keypair.public)),
+    });
+
+    let tokens = vec![
+        (address::xan(), "XAN", "Anoma"),
+        (address::btc(), "BTC", "Bitcoin"),
+        (address::eth(), "ETH", "Ethereum"),
+        (address::dot(), "DOT", "Polkadot"),
+        (address::schnitzel(), "SCHNITZEL", "Schnitzel"),
+        (address::apfel(), "APFEL", "Apfel"),
+        (address::kartoffel(), "KARTOFFEL", "Kartoffel"),
+    ]
+    .into_iter()
+    .map(|(token, symbol, name)| TokenConfig {
+        address: token.encode(),
+        vp_code_path: TOKEN_VP.to_owned(),
+        symbol: symbol.to_owned(),
+        name: name.to_owned(),
+        decimals: token::DEFAULT_DECIMALS,
+        mint_authority: None,
+        balances: users
+            .iter()
+            .map(|user| (user.to_string(), "1000000".to_owned()))
+            .collect(),
+    })
+    .collect();
+
+    GenesisConfig {
+        chain_id: "anoma-devchain".to_owned(),
+        parameters: Parameters::default(),
+        pos: PosConfig::default(),
+        governance: GovConfig::default(),
+        validators: vec![ValidatorConfig {
+            address: validator_account,
+            public_key: pk_hex(&validator.keypair.public),
+            stake: validator.voting_power.to_string(),
+            commission_rate: 0,
+        }],
+        established_accounts,
+        implicit_accounts: vec![],
+        tokens,
+    }
 }
 
 #[cfg(feature = "dev")]
@@ -67,16 +762,14 @@ pub fn genesis() -> Genesis {
     Genesis { validator }
 }
 
+#[cfg(feature = "dev")]
 impl Validator {
     // Generates a new validator
     #[allow(dead_code)]
     fn new() -> Self {
         let mut rng: ThreadRng = thread_rng();
         let keypair = Keypair::generate(&mut rng);
-        let mut hasher = Sha256::new();
-        hasher.update(keypair.public.to_bytes());
-        // hex of the first 40 chars of the hash
-        let address = format!("{:.40X}", hasher.finalize());
+        let address = validator_address(&keypair.public);
         Validator {
             address,
             keypair,
@@ -84,3 +777,69 @@ impl Validator {
         }
     }
 }
+
+/// The Tendermint address of a validator, derived from its consensus key.
+pub fn validator_address(pk: &ed25519_dalek::PublicKey) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(pk.to_bytes());
+    // hex of the first 40 chars of the hash
+    format!("{:.40X}", hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_genesis() {
+        let config: GenesisConfig = toml::from_str(
+            r#"
+chain_id = ""
+
+[governance.params]
+voting_period = 0
+
+[[validators]]
+address = "a1qq5qqqqqg4znssfsgcurjsfhgfpy2vjyxy6yg3z98pp5zvp5xgersvfjxvcnx3f4xycrzdfkak0xhx"
+public_key = "not hex"
+stake = "0.5"
+commission_rate = 10001
+
+[[implicit_accounts]]
+public_key = "5e704c4e46265e1ccc87505149f79b9d2e414d01a4e3806dfc65f0a73901c1d0"
+vp_code_path = "does/not/exist.wasm"
+
+[[tokens]]
+address = "a1qq5qqqqqxuc5gvz9gycryv3sgye5v3j9gvurjv34g9prsd6x8qu5xs2ygdzrzsf38q6rss33xf42f3"
+vp_code_path = "does/not/exist.wasm"
+symbol = "XAN"
+name = "Anoma"
+
+[tokens.balances]
+a1qq5qqqqqg4znssfsgcurjsfhgfpy2vjyxy6yg3z98pp5zvp5xgersvfjxvcnx3f4xycrzdfkak0xhx = "1.5"
+"#,
+        )
+        .expect("The genesis should parse");
+
+        let errors = config.validate();
+        assert!(matches!(errors[0], ValidationError::EmptyChainId));
+        assert!(matches!(errors[1], ValidationError::InvalidGovParams(_)));
+        assert!(matches!(
+            errors[2],
+            ValidationError::UnknownValidatorAccount(_)
+        ));
+        assert!(matches!(errors[3], ValidationError::InvalidPublicKey(_, _)));
+        assert!(matches!(errors[4], ValidationError::ZeroVotingPower(_)));
+        assert!(matches!(
+            errors[5],
+            ValidationError::InvalidCommissionRate(_, _)
+        ));
+        assert!(matches!(errors[6], ValidationError::InvalidVpCode(_, _)));
+        assert!(matches!(errors[7], ValidationError::InvalidVpCode(_, _)));
+        assert!(matches!(
+            errors[8],
+            ValidationError::UnknownBalanceOwner { .. }
+        ));
+        assert_eq!(errors.len(), 9);
+    }
+}
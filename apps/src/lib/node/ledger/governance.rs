@@ -0,0 +1,202 @@
+//! The tallying and execution of the governance proposals applied by the
+//! protocol.
+
+use anoma_shared::ledger::gas::BlockGasMeter;
+use anoma_shared::ledger::parameters::{self, Parameters};
+use anoma_shared::ledger::storage::write_log::WriteLog;
+use anoma_shared::types::governance::{
+    self, GovParams, Proposal, ProposalContent, ProposalId, ProposalResult,
+    ProposalStatus, Tally, Vote,
+};
+use anoma_shared::types::pos::{self, Bonds, EpochState, TotalBonds};
+use anoma_shared::types::token::{self, Amount};
+use anoma_shared::types::BlockHeight;
+use anoma_shared::vm::wasm::runner::TxRunner;
+
+use super::pos::{prefix_keys, read, read_opt, write};
+use super::storage::PersistentStorage;
+
+/// At the beginning of the first block of an epoch, tally the proposals whose
+/// voting period is over, settle their deposits and execute the passed ones.
+/// The votes are tallied from the last committed block, which includes all
+/// the votes cast in the voting period.
+pub fn begin_block(storage: &mut PersistentStorage, height: BlockHeight) {
+    let state = match read_opt::<EpochState>(storage, &pos::epoch_key()) {
+        Some(state) if state.start_height == height => state,
+        // the epoch hasn't changed in this block
+        _ => return,
+    };
+    let params: GovParams = read(storage, &governance::params_key());
+    let next_id: ProposalId =
+        read(storage, &governance::next_proposal_id_key());
+    for id in 0..next_id {
+        if read_opt::<ProposalResult>(storage, &governance::result_key(id))
+            .is_some()
+        {
+            continue;
+        }
+        let proposal: Proposal =
+            match read_opt(storage, &governance::proposal_key(id)) {
+                Some(proposal) => proposal,
+                None => continue,
+            };
+        if proposal.end_epoch > state.epoch {
+            continue;
+        }
+        let result = tally(storage, &params, id);
+        settle_deposit(storage, &proposal, result.status);
+        let status = match result.status {
+            ProposalStatus::Passed => {
+                execute(storage, height, id, proposal.content)
+            }
+            status => status,
+        };
+        let result = ProposalResult { status, ..result };
+        tracing::info!("Proposal {} {}: {:?}", id, status, result.tally);
+        write(storage, &governance::result_key(id), &result);
+    }
+}
+
+/// Count the votes on the proposal weighted by the voters' bonds.
+fn tally(
+    storage: &PersistentStorage,
+    params: &GovParams,
+    id: ProposalId,
+) -> ProposalResult {
+    let votes: Vec<_> = prefix_keys(storage, &governance::votes_prefix(id))
+        .into_iter()
+        .filter_map(|key| {
+            let (_, voter) = governance::is_vote_key(&key)?;
+            let voter = voter.clone();
+            Some((key, voter))
+        })
+        .collect();
+    let votes = votes.into_iter().filter_map(|(key, voter)| {
+        let vote: Vote = read_opt(storage, &key)?;
+        let bonds: Bonds = read(storage, &pos::bond_key(&voter));
+        Some((vote, total_stake(bonds.values())))
+    });
+    let tally = Tally::count(votes)
+        .expect("The votes cannot exceed the staking token's supply");
+    let total_bonds: TotalBonds = read(storage, &pos::total_bonds_key());
+    ProposalResult {
+        status: tally.status(params, total_stake(total_bonds.values())),
+        tally,
+    }
+}
+
+/// Sum up the bonded amounts.
+fn total_stake<'a>(amounts: impl Iterator<Item = &'a Amount>) -> Amount {
+    let mut total = Amount::default();
+    for amount in amounts {
+        total
+            .receive(amount)
+            .expect("The bonds cannot exceed the staking token's supply");
+    }
+    total
+}
+
+/// Return the deposit to the proposal's author if the quorum has been reached
+/// or burn it otherwise.
+fn settle_deposit(
+    storage: &mut PersistentStorage,
+    proposal: &Proposal,
+    status: ProposalStatus,
+) {
+    let token = pos::staking_token();
+    let gov_balance_key =
+        token::balance_key(&token, &governance::governance_address());
+    let mut gov_balance: Amount = read(storage, &gov_balance_key);
+    gov_balance
+        .spend(&proposal.deposit)
+        .expect("The governance balance holds all the deposits");
+    write(storage, &gov_balance_key, &gov_balance);
+
+    if status == ProposalStatus::NoQuorum {
+        let supply_key = token::supply_key(&token);
+        let mut supply: Amount = read(storage, &supply_key);
+        supply
+            .spend(&proposal.deposit)
+            .expect("The supply includes all the deposits");
+        write(storage, &supply_key, &supply);
+    } else {
+        let author_balance_key = token::balance_key(&token, &proposal.author);
+        let mut author_balance: Amount = read(storage, &author_balance_key);
+        author_balance
+            .receive(&proposal.deposit)
+            .expect("The author's balance shouldn't overflow");
+        write(storage, &author_balance_key, &author_balance);
+    }
+}
+
+/// Apply a passed proposal's content. Returns the proposal's final status.
+fn execute(
+    storage: &mut PersistentStorage,
+    height: BlockHeight,
+    id: ProposalId,
+    content: ProposalContent,
+) -> ProposalStatus {
+    match content {
+        ProposalContent::Text => {}
+        ProposalContent::Parameters(params) => {
+            if let Err(err) = params.validate() {
+                tracing::error!(
+                    "Proposal {} has invalid parameters: {}",
+                    id,
+                    err
+                );
+                return ProposalStatus::Failed;
+            }
+            write::<Parameters>(
+                storage,
+                &parameters::parameters_key(),
+                &params,
+            );
+        }
+        ProposalContent::PosParams(params) => {
+            if let Err(err) = params.validate() {
+                tracing::error!(
+                    "Proposal {} has invalid PoS parameters: {}",
+                    id,
+                    err
+                );
+                return ProposalStatus::Failed;
+            }
+            write(storage, &pos::params_key(), &params);
+        }
+        ProposalContent::Code(code) => {
+            // the proposal's code is not checked by any validity predicates
+            let mut write_log = WriteLog::default();
+            let params = Parameters::read(storage);
+            let mut gas_meter = BlockGasMeter::new(&params);
+            let result = TxRunner::new().run(
+                storage,
+                &params,
+                &mut write_log,
+                &mut gas_meter,
+                code,
+                vec![],
+            );
+            if let Err(err) = result {
+                tracing::error!("Proposal {} code failed: {}", id, err);
+                return ProposalStatus::Failed;
+            }
+            write_log.commit_tx();
+            write_log
+                .commit_block(storage)
+                .expect("Unable to commit the proposal's changes");
+        }
+        ProposalContent::Upgrade(plan) => {
+            if let Err(err) = plan.validate(height) {
+                tracing::error!(
+                    "Proposal {} has an invalid upgrade plan: {}",
+                    id,
+                    err
+                );
+                return ProposalStatus::Failed;
+            }
+            plan.write(storage);
+        }
+    }
+    ProposalStatus::Passed
+}
@@ -1,26 +1,33 @@
+mod governance;
+pub mod network;
+mod pos;
 pub mod protocol;
 pub mod storage;
 mod tendermint;
+mod upgrade;
 
 use std::convert::TryFrom;
-use std::path::Path;
 use std::sync::mpsc;
 
 use anoma_shared::bytes::ByteBuf;
 use anoma_shared::ledger::gas::{self, BlockGasMeter};
+use anoma_shared::ledger::parameters::Parameters;
 use anoma_shared::ledger::storage::write_log::WriteLog;
 use anoma_shared::ledger::storage::MerkleRoot;
-use anoma_shared::types::key::ed25519::PublicKey;
-use anoma_shared::types::token::Amount;
-use anoma_shared::types::{
-    address, key, token, Address, BlockHash, BlockHeight, Key,
-};
-use borsh::BorshSerialize;
+use anoma_shared::ledger::upgrade::UpgradePlan;
+use anoma_shared::types::pos::ValidatorUpdate;
+use anoma_shared::types::{BlockHash, BlockHeight, Key};
 use thiserror::Error;
 
-use self::tendermint::{AbciMsg, AbciReceiver};
+use self::tendermint::{AbciMsg, AbciReceiver, QueryError, QueryResponse};
+use crate::config;
+use crate::genesis::{self, GenesisConfig};
 use crate::proto::{self, Tx};
-use crate::{config, wallet};
+
+/// The response code of a failed ABCI query
+pub const QUERY_ERROR_CODE: u32 = 1;
+/// The response code of an ABCI query of a storage key that has no value
+pub const QUERY_NO_VALUE_CODE: u32 = 2;
 
 #[derive(Error, Debug)]
 pub enum Error {
@@ -36,17 +43,61 @@ pub enum Error {
     TxDecodingError(proto::Error),
     #[error("Error trying to apply a transaction: {0}")]
     TxError(protocol::Error),
+    #[error("Invalid storage key: {0}")]
+    InvalidStorageKey(String),
+    #[error("No value found for the storage key {0}")]
+    NoValueFound(Key),
+    #[error("Unknown query path {0}")]
+    UnknownQueryPath(String),
+    #[error("Genesis error: {0}")]
+    Genesis(genesis::Error),
+    #[error(
+        "The chain ID {genesis} in the genesis doesn't match Tendermint's \
+         chain ID {tendermint}"
+    )]
+    ChainIdMismatch { genesis: String, tendermint: String },
+    #[error(
+        "The upgrade {name} at height {height} is not supported by this \
+         binary, restart the node with a binary that supports it"
+    )]
+    UnsupportedUpgrade { name: String, height: u64 },
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
 
+impl From<Error> for QueryError {
+    fn from(err: Error) -> Self {
+        let code = match err {
+            Error::NoValueFound(_) => QUERY_NO_VALUE_CODE,
+            _ => QUERY_ERROR_CODE,
+        };
+        QueryError {
+            code,
+            log: err.to_string(),
+        }
+    }
+}
+
 pub fn run(config: config::Ledger) -> Result<()> {
+    let genesis = genesis::load(&config.genesis).map_err(Error::Genesis)?;
+    let chain_id = genesis.chain_id.clone();
     // open a channel between ABCI (the sender) and the shell (the receiver)
     let (sender, receiver) = mpsc::channel();
-    let shell = Shell::new(receiver, &config.db);
+    let storage = if config.in_memory {
+        // the ledger's state is lost on restart, so Tendermint has to start
+        // from the genesis too
+        tendermint::reset_state(&config);
+        storage::open_in_memory()
+    } else {
+        storage::open(&config.db)
+    };
+    let mut shell = Shell::new(receiver, storage, genesis);
+    // don't start the node if this binary cannot process the next block
+    shell.last_state();
+    shell.check_upgrade()?;
     // Run Tendermint ABCI server in another thread
-    let _tendermint_handle = std::thread::spawn(move || {
-        if let Err(err) = tendermint::run(sender.clone(), config) {
+    let tendermint_handle = std::thread::spawn(move || {
+        if let Err(err) = tendermint::run(sender.clone(), chain_id, config) {
             tracing::error!(
                 "Failed to start-up a Tendermint node with {}",
                 err
@@ -54,7 +105,14 @@ pub fn run(config: config::Ledger) -> Result<()> {
             sender.send(AbciMsg::Terminate).unwrap();
         }
     });
-    shell.run()
+    let result = shell.run();
+    if let Err(Error::UnsupportedUpgrade { .. }) = &result {
+        // halt the Tendermint node too, so that it doesn't process blocks at
+        // the upgrade height
+        tendermint::shutdown();
+        let _ = tendermint_handle.join();
+    }
+    result
 }
 
 pub fn reset(config: config::Ledger) -> Result<()> {
@@ -73,8 +131,13 @@ pub fn reset(config: config::Ledger) -> Result<()> {
 pub struct Shell {
     abci: AbciReceiver,
     storage: storage::PersistentStorage,
+    genesis: GenesisConfig,
     gas_meter: BlockGasMeter,
+    /// The protocol parameters of the current block
+    parameters: Parameters,
     write_log: WriteLog,
+    /// The time of the current block in seconds since the Unix epoch
+    block_time: u64,
 }
 
 #[derive(Clone, Debug)]
@@ -87,99 +150,19 @@ pub enum MempoolTxType {
 }
 
 impl Shell {
-    pub fn new(abci: AbciReceiver, db_path: impl AsRef<Path>) -> Self {
-        let mut storage = storage::open(db_path);
-
-        let token_vp = std::fs::read("wasm/vps/vp_token/vp.wasm")
-            .expect("cannot load token VP");
-        let user_vp = std::fs::read("wasm/vps/vp_user/vp.wasm")
-            .expect("cannot load user VP");
-
-        // TODO load initial accounts from genesis
-
-        // temporary account addresses for testing, generated by the address.rs
-        // module
-        let alberto = Address::decode("a1qq5qqqqqg4znssfsgcurjsfhgfpy2vjyxy6yg3z98pp5zvp5xgersvfjxvcnx3f4xycrzdfkak0xhx")
-            .expect("The genesis address shouldn't fail decoding");
-        let bertha = Address::decode("a1qq5qqqqqxv6yydz9xc6ry33589q5x33eggcnjs2xx9znydj9xuens3phxppnwvzpg4rrqdpswve4n9")
-        .expect("The genesis address shouldn't fail decoding");
-        let christel = Address::decode("a1qq5qqqqqxsuygd2x8pq5yw2ygdryxs6xgsmrsdzx8pryxv34gfrrssfjgccyg3zpxezrqd2y2s3g5s")
-            .expect("The genesis address shouldn't fail decoding");
-        let users = vec![alberto, bertha, christel];
-
-        let tokens = vec![
-            address::xan(),
-            address::btc(),
-            address::eth(),
-            address::dot(),
-            address::schnitzel(),
-            address::apfel(),
-            address::kartoffel(),
-        ];
-
-        for token in &tokens {
-            // default tokens VPs for testing
-            let key = Key::validity_predicate(&token).expect("expected VP key");
-            storage
-                .write(&key, token_vp.to_vec())
-                .expect("Unable to write token VP");
-        }
-
-        for user in &users {
-            // default user VPs for testing
-            let vp_key =
-                Key::validity_predicate(&user).expect("expected VP key");
-            storage
-                .write(&vp_key, user_vp.to_vec())
-                .expect("Unable to write user VP");
-
-            // default user's tokens for testing
-            for token in &tokens {
-                let token_key = token::balance_key(&token, &user);
-                storage
-                    .write(
-                        &token_key,
-                        Amount::whole(1_000_000)
-                            .try_to_vec()
-                            .expect("encode token amount"),
-                    )
-                    .expect("Unable to set genesis balance");
-            }
-
-            // default user's public keys for testing
-            let pk_key = key::ed25519::pk_key(&user);
-            let pk = PublicKey::from(wallet::key_of(user.encode()).public);
-            storage
-                .write(&pk_key, pk.try_to_vec().expect("encode public key"))
-                .expect("Unable to set genesis user public key");
-        }
-
-        // Temporary for testing, we have a fixed matchmaker account.  This
-        // account has a public key for signing matchmaker txs and verifying
-        // their signatures in its VP. The VP is the same as the user's VP,
-        // which simply checks the signature. We could consider using the same
-        // key as the intent gossip's p2p key.
-        let matchmaker = address::matchmaker();
-        let matchmaker_pk = key::ed25519::pk_key(&matchmaker);
-        storage
-            .write(
-                &matchmaker_pk,
-                wallet::matchmaker_pk()
-                    .try_to_vec()
-                    .expect("encode public key"),
-            )
-            .expect("Unable to set genesis user public key");
-        let matchmaker_vp =
-            Key::validity_predicate(&matchmaker).expect("expected VP key");
-        storage
-            .write(&matchmaker_vp, user_vp.to_vec())
-            .expect("Unable to write matchmaker VP");
-
+    pub fn new(
+        abci: AbciReceiver,
+        storage: storage::PersistentStorage,
+        genesis: GenesisConfig,
+    ) -> Self {
         Self {
             abci,
             storage,
+            genesis,
             gas_meter: BlockGasMeter::default(),
+            parameters: Parameters::default(),
             write_log: WriteLog::default(),
+            block_time: 0,
         }
     }
 
@@ -195,8 +178,8 @@ impl Shell {
                     })?
                 }
                 AbciMsg::InitChain { reply, chain_id } => {
-                    self.init_chain(chain_id)?;
-                    reply.send(()).map_err(|e| {
+                    let validators = self.init_chain(chain_id)?;
+                    reply.send(validators).map_err(|e| {
                         Error::AbciChannelSendError(format!("InitChain {}", e))
                     })?
                 }
@@ -215,8 +198,10 @@ impl Shell {
                     reply,
                     hash,
                     height,
+                    time,
+                    misbehavior,
                 } => {
-                    self.begin_block(hash, height);
+                    self.begin_block(hash, height, time, misbehavior);
                     reply.send(()).map_err(|e| {
                         Error::AbciChannelSendError(format!("BeginBlock {}", e))
                     })?
@@ -229,8 +214,8 @@ impl Shell {
                     })?
                 }
                 AbciMsg::EndBlock { reply, height } => {
-                    self.end_block(height);
-                    reply.send(()).map_err(|e| {
+                    let updates = self.end_block(height);
+                    reply.send(updates).map_err(|e| {
                         Error::AbciChannelSendError(format!("EndBlock {}", e))
                     })?
                 }
@@ -241,7 +226,9 @@ impl Shell {
                             "CommitBlock {}",
                             e
                         ))
-                    })?
+                    })?;
+                    // halt after the last block before an unsupported upgrade
+                    self.check_upgrade()?
                 }
                 AbciMsg::AbciQuery {
                     reply,
@@ -250,18 +237,28 @@ impl Shell {
                     height: _,
                     prove: _,
                 } => {
-                    if path == "dry_run_tx" {
-                        let result = self
-                            .dry_run_tx(&data)
-                            .map_err(|e| format!("{}", e));
-
-                        reply.send(result).map_err(|e| {
-                            Error::AbciChannelSendError(format!(
-                                "ApplyTx {}",
-                                e
-                            ))
-                        })?
+                    let result = match path.as_str() {
+                        "dry_run_tx" => {
+                            self.dry_run_tx(&data).map(|result| QueryResponse {
+                                info: result.to_string(),
+                                value: result.initialized_accounts_data(),
+                            })
+                        }
+                        "value" => {
+                            self.read_storage_value(&data).map(|value| {
+                                QueryResponse {
+                                    info: String::default(),
+                                    value,
+                                }
+                            })
+                        }
+                        _ => Err(Error::UnknownQueryPath(path)),
                     }
+                    .map_err(QueryError::from);
+
+                    reply.send(result).map_err(|e| {
+                        Error::AbciChannelSendError(format!("AbciQuery {}", e))
+                    })?
                 }
                 AbciMsg::Terminate => {
                     tracing::info!("Shutting down Anoma node");
@@ -274,10 +271,22 @@ impl Shell {
 }
 
 impl Shell {
-    pub fn init_chain(&mut self, chain_id: String) -> Result<()> {
+    /// Initialize the chain's state from the genesis and return the initial
+    /// validator set.
+    pub fn init_chain(
+        &mut self,
+        chain_id: String,
+    ) -> Result<Vec<ValidatorUpdate>> {
+        if chain_id != self.genesis.chain_id {
+            return Err(Error::ChainIdMismatch {
+                genesis: self.genesis.chain_id.clone(),
+                tendermint: chain_id,
+            });
+        }
         self.storage
             .set_chain_id(&chain_id)
-            .map_err(Error::StorageError)
+            .map_err(Error::StorageError)?;
+        Ok(self.genesis.init_storage(&mut self.storage))
     }
 
     /// Validate a transaction request. On success, the transaction will
@@ -299,12 +308,135 @@ impl Shell {
     ) -> (i64, Result<protocol::TxResult>) {
         let result = protocol::apply_tx(
             tx_bytes,
+            &self.parameters,
             &mut self.gas_meter,
             &mut self.write_log,
             &self.storage,
         )
         .map_err(Error::TxError);
+        self.finalize_tx(result)
+    }
 
+    /// Apply the transactions of a block. The transactions are executed
+    /// optimistically in parallel, each against its own fork of the block's
+    /// write log, which shares the block's modifications without copying
+    /// them. Then, in the block order, the result of a transaction is merged
+    /// into the block's write log if it hasn't read any key written by the
+    /// transactions before it, otherwise the transaction is applied again.
+    /// This produces the same state as applying the transactions one after
+    /// another with [`Shell::apply_tx`].
+    ///
+    /// Note that with the storage deposits enabled, every transaction that
+    /// changes the storage usage reads and writes the escrow's balance, so
+    /// these transactions conflict with each other and are applied again one
+    /// after another.
+    ///
+    /// This is experimental and only built for tests, because Tendermint's
+    /// ABCI 0.34 delivers the transactions of a block one at a time and
+    /// requires the result of each before the next one, so the node applies
+    /// them with [`Shell::apply_tx`].
+    #[cfg(test)]
+    pub fn apply_txs(
+        &mut self,
+        txs: &[Vec<u8>],
+    ) -> Vec<(i64, Result<protocol::TxResult>)> {
+        use std::collections::HashSet;
+
+        use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
+
+        let base = self.write_log.fork();
+        let parameters = &self.parameters;
+        let storage = &self.storage;
+        let speculative_results: Vec<_> = txs
+            .par_iter()
+            .map(|tx_bytes| {
+                let mut gas_meter = BlockGasMeter::new(parameters);
+                let mut write_log = base.fork();
+                let result = protocol::apply_tx(
+                    tx_bytes,
+                    parameters,
+                    &mut gas_meter,
+                    &mut write_log,
+                    storage,
+                );
+                (result, write_log)
+            })
+            .collect();
+
+        // The keys written by the applied transactions
+        let mut written_keys: HashSet<Key> = HashSet::new();
+        txs.iter()
+            .zip(speculative_results)
+            .map(|(tx_bytes, (result, fork))| {
+                // A failed transaction leaves its gas to the next
+                // transaction, which then has to be applied again
+                let is_clean = self.gas_meter.get_current_transaction_gas()
+                    == 0
+                    && self.write_log.get_keys().is_empty();
+                let is_conflicting = (fork.has_new_addresses(&base)
+                    && self.write_log.has_new_addresses(&base))
+                    || !fork.get_read_keys().is_disjoint(&written_keys);
+                let mut gas_meter = self.gas_meter.clone();
+                let result = match result {
+                    Ok(result)
+                        if is_clean
+                            && !is_conflicting
+                            && gas_meter.add(result.gas_used).is_ok()
+                            && gas_meter.finalize_transaction().is_ok() =>
+                    {
+                        self.gas_meter = gas_meter;
+                        self.write_log.merge_fork(fork, &base);
+                        Ok(result)
+                    }
+                    _ => {
+                        tracing::debug!(
+                            "applying a conflicting or failed transaction \
+                             again"
+                        );
+                        protocol::apply_tx(
+                            tx_bytes,
+                            &self.parameters,
+                            &mut self.gas_meter,
+                            &mut self.write_log,
+                            &self.storage,
+                        )
+                        .map_err(Error::TxError)
+                    }
+                };
+                written_keys.extend(self.write_log.get_keys());
+                self.finalize_tx(result)
+            })
+            .collect()
+    }
+
+    /// Begin a block, apply its transactions and end it. Returns the results
+    /// of the transactions and the changes of the validator set, if any. The
+    /// block can then be committed with [`Shell::commit`].
+    ///
+    /// Like [`Shell::apply_txs`], this is experimental and only built for
+    /// tests. The blocks from Tendermint are applied one transaction at a time
+    /// with [`Shell::apply_tx`].
+    #[cfg(test)]
+    pub fn finalize_block(
+        &mut self,
+        hash: BlockHash,
+        height: BlockHeight,
+        time: u64,
+        misbehavior: pos::Misbehavior,
+        txs: &[Vec<u8>],
+    ) -> (Vec<(i64, Result<protocol::TxResult>)>, Vec<ValidatorUpdate>) {
+        self.begin_block(hash, height, time, misbehavior);
+        let results = self.apply_txs(txs);
+        let updates = self.end_block(height);
+        (results, updates)
+    }
+
+    /// Commit or drop the current transaction's write log depending on the
+    /// result of the transaction and return the transaction's gas.
+    fn finalize_tx(
+        &mut self,
+        result: Result<protocol::TxResult>,
+    ) -> (i64, Result<protocol::TxResult>) {
         match result {
             Ok(result) => {
                 if result.is_accepted() {
@@ -325,6 +457,10 @@ impl Shell {
                 (gas, Ok(result))
             }
             err @ Err(_) => {
+                // the modifications of a failed transaction must not be
+                // committed with the next transaction, e.g. when it couldn't
+                // pay its storage deposits
+                self.write_log.drop_tx();
                 let gas =
                     gas::as_i64(self.gas_meter.get_current_transaction_gas());
                 (gas, err)
@@ -333,27 +469,62 @@ impl Shell {
     }
 
     /// Simulate validation and application of a transaction.
-    pub fn dry_run_tx(&mut self, tx_bytes: &[u8]) -> Result<String> {
-        let mut gas_meter = BlockGasMeter::default();
+    pub fn dry_run_tx(
+        &mut self,
+        tx_bytes: &[u8],
+    ) -> Result<protocol::TxResult> {
+        let parameters = Parameters::read(&self.storage);
+        let mut gas_meter = BlockGasMeter::new(&parameters);
         let mut write_log = self.write_log.clone();
-        let result = protocol::apply_tx(
+        protocol::apply_tx(
             tx_bytes,
+            &parameters,
             &mut gas_meter,
             &mut write_log,
             &self.storage,
         )
-        .map_err(Error::TxError)?;
-        Ok(result.to_string())
+        .map_err(Error::TxError)
+    }
+
+    /// Read the committed value of the storage key given as a string.
+    pub fn read_storage_value(&self, key: &[u8]) -> Result<Vec<u8>> {
+        let key = std::str::from_utf8(key)
+            .map_err(|e| Error::InvalidStorageKey(e.to_string()))?;
+        let key = Key::parse(key.to_owned())
+            .map_err(|e| Error::InvalidStorageKey(e.to_string()))?;
+        let (value, _gas) =
+            self.storage.read(&key).map_err(Error::StorageError)?;
+        value.ok_or(Error::NoValueFound(key))
     }
 
     /// Begin a new block.
-    pub fn begin_block(&mut self, hash: BlockHash, height: BlockHeight) {
-        self.gas_meter.reset();
+    pub fn begin_block(
+        &mut self,
+        hash: BlockHash,
+        height: BlockHeight,
+        time: u64,
+        misbehavior: pos::Misbehavior,
+    ) {
+        self.block_time = time;
         self.storage.begin_block(hash, height).unwrap();
+        // the upgrade's migration applies before anything else in the block
+        upgrade::begin_block(&mut self.storage, height);
+        governance::begin_block(&mut self.storage, height);
+        // the parameters changed in the last block or by a passed proposal
+        // apply from this block
+        self.parameters = Parameters::read(&self.storage);
+        self.gas_meter = BlockGasMeter::new(&self.parameters);
+        pos::begin_block(&mut self.storage, height, time, misbehavior);
     }
 
-    /// End a block.
-    pub fn end_block(&mut self, _height: BlockHeight) {}
+    /// End a block and return the changes of the validator set, if any.
+    pub fn end_block(&mut self, height: BlockHeight) -> Vec<ValidatorUpdate> {
+        // the block's changes must be applied to compute the validator set
+        self.write_log
+            .commit_block(&mut self.storage)
+            .expect("Expected committing block write log success");
+        pos::end_block(&mut self.storage, height, self.block_time)
+    }
 
     /// Commit a block. Persist the application state and return the Merkle root
     /// hash.
@@ -374,6 +545,26 @@ impl Shell {
         MerkleRoot(root.as_slice().to_vec())
     }
 
+    /// Check that this binary supports the planned upgrade, if it is due at the
+    /// next block.
+    pub fn check_upgrade(&self) -> Result<()> {
+        let next_height = self.storage.get_block_height().0.next_height();
+        match upgrade::unsupported_upgrade(&self.storage, next_height) {
+            Some(UpgradePlan { name, height, .. }) => {
+                tracing::error!(
+                    "Halting before the unsupported upgrade {} at height {}",
+                    name,
+                    height.0
+                );
+                Err(Error::UnsupportedUpgrade {
+                    name,
+                    height: height.0,
+                })
+            }
+            None => Ok(()),
+        }
+    }
+
     /// Load the Merkle root hash and the height of the last committed block, if
     /// any.
     pub fn last_state(&mut self) -> Option<(MerkleRoot, u64)> {
@@ -400,3 +591,332 @@ impl Shell {
         result
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use anoma_shared::types::{token, Address};
+    use borsh::BorshSerialize;
+
+    use super::*;
+
+    fn shell() -> Shell {
+        shell_with_accounts(vec![], vec![], vec![])
+    }
+
+    fn shell_with_accounts(
+        established_accounts: Vec<genesis::EstablishedAccountConfig>,
+        implicit_accounts: Vec<genesis::ImplicitAccountConfig>,
+        tokens: Vec<genesis::TokenConfig>,
+    ) -> Shell {
+        let (_sender, receiver) = mpsc::channel();
+        let genesis = GenesisConfig {
+            chain_id: "test-chain".to_owned(),
+            parameters: Parameters::default(),
+            pos: Default::default(),
+            governance: Default::default(),
+            validators: vec![],
+            established_accounts,
+            implicit_accounts,
+            tokens,
+        };
+        assert!(genesis.validate().is_empty(), "the genesis is invalid");
+        Shell::new(receiver, storage::open_in_memory(), genesis)
+    }
+
+    /// Escape the bytes for a data segment of a wat module.
+    fn wat_data(bytes: &[u8]) -> String {
+        bytes.iter().map(|byte| format!("\\{:02x}", byte)).collect()
+    }
+
+    /// A validity predicate that accepts any transaction.
+    fn accept_vp() -> Vec<u8> {
+        wasmer::wat2wasm(
+            br#"
+            (module
+                (func $validate_tx (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                    (i64.const 1))
+                (memory (;0;) 16)
+                (export "memory" (memory 0))
+                (export "_validate_tx" (func $validate_tx)))
+            "#,
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned()
+    }
+
+    /// A validity predicate that rejects any transaction.
+    fn reject_vp() -> Vec<u8> {
+        wasmer::wat2wasm(
+            br#"
+            (module
+                (func $validate_tx (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
+                    (i64.const 0))
+                (memory (;0;) 16)
+                (export "memory" (memory 0))
+                (export "_validate_tx" (func $validate_tx)))
+            "#,
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned()
+    }
+
+    /// A transaction that appends a byte to the value of the `counter` key
+    /// and initializes an account, so that it conflicts with any other
+    /// instance of it in the same block.
+    fn conflicting_tx() -> Vec<u8> {
+        init_account_tx(&accept_vp())
+    }
+
+    /// A transaction that appends a byte to the value of the `counter` key
+    /// and initializes an account with the given validity predicate.
+    fn init_account_tx(vp_code: &[u8]) -> Vec<u8> {
+        let vp_data = wat_data(vp_code);
+        let tx_code = wasmer::wat2wasm(
+            format!(
+                r#"
+            (module
+                (import "env" "anoma_tx_read" (func $read (param i64 i64 i64) (result i64)))
+                (import "env" "anoma_tx_write" (func $write (param i64 i64 i64 i64)))
+                (import "env" "anoma_tx_init_account" (func $init_account (param i64 i64 i64) (result i64)))
+                (func $apply_tx (param i64 i64)
+                    (local $len i64)
+                    (local.set $len
+                        (call $read (i64.const 0) (i64.const 7) (i64.const 1024)))
+                    (if (i64.lt_s (local.get $len) (i64.const 0))
+                        (then (local.set $len (i64.const 0))))
+                    (call $write (i64.const 0) (i64.const 7) (i64.const 1024)
+                        (i64.add (local.get $len) (i64.const 1)))
+                    (drop (call $init_account
+                        (i64.const 2048) (i64.const {}) (i64.const 4096))))
+                (memory (;0;) 16)
+                (data (i32.const 0) "counter")
+                (data (i32.const 2048) "{}")
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $apply_tx)))
+            "#,
+                vp_code.len(),
+                vp_data
+            )
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+        Tx::new(tx_code, None).to_bytes()
+    }
+
+    /// Test that applying a block's transactions in parallel produces the
+    /// same state as applying them one after another, when they read and
+    /// write the same key and initialize accounts.
+    #[test]
+    fn test_apply_txs_same_as_sequential() {
+        let txs = vec![conflicting_tx(); 4];
+        let hash = BlockHash::default();
+        let height = BlockHeight(1);
+
+        let mut sequential = shell();
+        sequential.begin_block(
+            hash.clone(),
+            height,
+            0,
+            pos::Misbehavior::default(),
+        );
+        let sequential_results: Vec<_> =
+            txs.iter().map(|tx| sequential.apply_tx(tx)).collect();
+        sequential.end_block(height);
+        let sequential_root = sequential.commit();
+
+        let mut parallel = shell();
+        let (parallel_results, _updates) = parallel.finalize_block(
+            hash,
+            height,
+            0,
+            pos::Misbehavior::default(),
+            &txs,
+        );
+        let parallel_root = parallel.commit();
+
+        for ((seq_gas, seq_result), (par_gas, par_result)) in
+            sequential_results.iter().zip(&parallel_results)
+        {
+            assert_eq!(seq_gas, par_gas);
+            let seq_result = seq_result.as_ref().expect("the tx failed");
+            let par_result = par_result.as_ref().expect("the tx failed");
+            assert!(seq_result.is_accepted());
+            assert_eq!(
+                seq_result.initialized_accounts,
+                par_result.initialized_accounts
+            );
+        }
+        assert_eq!(sequential_root.0, parallel_root.0);
+
+        let counter = Key::parse("counter".to_owned()).unwrap();
+        let (value, _gas) = parallel.storage.read(&counter).unwrap();
+        assert_eq!(value.map(|value| value.len()), Some(txs.len()));
+    }
+
+    /// Test that the response data of a transaction rejected by the validity
+    /// predicates doesn't report the accounts it would have initialized.
+    #[test]
+    fn test_rejected_tx_initialized_accounts_data() {
+        let mut shell = shell();
+        shell.begin_block(
+            BlockHash::default(),
+            BlockHeight(1),
+            0,
+            pos::Misbehavior::default(),
+        );
+
+        let (_gas, result) = shell.apply_tx(&init_account_tx(&accept_vp()));
+        let result = result.expect("the tx failed");
+        assert!(result.is_accepted());
+        assert!(!result.initialized_accounts_data().is_empty());
+
+        // the new account's VP rejects its own initialization
+        let (_gas, result) = shell.apply_tx(&init_account_tx(&reject_vp()));
+        let result = result.expect("the tx failed");
+        assert!(!result.is_accepted());
+        assert!(result.initialized_accounts_data().is_empty());
+    }
+
+    /// Test that a query of a key without a value can be told apart from a
+    /// failed query by its response code.
+    #[test]
+    fn test_query_error_codes() {
+        let shell = shell();
+        let err = shell.read_storage_value(b"missing").unwrap_err();
+        assert_eq!(QueryError::from(err).code, QUERY_NO_VALUE_CODE);
+        let err = shell.read_storage_value(&[0xff]).unwrap_err();
+        assert_eq!(QueryError::from(err).code, QUERY_ERROR_CODE);
+    }
+
+    /// A transaction that writes the given balances of a token.
+    fn write_balances_tx(
+        token: &Address,
+        balances: &[(&Address, token::Amount)],
+    ) -> Vec<u8> {
+        let writes: String = balances
+            .iter()
+            .enumerate()
+            .map(|(i, (owner, amount))| {
+                let key = token::balance_key(token, owner).to_string();
+                let value = amount.try_to_vec().unwrap();
+                let key_ptr = 1024 * i;
+                let value_ptr = key_ptr + 512;
+                format!(
+                    r#"
+                (func $write_{i}
+                    (call $write (i64.const {}) (i64.const {})
+                        (i64.const {}) (i64.const {})))
+                (data (i32.const {}) "{}")
+                (data (i32.const {}) "{}")"#,
+                    key_ptr,
+                    key.len(),
+                    value_ptr,
+                    value.len(),
+                    key_ptr,
+                    wat_data(key.as_bytes()),
+                    value_ptr,
+                    wat_data(&value),
+                    i = i,
+                )
+            })
+            .collect();
+        let calls: String = (0..balances.len())
+            .map(|i| format!("(call $write_{})", i))
+            .collect();
+        let tx_code = wasmer::wat2wasm(
+            format!(
+                r#"
+            (module
+                (import "env" "anoma_tx_write" (func $write (param i64 i64 i64 i64)))
+                {}
+                (func $apply_tx (param i64 i64)
+                    {})
+                (memory (;0;) 16)
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $apply_tx)))
+            "#,
+                writes, calls
+            )
+            .as_bytes(),
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+        Tx::new(tx_code, None).to_bytes()
+    }
+
+    /// Test that a transfer to an implicit account declared in the genesis
+    /// is applied, i.e. that the account has a validity predicate.
+    #[test]
+    fn test_transfer_to_genesis_implicit_account() {
+        let vp_dir = tempfile::tempdir().unwrap();
+        let vp_path = vp_dir.path().join("vp.wasm");
+        std::fs::write(&vp_path, accept_vp()).unwrap();
+        let vp_code_path = vp_path.to_string_lossy().into_owned();
+
+        let source = Address::decode(
+            "a1qq5qqqqqg4znssfsgcurjsfhgfpy2vjyxy6yg3z98pp5zvp5xgersvfjxvcnx3f4xycrzdfkak0xhx",
+        )
+        .unwrap();
+        let token = Address::decode(
+            "a1qq5qqqqqxuc5gvz9gycryv3sgye5v3j9gvurjv34g9prsd6x8qu5xs2ygdzrzsf38q6rss33xf42f3",
+        )
+        .unwrap();
+        let public_key =
+            "5e704c4e46265e1ccc87505149f79b9d2e414d01a4e3806dfc65f0a73901c1d0";
+        let target = genesis::implicit_address(
+            genesis::parse_public_key(public_key).unwrap(),
+        );
+
+        let mut shell = shell_with_accounts(
+            vec![genesis::EstablishedAccountConfig {
+                address: source.encode(),
+                vp_code_path: vp_code_path.clone(),
+                public_key: None,
+            }],
+            vec![genesis::ImplicitAccountConfig {
+                public_key: public_key.to_owned(),
+                vp_code_path: vp_code_path.clone(),
+            }],
+            vec![genesis::TokenConfig {
+                address: token.encode(),
+                vp_code_path,
+                symbol: "XAN".to_owned(),
+                name: "Anoma".to_owned(),
+                decimals: token::DEFAULT_DECIMALS,
+                mint_authority: None,
+                balances: vec![(source.encode(), "100".to_owned())]
+                    .into_iter()
+                    .collect(),
+            }],
+        );
+        shell.init_chain("test-chain".to_owned()).unwrap();
+
+        let amount = |amount: &str| {
+            token::Amount::from_str_with_decimals(
+                amount,
+                token::DEFAULT_DECIMALS,
+            )
+            .unwrap()
+        };
+        let tx = write_balances_tx(
+            &token,
+            &[(&source, amount("60")), (&target, amount("40"))],
+        );
+        let height = BlockHeight(1);
+        shell.begin_block(
+            BlockHash::default(),
+            height,
+            0,
+            pos::Misbehavior::default(),
+        );
+        let (_gas, result) = shell.apply_tx(&tx);
+        assert!(result.expect("the tx failed").is_accepted());
+        shell.end_block(height);
+        shell.commit();
+
+        let key = token::balance_key(&token, &target);
+        let (value, _gas) = shell.storage.read(&key).unwrap();
+        assert_eq!(value, Some(amount("40").try_to_vec().unwrap()));
+    }
+}
@@ -0,0 +1,344 @@
+//! Generator of a network of validator nodes running on a single machine.
+//!
+//! Every node gets its own base directory with a config, a Tendermint home
+//! directory with fresh keys and a copy of the shared genesis file. The nodes'
+//! ledgers and intent gossipers are connected to each other as peers.
+
+use std::collections::HashSet;
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+
+use anoma_shared::types::address::EstablishedAddressGen;
+use libp2p::multiaddr::Multiaddr;
+use thiserror::Error;
+
+use super::tendermint;
+use crate::config::{self, Config};
+use crate::genesis::{
+    self, EstablishedAccountConfig, GenesisConfig, ValidatorConfig,
+};
+
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("The network must have at least one validator")]
+    NoValidators,
+    #[error("Too many validators {0}, the ports would overflow")]
+    TooManyValidators(u16),
+    #[error("Failed to write a node's config: {0}")]
+    Config(config::Error),
+    #[error("Failed to initialize a Tendermint node: {0}")]
+    Tendermint(tendermint::Error),
+    #[error("Failed to serialize the genesis: {0}")]
+    SerializeGenesis(toml::ser::Error),
+    #[error("Failed to write the genesis: {0}")]
+    WriteGenesis(std::io::Error),
+    #[error("Invalid gossip address {0}")]
+    InvalidGossipAddress(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The self-bonded stake of every validator of the network
+const STAKE: &str = "1000";
+/// The validity predicate of the validators' accounts
+const USER_VP: &str = "wasm/vps/vp_user/vp.wasm";
+
+// The ports of the first node. The ports of the other nodes are offset by
+// `PORT_OFFSET` for each node.
+const P2P_PORT: u16 = 26656;
+const RPC_PORT: u16 = 26657;
+const ABCI_PORT: u16 = 26658;
+const GOSSIP_PORT: u16 = 20201;
+const PORT_OFFSET: u16 = 100;
+
+/// The ports used by a node
+struct NodePorts {
+    p2p: u16,
+    rpc: u16,
+    abci: u16,
+    gossip: u16,
+}
+
+impl NodePorts {
+    fn new(index: u16) -> Self {
+        let offset = index * PORT_OFFSET;
+        Self {
+            p2p: P2P_PORT + offset,
+            rpc: RPC_PORT + offset,
+            abci: ABCI_PORT + offset,
+            gossip: GOSSIP_PORT + offset,
+        }
+    }
+}
+
+/// Generate the base directories of a network with the given number of
+/// validator nodes in the output directory. The nodes' base directories are
+/// named `node-<index>`. The first node's ledger RPC is on the default port.
+pub fn init_network(
+    validators: u16,
+    out_dir: impl AsRef<Path>,
+    chain_id: String,
+) -> Result<()> {
+    if validators == 0 {
+        return Err(Error::NoValidators);
+    }
+    // the highest port must not overflow
+    let max_offset = (validators - 1) as u32 * PORT_OFFSET as u32;
+    if max_offset + RPC_PORT as u32 > u16::MAX as u32 {
+        return Err(Error::TooManyValidators(validators));
+    }
+    let out_dir = out_dir.as_ref();
+
+    let mut nodes = vec![];
+    for index in 0..validators {
+        let base_dir = out_dir.join(format!("node-{}", index));
+        let config = Config::generate(&base_dir.to_string_lossy(), true)
+            .map_err(Error::Config)?;
+        let ledger = config.ledger.as_ref().expect("default has ledger");
+        let identity = tendermint::init_home(&ledger.tendermint)
+            .map_err(Error::Tendermint)?;
+        nodes.push((base_dir, config, identity, NodePorts::new(index)));
+    }
+
+    // Every validator has an account that uses its consensus key
+    let mut address_gen = EstablishedAddressGen::new(&chain_id);
+    let (validators, accounts) = nodes
+        .iter()
+        .map(|(_, _, identity, _)| {
+            let address =
+                address_gen.generate_address(&identity.node_id).encode();
+            let public_key = hex::encode(identity.validator_pk.to_bytes());
+            (
+                ValidatorConfig {
+                    address: address.clone(),
+                    public_key: public_key.clone(),
+                    stake: STAKE.to_owned(),
+                    commission_rate: 0,
+                },
+                EstablishedAccountConfig {
+                    address,
+                    vp_code_path: USER_VP.to_owned(),
+                    public_key: Some(public_key),
+                },
+            )
+        })
+        .unzip();
+    let genesis = network_genesis(chain_id, validators, accounts);
+    let genesis_str =
+        toml::to_string(&genesis).map_err(Error::SerializeGenesis)?;
+    std::fs::write(out_dir.join(genesis::FILENAME), &genesis_str)
+        .map_err(Error::WriteGenesis)?;
+
+    let tendermint_homes: Vec<PathBuf> = nodes
+        .iter()
+        .map(|(_, config, _, _)| {
+            config
+                .ledger
+                .as_ref()
+                .expect("default has ledger")
+                .tendermint
+                .clone()
+        })
+        .collect();
+    tendermint::share_genesis(&tendermint_homes, &genesis.chain_id)
+        .map_err(Error::Tendermint)?;
+
+    let peers = |index: usize| {
+        peers_of(&nodes, index).map(|(_, _, identity, ports)| (identity, ports))
+    };
+    let mut ledger_peers: Vec<Vec<String>> = vec![];
+    let mut gossip_peers: Vec<HashSet<Multiaddr>> = vec![];
+    for index in 0..nodes.len() {
+        ledger_peers.push(
+            peers(index)
+                .map(|(identity, ports)| {
+                    ledger_peer_address(&identity.node_id, ports)
+                })
+                .collect(),
+        );
+        gossip_peers.push(
+            peers(index)
+                .map(|(_, ports)| gossip_peer_address(ports))
+                .collect::<Result<_>>()?,
+        );
+    }
+
+    for (
+        index,
+        ((base_dir, mut config, _, ports), (ledger_peers, gossip_peers)),
+    ) in nodes
+        .into_iter()
+        .zip(ledger_peers.into_iter().zip(gossip_peers))
+        .enumerate()
+    {
+        let ledger = config.ledger.as_mut().expect("default has ledger");
+        ledger.address =
+            SocketAddr::new(IpAddr::V4(Ipv4Addr::new(0, 0, 0, 0)), ports.abci);
+        tendermint::configure_local_node(
+            &ledger.tendermint,
+            ports.p2p,
+            ports.rpc,
+            ledger.address,
+            &ledger_peers,
+        )
+        .map_err(Error::Tendermint)?;
+        std::fs::write(&ledger.genesis, &genesis_str)
+            .map_err(Error::WriteGenesis)?;
+
+        let gossiper = config
+            .intent_gossiper
+            .as_mut()
+            .expect("default has intent gossiper");
+        gossiper.address =
+            gossip_address(format!("/ip4/0.0.0.0/tcp/{}", ports.gossip))?;
+        gossiper.peers = gossip_peers;
+
+        config
+            .write(base_dir.clone(), true)
+            .map_err(Error::Config)?;
+
+        tracing::info!(
+            "Initialized the node {} in {} with the ledger RPC on port {}",
+            index,
+            base_dir.to_string_lossy(),
+            ports.rpc
+        );
+    }
+    Ok(())
+}
+
+/// The genesis of the network with the validators' accounts. In "dev", it
+/// also includes the development accounts and tokens, otherwise only the
+/// staking token.
+fn network_genesis(
+    chain_id: String,
+    validators: Vec<ValidatorConfig>,
+    mut established_accounts: Vec<EstablishedAccountConfig>,
+) -> GenesisConfig {
+    #[cfg(feature = "dev")]
+    let genesis = genesis::dev_genesis();
+    #[cfg(not(feature = "dev"))]
+    let genesis = GenesisConfig {
+        chain_id: String::default(),
+        parameters: Default::default(),
+        pos: Default::default(),
+        governance: Default::default(),
+        validators: vec![],
+        established_accounts: vec![],
+        implicit_accounts: vec![],
+        tokens: vec![genesis::TokenConfig {
+            address: anoma_shared::types::pos::staking_token().encode(),
+            vp_code_path: "wasm/vps/vp_token/vp.wasm".to_owned(),
+            symbol: "XAN".to_owned(),
+            name: "Anoma".to_owned(),
+            decimals: anoma_shared::types::token::DEFAULT_DECIMALS,
+            mint_authority: None,
+            balances: Default::default(),
+        }],
+    };
+    established_accounts.extend(genesis.established_accounts);
+    GenesisConfig {
+        chain_id,
+        validators,
+        established_accounts,
+        ..genesis
+    }
+}
+
+/// The peers of the node at the given index are all the other nodes.
+fn peers_of<T>(nodes: &[T], index: usize) -> impl Iterator<Item = &T> {
+    nodes
+        .iter()
+        .enumerate()
+        .filter(move |(other, _)| *other != index)
+        .map(|(_, node)| node)
+}
+
+/// The address of a node's ledger as a Tendermint persistent peer.
+fn ledger_peer_address(node_id: &str, ports: &NodePorts) -> String {
+    format!("tcp://{}@127.0.0.1:{}", node_id, ports.p2p)
+}
+
+/// The address of a node's intent gossiper as a peer.
+fn gossip_peer_address(ports: &NodePorts) -> Result<Multiaddr> {
+    gossip_address(format!("/ip4/127.0.0.1/tcp/{}", ports.gossip))
+}
+
+fn gossip_address(address: String) -> Result<Multiaddr> {
+    Multiaddr::from_str(&address)
+        .map_err(|_| Error::InvalidGossipAddress(address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_node_ports() {
+        let first = NodePorts::new(0);
+        assert_eq!(first.p2p, P2P_PORT);
+        assert_eq!(first.rpc, RPC_PORT);
+        assert_eq!(first.abci, ABCI_PORT);
+        assert_eq!(first.gossip, GOSSIP_PORT);
+
+        let third = NodePorts::new(2);
+        assert_eq!(third.p2p, P2P_PORT + 2 * PORT_OFFSET);
+        assert_eq!(third.rpc, RPC_PORT + 2 * PORT_OFFSET);
+        assert_eq!(third.abci, ABCI_PORT + 2 * PORT_OFFSET);
+        assert_eq!(third.gossip, GOSSIP_PORT + 2 * PORT_OFFSET);
+
+        // the ports of different nodes don't clash
+        let ports: Vec<u16> = (0..10)
+            .map(NodePorts::new)
+            .flat_map(|ports| {
+                vec![ports.p2p, ports.rpc, ports.abci, ports.gossip]
+            })
+            .collect();
+        let unique: HashSet<&u16> = ports.iter().collect();
+        assert_eq!(unique.len(), ports.len());
+    }
+
+    #[test]
+    fn test_invalid_validators_count() {
+        let out_dir = std::env::temp_dir().join("anoma-test-network");
+        assert!(matches!(
+            init_network(0, &out_dir, "test-chain".to_owned()),
+            Err(Error::NoValidators)
+        ));
+        assert!(matches!(
+            init_network(u16::MAX, &out_dir, "test-chain".to_owned()),
+            Err(Error::TooManyValidators(u16::MAX))
+        ));
+        // nothing has been written
+        assert!(!out_dir.exists());
+    }
+
+    #[test]
+    fn test_peers_exclude_self() {
+        let nodes: Vec<(&str, NodePorts)> = vec!["node0", "node1", "node2"]
+            .into_iter()
+            .enumerate()
+            .map(|(index, node_id)| (node_id, NodePorts::new(index as u16)))
+            .collect();
+
+        let ledger_peers: Vec<String> = peers_of(&nodes, 1)
+            .map(|(node_id, ports)| ledger_peer_address(node_id, ports))
+            .collect();
+        assert_eq!(
+            ledger_peers,
+            vec![
+                format!("tcp://node0@127.0.0.1:{}", P2P_PORT),
+                format!("tcp://node2@127.0.0.1:{}", P2P_PORT + 2 * PORT_OFFSET),
+            ]
+        );
+
+        let gossip_peers: Vec<Multiaddr> = peers_of(&nodes, 0)
+            .map(|(_, ports)| gossip_peer_address(ports).unwrap())
+            .collect();
+        assert_eq!(gossip_peers.len(), 2);
+        assert!(
+            !gossip_peers.contains(&gossip_peer_address(&nodes[0].1).unwrap())
+        );
+    }
+}
@@ -0,0 +1,478 @@
+//! The proof-of-stake epochs, rewards, slashes and validator set updates
+//! applied by the protocol.
+
+use std::collections::BTreeMap;
+
+use anoma_shared::types::key::ed25519::PublicKey;
+use anoma_shared::types::pos::{
+    self, Bonds, CommissionRate, Epoch, EpochState, MissedBlocks,
+    PastValidatorSet, PastValidatorSets, PosParams, RewardPool, Slash,
+    SlashType, Slashes, TotalBonds, Unbonds, ValidatorSet, ValidatorUpdate,
+};
+use anoma_shared::types::token::{self, Amount};
+use anoma_shared::types::{Address, BlockHeight, Key};
+use borsh::{BorshDeserialize, BorshSerialize};
+
+use super::storage::PersistentStorage;
+
+/// The validators' misbehavior reported by Tendermint at the beginning of a
+/// block. The validators are identified by their Tendermint addresses.
+#[derive(Debug, Clone, Default)]
+pub struct Misbehavior {
+    /// The validators that voted twice in the same round with the height of
+    /// the infraction
+    pub duplicate_votes: Vec<(String, BlockHeight)>,
+    /// The validators that didn't sign the last block
+    pub missed_votes: Vec<String>,
+}
+
+/// Start the first epoch if it hasn't been started yet, distribute the rewards
+/// of the last ended epoch, if any, and slash the misbehaving validators.
+pub fn begin_block(
+    storage: &mut PersistentStorage,
+    height: BlockHeight,
+    time: u64,
+    misbehavior: Misbehavior,
+) {
+    let state = match read_opt::<EpochState>(storage, &pos::epoch_key()) {
+        Some(state) => state,
+        None => {
+            let state = EpochState {
+                epoch: Epoch::default(),
+                start_height: height,
+                start_time: time,
+            };
+            write(storage, &pos::epoch_key(), &state);
+            state
+        }
+    };
+    if let Some(pool) = read_opt::<RewardPool>(storage, &pos::reward_pool_key())
+    {
+        distribute_rewards(storage, pool);
+    }
+    process_misbehavior(storage, state.epoch, height, misbehavior);
+}
+
+/// At the end of the last block of an epoch, set aside the epoch's rewards for
+/// its validator set, start the next epoch and recompute the validator set from
+/// the bonds. The validator set is also recomputed when any of its validators
+/// has been jailed. Returns the changes of the validator set.
+pub fn end_block(
+    storage: &mut PersistentStorage,
+    height: BlockHeight,
+    time: u64,
+) -> Vec<ValidatorUpdate> {
+    let params: PosParams = read(storage, &pos::params_key());
+    let state = match read_opt::<EpochState>(storage, &pos::epoch_key()) {
+        Some(state) => state,
+        None => return vec![],
+    };
+    let old_set: ValidatorSet = read(storage, &pos::validator_set_key());
+    let is_epoch_end = params.is_epoch_end(&state, height, time);
+    if is_epoch_end {
+        let reward = params.epoch_reward(&state, height);
+        if reward != Amount::default() && !old_set.is_empty() {
+            let pool = RewardPool {
+                reward,
+                validator_set: old_set.clone(),
+            };
+            write(storage, &pos::reward_pool_key(), &pool);
+        }
+        let next_state = EpochState {
+            epoch: state.epoch + 1,
+            start_height: BlockHeight(height.0 + 1),
+            start_time: time,
+        };
+        write(storage, &pos::epoch_key(), &next_state);
+        // the missed blocks are counted per epoch
+        storage
+            .delete(&pos::missed_blocks_key())
+            .expect("Unable to delete the missed blocks");
+    } else if !old_set
+        .keys()
+        .any(|validator| is_jailed(storage, validator))
+    {
+        return vec![];
+    }
+
+    let total_bonds: TotalBonds = read(storage, &pos::total_bonds_key());
+    let total_bonds: TotalBonds = total_bonds
+        .into_iter()
+        .filter(|(validator, _)| !is_jailed(storage, validator))
+        .collect();
+    let new_set =
+        pos::compute_validator_set(&params, &total_bonds, |validator| {
+            read_opt::<PublicKey>(storage, &pos::consensus_key_key(validator))
+        });
+    if new_set.is_empty() {
+        // the chain cannot continue without validators
+        tracing::warn!(
+            "No validator has any voting power, keeping the current \
+             validator set"
+        );
+        return vec![];
+    }
+    let updates = pos::validator_set_updates(&old_set, &new_set);
+    if !updates.is_empty() {
+        tracing::info!("New validator set: {:#?}", new_set);
+        write(storage, &pos::validator_set_key(), &new_set);
+        // Tendermint applies the updates from the block after the next one
+        let epoch = if is_epoch_end {
+            state.epoch + 1
+        } else {
+            state.epoch
+        };
+        keep_past_validator_set(
+            storage,
+            &params,
+            epoch,
+            BlockHeight(height.0 + 2),
+            old_set,
+        );
+    }
+    updates
+}
+
+/// Keep the replaced validator set to be able to process the misbehavior
+/// reported at the heights at which it was used. The sets replaced more than
+/// the unbonding epochs ago are pruned, as their bonds may have already been
+/// withdrawn.
+fn keep_past_validator_set(
+    storage: &mut PersistentStorage,
+    params: &PosParams,
+    epoch: Epoch,
+    last_height: BlockHeight,
+    validator_set: ValidatorSet,
+) {
+    let key = pos::past_validator_sets_key();
+    let mut past_sets: PastValidatorSets = read(storage, &key);
+    past_sets.retain(|_, past| past.epoch + params.unbonding_epochs >= epoch);
+    past_sets.insert(
+        last_height,
+        PastValidatorSet {
+            epoch,
+            validator_set,
+        },
+    );
+    write(storage, &key, &past_sets);
+}
+
+/// Mint the pool's reward into the PoS account and credit it to the owners of
+/// the bonds.
+fn distribute_rewards(storage: &mut PersistentStorage, pool: RewardPool) {
+    let mut bonds: BTreeMap<Address, BTreeMap<Address, Amount>> =
+        BTreeMap::new();
+    for key in prefix_keys(storage, &pos::bonds_prefix()) {
+        let source = match pos::is_bond_key(&key) {
+            Some(source) => source,
+            None => continue,
+        };
+        let source_bonds: Bonds = read(storage, &key);
+        for (validator, amount) in source_bonds {
+            bonds
+                .entry(validator)
+                .or_default()
+                .insert(source.clone(), amount);
+        }
+    }
+    let rewards = pos::distribute_rewards(
+        &pool,
+        |validator| {
+            read::<CommissionRate>(
+                storage,
+                &pos::commission_rate_key(validator),
+            )
+        },
+        &bonds,
+    );
+
+    let mut minted = Amount::default();
+    for (owner, reward) in rewards {
+        let key = pos::rewards_key(&owner);
+        let mut owner_rewards: Amount = read(storage, &key);
+        owner_rewards
+            .receive(&reward)
+            .expect("The rewards shouldn't overflow");
+        write(storage, &key, &owner_rewards);
+        minted
+            .receive(&reward)
+            .expect("The rewards shouldn't overflow");
+    }
+    change_staking_supply(storage, minted, true);
+    storage
+        .delete(&pos::reward_pool_key())
+        .expect("Unable to delete the reward pool");
+    tracing::info!("Distributed the rewards of {} to the bonds", minted);
+}
+
+/// Slash the validators that voted twice and count the blocks missed by the
+/// others. The misbehaving validators are looked up in the validator set that
+/// Tendermint used at the height of the infraction, which may have been
+/// replaced since. The misbehavior reported at a height that is older than
+/// the kept validator sets is ignored.
+fn process_misbehavior(
+    storage: &mut PersistentStorage,
+    epoch: Epoch,
+    height: BlockHeight,
+    misbehavior: Misbehavior,
+) {
+    let params: PosParams = read(storage, &pos::params_key());
+    let current_set: ValidatorSet = read(storage, &pos::validator_set_key());
+    let past_sets: PastValidatorSets =
+        read(storage, &pos::past_validator_sets_key());
+    // Find the validator with the given Tendermint address and its voting
+    // power at the given height
+    let find_validator = |tm_address: &str, height: BlockHeight| {
+        pos::validator_set_at(&current_set, &past_sets, height)
+            .iter()
+            .find(|(_, active)| {
+                let pk: ed25519_dalek::PublicKey =
+                    active.consensus_key.clone().into();
+                crate::genesis::validator_address(&pk) == tm_address
+            })
+            .map(|(validator, active)| (validator.clone(), active.voting_power))
+    };
+
+    for (tm_address, infraction_height) in misbehavior.duplicate_votes {
+        match find_validator(&tm_address, infraction_height) {
+            Some((validator, voting_power))
+                if !is_jailed(storage, &validator) =>
+            {
+                slash(
+                    storage,
+                    &params,
+                    epoch,
+                    height,
+                    &validator,
+                    voting_power,
+                    SlashType::DuplicateVote,
+                )
+            }
+            Some(_) => {}
+            None => tracing::warn!(
+                "Ignoring the duplicate vote of an unknown validator {} at \
+                 height {}",
+                tm_address,
+                infraction_height
+            ),
+        }
+    }
+
+    if misbehavior.missed_votes.is_empty() {
+        return;
+    }
+    // the votes are for the last block
+    let last_height = BlockHeight(height.0.saturating_sub(1));
+    let mut missed_blocks: MissedBlocks =
+        read(storage, &pos::missed_blocks_key());
+    for tm_address in misbehavior.missed_votes {
+        let (validator, voting_power) =
+            match find_validator(&tm_address, last_height) {
+                Some((validator, voting_power))
+                    if !is_jailed(storage, &validator) =>
+                {
+                    (validator, voting_power)
+                }
+                _ => continue,
+            };
+        let missed = missed_blocks.entry(validator.clone()).or_default();
+        *missed += 1;
+        if *missed > params.max_missed_blocks {
+            missed_blocks.remove(&validator);
+            slash(
+                storage,
+                &params,
+                epoch,
+                height,
+                &validator,
+                voting_power,
+                SlashType::Downtime,
+            );
+        }
+    }
+    write(storage, &pos::missed_blocks_key(), &missed_blocks);
+}
+
+/// Slash all the tokens bonded to the validator, including those that are
+/// still unbonding, burn the slashed tokens and jail the validator. The rate
+/// is reduced for the tokens bonded since the infraction, when the validator
+/// had the given voting power.
+fn slash(
+    storage: &mut PersistentStorage,
+    params: &PosParams,
+    epoch: Epoch,
+    height: BlockHeight,
+    validator: &Address,
+    infraction_voting_power: u64,
+    slash_type: SlashType,
+) {
+    let mut total_bonds: TotalBonds = read(storage, &pos::total_bonds_key());
+    let voting_power = pos::voting_power(
+        total_bonds.get(validator).copied().unwrap_or_default(),
+    );
+    let rate = pos::infraction_slash_rate(
+        params.slash_rate(slash_type),
+        infraction_voting_power,
+        voting_power,
+    );
+    let mut bonds_slashed = Amount::default();
+    for key in prefix_keys(storage, &pos::bonds_prefix()) {
+        let mut bonds: Bonds = read(storage, &key);
+        let bonded = match bonds.get(validator) {
+            Some(bonded) => *bonded,
+            None => continue,
+        };
+        let slashed = pos::slashed_amount(bonded, rate);
+        let remaining = bonded
+            .checked_sub(&slashed)
+            .expect("The slashed amount cannot exceed the bond");
+        if remaining == Amount::default() {
+            bonds.remove(validator);
+        } else {
+            bonds.insert(validator.clone(), remaining);
+        }
+        write(storage, &key, &bonds);
+        bonds_slashed
+            .receive(&slashed)
+            .expect("The slashed bonds cannot exceed the total bonds");
+    }
+    let mut unbonds_slashed = Amount::default();
+    for key in prefix_keys(storage, &pos::unbonds_prefix()) {
+        let mut unbonds: Unbonds = read(storage, &key);
+        let validator_unbonds = match unbonds.get_mut(validator) {
+            Some(validator_unbonds) => validator_unbonds,
+            None => continue,
+        };
+        // the tokens that cannot be withdrawn yet are still at stake
+        for amount in validator_unbonds
+            .range_mut(epoch + 1..)
+            .map(|(_, amount)| amount)
+        {
+            let slashed = pos::slashed_amount(*amount, rate);
+            amount
+                .spend(&slashed)
+                .expect("The slashed amount cannot exceed the unbond");
+            unbonds_slashed
+                .receive(&slashed)
+                .expect("The slashed unbonds cannot exceed the balance");
+        }
+        write(storage, &key, &unbonds);
+    }
+
+    if let Some(total) = total_bonds.get_mut(validator) {
+        total
+            .spend(&bonds_slashed)
+            .expect("The total bonds include all the slashed bonds");
+        if *total == Amount::default() {
+            total_bonds.remove(validator);
+        }
+    }
+    write(storage, &pos::total_bonds_key(), &total_bonds);
+
+    let mut amount = bonds_slashed;
+    amount
+        .receive(&unbonds_slashed)
+        .expect("The slashed amount cannot exceed the balance");
+    change_staking_supply(storage, amount, false);
+
+    let slashes_key = pos::slashes_key(validator);
+    let mut slashes: Slashes = read(storage, &slashes_key);
+    slashes.push(Slash {
+        epoch,
+        height,
+        slash_type,
+        rate,
+        amount,
+    });
+    write(storage, &slashes_key, &slashes);
+    let jailed_until = epoch + params.jail_epochs;
+    write(storage, &pos::jailed_until_key(validator), &jailed_until);
+    tracing::info!(
+        "Slashed {} from the validator {} for {} and jailed it until the \
+         epoch {}",
+        amount,
+        validator,
+        slash_type,
+        jailed_until
+    );
+}
+
+/// Mint or burn the given amount of the staking token in the PoS account.
+fn change_staking_supply(
+    storage: &mut PersistentStorage,
+    amount: Amount,
+    mint: bool,
+) {
+    let token = pos::staking_token();
+    for key in &[
+        token::supply_key(&token),
+        token::balance_key(&token, &pos::pos_address()),
+    ] {
+        let mut value: Amount = read(storage, key);
+        if mint {
+            value
+                .receive(&amount)
+                .expect("The staking token's supply shouldn't overflow");
+        } else {
+            value
+                .spend(&amount)
+                .expect("The PoS balance holds all the bonded tokens");
+        }
+        write(storage, key, &value);
+    }
+}
+
+/// Check if the validator is jailed.
+fn is_jailed(storage: &PersistentStorage, validator: &Address) -> bool {
+    read_opt::<Epoch>(storage, &pos::jailed_until_key(validator)).is_some()
+}
+
+/// Get the keys with the given prefix. The keys are listed from the last
+/// committed block, so the values have to be read from the storage to include
+/// the changes of the current block.
+pub(super) fn prefix_keys(
+    storage: &PersistentStorage,
+    prefix: &Key,
+) -> Vec<Key> {
+    let (iter, _gas) = storage.iter_prefix(prefix);
+    iter.map(|(key, _value, _gas)| {
+        Key::parse(key).expect("Unable to parse a storage key")
+    })
+    .collect()
+}
+
+/// Read a value from the storage or its default if it's not set.
+pub(super) fn read<T: BorshDeserialize + Default>(
+    storage: &PersistentStorage,
+    key: &Key,
+) -> T {
+    read_opt(storage, key).unwrap_or_default()
+}
+
+/// Read a value from the storage, if it's set.
+pub(super) fn read_opt<T: BorshDeserialize>(
+    storage: &PersistentStorage,
+    key: &Key,
+) -> Option<T> {
+    let (value, _gas) = storage
+        .read(key)
+        .unwrap_or_else(|err| panic!("Unable to read {}: {}", key, err));
+    value.map(|bytes| {
+        T::try_from_slice(&bytes[..])
+            .unwrap_or_else(|err| panic!("Unable to decode {}: {}", key, err))
+    })
+}
+
+/// Write a value to the storage.
+pub(super) fn write<T: BorshSerialize>(
+    storage: &mut PersistentStorage,
+    key: &Key,
+    value: &T,
+) {
+    let bytes = value
+        .try_to_vec()
+        .unwrap_or_else(|err| panic!("Unable to encode {}: {}", key, err));
+    storage
+        .write(key, bytes)
+        .unwrap_or_else(|err| panic!("Unable to write {}: {}", key, err));
+}
@@ -5,10 +5,14 @@ use std::convert::TryFrom;
 use std::fmt;
 
 use anoma_shared::ledger::gas::{self, BlockGasMeter, VpGasMeter, VpsGas};
-use anoma_shared::ledger::storage::write_log::WriteLog;
-use anoma_shared::types::{Address, Key};
+use anoma_shared::ledger::native_vp;
+use anoma_shared::ledger::parameters::{self, Parameters};
+use anoma_shared::ledger::storage::write_log::{StorageModification, WriteLog};
+use anoma_shared::ledger::storage_usage;
+use anoma_shared::types::{Address, InternalAddress, Key};
 use anoma_shared::vm;
 use anoma_shared::vm::wasm::runner::{TxRunner, VpRunner};
+use borsh::BorshSerialize;
 use rayon::iter::{IntoParallelRefIterator, ParallelIterator};
 use thiserror::Error;
 
@@ -27,8 +31,14 @@ pub enum Error {
     GasError(gas::Error),
     #[error("Error executing VP for addresses: {0:?}")]
     VpRunnerError(vm::wasm::runner::Error),
+    #[error("Native VP error: {0}")]
+    NativeVpError(native_vp::Error),
     #[error("The address {0} doesn't exist")]
     MissingAddress(Address),
+    #[error("The transaction code with hash {0} is not whitelisted")]
+    DisallowedTx(String),
+    #[error("Storage usage error: {0}")]
+    StorageUsageError(storage_usage::Error),
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
@@ -39,12 +49,26 @@ pub struct TxResult {
     pub gas_used: u64,
     pub changed_keys: Vec<Key>,
     pub vps_result: VpsResult,
+    pub initialized_accounts: Vec<Address>,
 }
 
 impl TxResult {
     pub fn is_accepted(&self) -> bool {
         self.vps_result.rejected_vps.is_empty()
     }
+
+    /// Encode the addresses of the accounts initialized by the transaction to
+    /// be returned to the client in the response data. The data is empty if
+    /// the transaction has been rejected, because its accounts are not
+    /// initialized.
+    pub fn initialized_accounts_data(&self) -> Vec<u8> {
+        if !self.is_accepted() {
+            return vec![];
+        }
+        self.initialized_accounts
+            .try_to_vec()
+            .expect("Encoding the initialized accounts shouldn't fail")
+    }
 }
 
 /// Result of checking a transaction with validity predicates
@@ -56,20 +80,31 @@ pub struct VpsResult {
     pub errors: Vec<(Address, String)>,
 }
 
-impl Default for VpsResult {
-    fn default() -> Self {
+impl VpsResult {
+    /// An empty result with the gas meter using the given protocol parameters
+    fn new(parameters: &Parameters) -> Self {
         Self {
             accepted_vps: HashSet::default(),
             rejected_vps: HashSet::default(),
-            gas_used: VpsGas::default(),
+            gas_used: VpsGas::new(parameters),
             errors: Vec::default(),
         }
     }
 }
 
+/// The validity predicate of a verifier
+#[derive(Clone, Debug)]
+enum Vp {
+    /// A wasm VP read from the storage
+    Wasm(Vec<u8>),
+    /// A native VP of an internal address
+    Native(InternalAddress),
+}
+
 /// Apply a given transaction
 pub fn apply_tx(
     tx_bytes: &[u8],
+    parameters: &Parameters,
     block_gas_meter: &mut BlockGasMeter,
     write_log: &mut WriteLog,
     storage: &PersistentStorage,
@@ -79,27 +114,55 @@ pub fn apply_tx(
         .map_err(Error::GasError)?;
 
     let tx = Tx::try_from(tx_bytes).map_err(Error::TxDecodingError)?;
+    if !parameters.is_tx_allowed(&tx.code) {
+        return Err(Error::DisallowedTx(parameters::code_hash(&tx.code)));
+    }
 
-    let verifiers = execute_tx(&tx, storage, block_gas_meter, write_log)?;
+    let verifiers =
+        execute_tx(&tx, parameters, storage, block_gas_meter, write_log)?;
+
+    let vps_result = check_vps(
+        &tx,
+        parameters,
+        storage,
+        block_gas_meter,
+        write_log,
+        &verifiers,
+    )?;
 
-    let vps_result =
-        check_vps(&tx, storage, block_gas_meter, write_log, &verifiers)?;
+    // the storage usage is updated after the VPs have accepted the
+    // transaction's modifications, including the declaration of its storage
+    // deposit payer, so the protocol's updates are not checked by the VPs
+    if vps_result.rejected_vps.is_empty() {
+        let storage_gas =
+            storage_usage::apply_tx(parameters, storage, write_log)
+                .map_err(Error::StorageUsageError)?;
+        block_gas_meter.add(storage_gas).map_err(Error::GasError)?;
+    }
 
     let gas_used = block_gas_meter
         .finalize_transaction()
         .map_err(Error::GasError)?;
     let changed_keys = write_log.get_keys();
+    let initialized_accounts = write_log
+        .get_partitioned_keys()
+        .1
+        .into_iter()
+        .filter_map(|key| key.is_validity_predicate().cloned())
+        .collect();
 
     Ok(TxResult {
         gas_used,
         changed_keys,
         vps_result,
+        initialized_accounts,
     })
 }
 
 /// Execute a transaction code. Returns verifiers requested by the transaction.
 fn execute_tx(
     tx: &Tx,
+    parameters: &Parameters,
     storage: &PersistentStorage,
     gas_meter: &mut BlockGasMeter,
     write_log: &mut WriteLog,
@@ -112,13 +175,14 @@ fn execute_tx(
     let tx_runner = TxRunner::new();
 
     tx_runner
-        .run(storage, write_log, gas_meter, tx_code, tx_data)
+        .run(storage, parameters, write_log, gas_meter, tx_code, tx_data)
         .map_err(Error::TxRunnerError)
 }
 
 /// Check the acceptance of a transaction by validity predicates
 fn check_vps(
     tx: &Tx,
+    parameters: &Parameters,
     storage: &PersistentStorage,
     gas_meter: &mut BlockGasMeter,
     write_log: &WriteLog,
@@ -130,18 +194,23 @@ fn check_vps(
     let tx_code = tx.code.clone();
 
     // collect the changed storage keys and VPs for the verifiers
-    let verifiers: Vec<(Address, Vec<Key>, Vec<u8>)> = verifiers
+    let verifiers: Vec<(Address, Vec<Key>, Vp)> = verifiers
         .iter()
         .map(|(addr, keys)| {
-            let (vp, gas) = storage
-                .validity_predicate(&addr)
-                .map_err(Error::StorageError)?;
-            gas_meter.add(gas).map_err(Error::GasError)?;
-            let vp = vp.ok_or_else(|| Error::MissingAddress(addr.clone()))?;
-
-            gas_meter
-                .add_compiling_fee(vp.len())
-                .map_err(Error::GasError)?;
+            let vp = match addr {
+                Address::Internal(internal) => Vp::Native(*internal),
+                _ => {
+                    let (vp, gas) = read_vp(storage, write_log, addr)?;
+                    gas_meter.add(gas).map_err(Error::GasError)?;
+                    let vp =
+                        vp.ok_or_else(|| Error::MissingAddress(addr.clone()))?;
+
+                    gas_meter
+                        .add_compiling_fee(vp.len())
+                        .map_err(Error::GasError)?;
+                    Vp::Wasm(vp)
+                }
+            };
 
             Ok((addr.clone(), keys.clone(), vp))
         })
@@ -153,6 +222,7 @@ fn check_vps(
         verifiers,
         tx_data,
         tx_code,
+        parameters,
         storage,
         write_log,
         initial_gas,
@@ -166,6 +236,25 @@ fn check_vps(
     Ok(vps_result)
 }
 
+/// Read a validity predicate of the given address. The accounts initialized in
+/// the current transaction are only present in the write log.
+fn read_vp(
+    storage: &PersistentStorage,
+    write_log: &WriteLog,
+    addr: &Address,
+) -> Result<(Option<Vec<u8>>, u64)> {
+    let key = Key::validity_predicate(addr)
+        .expect("Unable to create a validity predicate key");
+    match write_log.read(&key) {
+        (Some(StorageModification::InitAccount { vp }), gas) => {
+            Ok((Some(vp.clone()), gas))
+        }
+        _ => storage
+            .validity_predicate(addr)
+            .map_err(Error::StorageError),
+    }
+}
+
 /// Get verifiers from storage changes written to a write log
 fn get_verifiers(
     write_log: &WriteLog,
@@ -202,9 +291,10 @@ fn get_verifiers(
 
 /// Execute verifiers' validity predicates
 fn execute_vps(
-    verifiers: Vec<(Address, Vec<Key>, Vec<u8>)>,
+    verifiers: Vec<(Address, Vec<Key>, Vp)>,
     tx_data: Vec<u8>,
     tx_code: Vec<u8>,
+    parameters: &Parameters,
     storage: &PersistentStorage,
     write_log: &WriteLog,
     initial_gas: u64,
@@ -216,21 +306,26 @@ fn execute_vps(
 
     verifiers
         .par_iter()
-        .try_fold(VpsResult::default, |result, (addr, keys, vp)| {
-            execute_vp(
-                result,
-                tx_data.clone(),
-                tx_code.clone(),
-                storage,
-                write_log,
-                addresses.clone(),
-                &mut VpGasMeter::new(initial_gas),
-                (addr, keys, vp),
-            )
-        })
-        .try_reduce(VpsResult::default, |a, b| {
-            merge_vp_results(a, b, initial_gas)
-        })
+        .try_fold(
+            || VpsResult::new(parameters),
+            |result, (addr, keys, vp)| {
+                execute_vp(
+                    result,
+                    tx_data.clone(),
+                    tx_code.clone(),
+                    parameters,
+                    storage,
+                    write_log,
+                    addresses.clone(),
+                    &mut VpGasMeter::new(initial_gas, parameters),
+                    (addr, keys, vp),
+                )
+            },
+        )
+        .try_reduce(
+            || VpsResult::new(parameters),
+            |a, b| merge_vp_results(a, b, initial_gas),
+        )
 }
 
 /// Merge VP results from parallel runs
@@ -267,27 +362,34 @@ fn execute_vp(
     mut result: VpsResult,
     tx_data: Vec<u8>,
     tx_code: Vec<u8>,
+    parameters: &Parameters,
     storage: &PersistentStorage,
     write_log: &WriteLog,
     addresses: HashSet<Address>,
     vp_gas_meter: &mut VpGasMeter,
-    (addr, keys, vp): (&Address, &[Key], &[u8]),
+    (addr, keys, vp): (&Address, &[Key], &Vp),
 ) -> Result<VpsResult> {
-    let vp_runner = VpRunner::new();
-
-    let accept = vp_runner
-        .run(
-            vp,
-            tx_data,
-            &tx_code,
-            addr,
-            storage,
-            write_log,
-            vp_gas_meter,
-            keys,
-            &addresses,
-        )
-        .map_err(Error::VpRunnerError);
+    let accept = match vp {
+        Vp::Wasm(vp) => VpRunner::new()
+            .run(
+                vp,
+                tx_data,
+                &tx_code,
+                addr,
+                storage,
+                parameters,
+                write_log,
+                vp_gas_meter,
+                keys,
+                &addresses,
+            )
+            .map_err(Error::VpRunnerError),
+        Vp::Native(internal) => {
+            let ctx = native_vp::Ctx::new(storage, write_log, vp_gas_meter);
+            native_vp::validate_tx(*internal, &ctx, keys)
+                .map_err(Error::NativeVpError)
+        }
+    };
 
     match accept {
         Ok(accepted) => {
@@ -318,7 +420,7 @@ impl fmt::Display for TxResult {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         write!(
             f,
-            "Transaction is {}. Gas used: {};{} VPs result: {}",
+            "Transaction is {}. Gas used: {};{}{} VPs result: {}",
             if self.is_accepted() {
                 "valid"
             } else {
@@ -326,6 +428,10 @@ impl fmt::Display for TxResult {
             },
             self.gas_used,
             iterable_to_string("Changed keys", self.changed_keys.iter()),
+            iterable_to_string(
+                "Initialized accounts",
+                self.initialized_accounts.iter()
+            ),
             self.vps_result,
         )
     }
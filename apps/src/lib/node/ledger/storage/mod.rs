@@ -7,9 +7,10 @@ use std::collections::HashMap;
 use std::fmt;
 use std::path::Path;
 
+use anoma_shared::ledger::storage::mockdb::{MockDB, MockPrefixIterator};
 use anoma_shared::ledger::storage::types::MerkleTree;
 use anoma_shared::ledger::storage::{
-    types, BlockStorage, Storage, StorageHasher,
+    types, BlockState, BlockStorage, DBIter, Result, Storage, StorageHasher, DB,
 };
 use anoma_shared::types::address::EstablishedAddressGen;
 use anoma_shared::types::{BlockHash, BlockHeight, Key, CHAIN_ID_LENGTH};
@@ -20,11 +21,34 @@ use sparse_merkle_tree::H256;
 
 pub struct PersistentStorageHasher(Blake2bHasher);
 
-pub type PersistentDB = rocksdb::RocksDB;
+/// The DB of the ledger's storage. The state is either persisted in RocksDB or
+/// kept only in memory, in which case it's lost when the node stops.
+#[derive(Debug)]
+pub enum PersistentDB {
+    RocksDB(rocksdb::RocksDB),
+    InMemory(MockDB),
+}
+
+/// A prefix iterator of the [`PersistentDB`].
+pub enum PersistentPrefixIterator<'iter> {
+    RocksDB(rocksdb::PersistentPrefixIterator<'iter>),
+    InMemory(MockPrefixIterator<'iter>),
+}
 
 pub type PersistentStorage = Storage<PersistentDB, PersistentStorageHasher>;
 
+/// Open the storage persisted in RocksDB at the given path.
 pub fn open(db_path: impl AsRef<Path>) -> PersistentStorage {
+    let db = rocksdb::open(db_path).expect("cannot open the DB");
+    new_storage(PersistentDB::RocksDB(db))
+}
+
+/// Open an empty storage that is kept only in memory.
+pub fn open_in_memory() -> PersistentStorage {
+    new_storage(PersistentDB::InMemory(MockDB::default()))
+}
+
+fn new_storage(db: PersistentDB) -> PersistentStorage {
     let tree = MerkleTree::default();
     let subspaces = HashMap::new();
     let block = BlockStorage {
@@ -34,7 +58,7 @@ pub fn open(db_path: impl AsRef<Path>) -> PersistentStorage {
         subspaces,
     };
     PersistentStorage {
-        db: rocksdb::open(db_path).expect("cannot open the DB"),
+        db,
         chain_id: String::with_capacity(CHAIN_ID_LENGTH),
         block,
         current_height: BlockHeight(0),
@@ -44,6 +68,87 @@ pub fn open(db_path: impl AsRef<Path>) -> PersistentStorage {
     }
 }
 
+impl DB for PersistentDB {
+    fn flush(&self) -> Result<()> {
+        match self {
+            PersistentDB::RocksDB(db) => db.flush(),
+            PersistentDB::InMemory(db) => db.flush(),
+        }
+    }
+
+    fn write_block<H: StorageHasher>(
+        &mut self,
+        tree: &MerkleTree<H>,
+        hash: &BlockHash,
+        height: BlockHeight,
+        subspaces: &HashMap<Key, Vec<u8>>,
+        address_gen: &EstablishedAddressGen,
+    ) -> Result<()> {
+        match self {
+            PersistentDB::RocksDB(db) => {
+                db.write_block(tree, hash, height, subspaces, address_gen)
+            }
+            PersistentDB::InMemory(db) => {
+                db.write_block(tree, hash, height, subspaces, address_gen)
+            }
+        }
+    }
+
+    fn write_chain_id(&mut self, chain_id: &String) -> Result<()> {
+        match self {
+            PersistentDB::RocksDB(db) => db.write_chain_id(chain_id),
+            PersistentDB::InMemory(db) => db.write_chain_id(chain_id),
+        }
+    }
+
+    fn read(&self, height: BlockHeight, key: &Key) -> Result<Option<Vec<u8>>> {
+        match self {
+            PersistentDB::RocksDB(db) => db.read(height, key),
+            PersistentDB::InMemory(db) => db.read(height, key),
+        }
+    }
+
+    fn read_last_block<H: StorageHasher>(
+        &mut self,
+    ) -> Result<Option<BlockState<H>>> {
+        match self {
+            PersistentDB::RocksDB(db) => db.read_last_block(),
+            PersistentDB::InMemory(db) => db.read_last_block(),
+        }
+    }
+}
+
+impl<'iter> DBIter<'iter> for PersistentDB {
+    type PrefixIter = PersistentPrefixIterator<'iter>;
+
+    fn iter_prefix(
+        &'iter self,
+        height: BlockHeight,
+        prefix: &Key,
+    ) -> PersistentPrefixIterator<'iter> {
+        match self {
+            PersistentDB::RocksDB(db) => PersistentPrefixIterator::RocksDB(
+                db.iter_prefix(height, prefix),
+            ),
+            PersistentDB::InMemory(db) => PersistentPrefixIterator::InMemory(
+                db.iter_prefix(height, prefix),
+            ),
+        }
+    }
+}
+
+impl<'iter> Iterator for PersistentPrefixIterator<'iter> {
+    type Item = (String, Vec<u8>, u64);
+
+    /// Returns the next pair and the gas cost
+    fn next(&mut self) -> Option<(String, Vec<u8>, u64)> {
+        match self {
+            PersistentPrefixIterator::RocksDB(iter) => iter.next(),
+            PersistentPrefixIterator::InMemory(iter) => iter.next(),
+        }
+    }
+}
+
 impl Default for PersistentStorageHasher {
     fn default() -> Self {
         Self(Blake2bHasher::default())
@@ -224,6 +329,46 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_in_memory() {
+        let mut storage = open_in_memory();
+        assert!(storage
+            .load_last_state()
+            .expect("loading the last state failed")
+            .is_none());
+        storage
+            .set_chain_id("test_chain_id_000000")
+            .expect("setting a chain ID failed");
+        storage
+            .begin_block(BlockHash::default(), BlockHeight(100))
+            .expect("begin_block failed");
+        let prefix = Key::parse("prefix".to_owned())
+            .expect("cannot parse the key string");
+        let key = prefix
+            .push(&"key".to_owned())
+            .expect("cannot push the key segment");
+        let value_bytes = types::encode(&1u64);
+
+        // insert and commit
+        storage
+            .write(&key, value_bytes.clone())
+            .expect("write failed");
+        storage.commit().expect("commit failed");
+        let (val, _) = storage.read(&key).expect("read failed");
+        assert_eq!(val.expect("no value"), value_bytes);
+        let (iter, _) = storage.iter_prefix(&prefix);
+        let pairs: Vec<(String, Vec<u8>)> =
+            iter.map(|(key, val, _gas)| (key, val)).collect();
+        assert_eq!(pairs, vec![(key.to_string(), value_bytes)]);
+
+        // a new in-memory storage starts from an empty state
+        let mut storage = open_in_memory();
+        assert!(storage
+            .load_last_state()
+            .expect("loading the last state failed")
+            .is_none());
+    }
+
     #[test]
     fn test_validity_predicate() {
         let db_path =
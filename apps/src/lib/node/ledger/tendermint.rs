@@ -6,16 +6,20 @@
 use std::convert::{TryFrom, TryInto};
 use std::fs::{self, File, OpenOptions};
 use std::io::{self, Write};
-use std::path::Path;
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::process::Command;
+use std::str::FromStr;
 use std::sync::mpsc::{self, channel, Sender};
 
 use anoma_shared::ledger::storage::MerkleRoot;
+use anoma_shared::types::pos::ValidatorUpdate;
 use anoma_shared::types::{BlockHash, BlockHeight};
 use serde_json::json;
-use signal_hook::consts::TERM_SIGNALS;
+use signal_hook::consts::{SIGTERM, TERM_SIGNALS};
 use signal_hook::iterator::Signals;
 use tendermint::config::TendermintConfig;
+use tendermint::net;
 use tendermint_abci::{self, ServerBuilder};
 use tendermint_proto::abci::{
     CheckTxType, RequestApplySnapshotChunk, RequestBeginBlock, RequestCheckTx,
@@ -30,7 +34,9 @@ use tendermint_proto::abci::{
 use thiserror::Error;
 
 use crate::config;
+#[cfg(feature = "dev")]
 use crate::genesis::{self, Validator};
+use crate::node::ledger::pos::Misbehavior;
 use crate::node::ledger::protocol::TxResult;
 use crate::node::ledger::MempoolTxType;
 
@@ -38,6 +44,8 @@ use crate::node::ledger::MempoolTxType;
 pub enum Error {
     #[error("Failed to initialize Tendermint: {0}")]
     TendermintInit(std::io::Error),
+    #[error("Tendermint command \"{command}\" failed: {stderr}")]
+    TendermintCommand { command: String, stderr: String },
     #[error("Failed to write Tendermint validator key: {0}")]
     TendermintValidatorKey(std::io::Error),
     #[error("Failed to load Tendermint config file: {0}")]
@@ -48,6 +56,14 @@ pub enum Error {
     TendermintConfigSerializeToml(toml::ser::Error),
     #[error("Failed to write Tendermint config: {0}")]
     TendermintWriteConfig(std::io::Error),
+    #[error("Failed to read or write Tendermint genesis file: {0}")]
+    TendermintGenesisFile(std::io::Error),
+    #[error("Failed to decode Tendermint genesis JSON: {0}")]
+    TendermintGenesisJson(serde_json::Error),
+    #[error("Invalid Tendermint validator key")]
+    InvalidValidatorKey,
+    #[error("Invalid Tendermint node address {0}")]
+    InvalidNodeAddress(String),
     #[error("Failed to start up Tendermint node: {0}")]
     TendermintStartUp(std::io::Error),
     #[error("Failed to bind ABCI server: {0}")]
@@ -61,6 +77,25 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub type AbciReceiver = mpsc::Receiver<AbciMsg>;
 pub type AbciSender = mpsc::Sender<AbciMsg>;
 
+/// A successful response to an ABCI query
+#[derive(Debug, Clone)]
+pub struct QueryResponse {
+    /// Additional information, e.g. the result of a dry-run transaction
+    pub info: String,
+    /// The queried value, if any. For a dry-run transaction, it's the
+    /// encoded addresses of the accounts initialized by it.
+    pub value: Vec<u8>,
+}
+
+/// A failed response to an ABCI query
+#[derive(Debug, Clone)]
+pub struct QueryError {
+    /// The response code, e.g. [`super::QUERY_NO_VALUE_CODE`]
+    pub code: u32,
+    /// The error message
+    pub log: String,
+}
+
 #[derive(Debug, Clone)]
 pub enum AbciMsg {
     /// Get the height and the Merkle root hash of the last committed block, if
@@ -68,9 +103,10 @@ pub enum AbciMsg {
     GetInfo {
         reply: Sender<Option<(MerkleRoot, u64)>>,
     },
-    /// Initialize a chain with the given ID
+    /// Initialize a chain with the given ID. The expected result is the
+    /// initial validator set.
     InitChain {
-        reply: Sender<()>,
+        reply: Sender<Vec<ValidatorUpdate>>,
         chain_id: String,
     },
     /// Validate a given transaction for inclusion in the mempool
@@ -84,19 +120,22 @@ pub enum AbciMsg {
         reply: Sender<()>,
         hash: BlockHash,
         height: BlockHeight,
+        /// The block's time in seconds since the Unix epoch
+        time: u64,
+        misbehavior: Misbehavior,
     },
     /// Apply a transaction in a block
     ApplyTx {
         reply: Sender<(i64, std::result::Result<TxResult, String>)>,
         tx: Vec<u8>,
     },
-    /// End a block
+    /// End a block. The expected result is the changes of the validator set.
     EndBlock {
-        reply: Sender<()>,
+        reply: Sender<Vec<ValidatorUpdate>>,
         height: BlockHeight,
     },
     AbciQuery {
-        reply: Sender<std::result::Result<String, String>>,
+        reply: Sender<std::result::Result<QueryResponse, QueryError>>,
         path: String,
         data: Vec<u8>,
         height: BlockHeight,
@@ -112,7 +151,11 @@ pub enum AbciMsg {
 }
 
 /// Run the ABCI server in the current thread (blocking).
-pub fn run(sender: AbciSender, config: config::Ledger) -> Result<()> {
+pub fn run(
+    sender: AbciSender,
+    chain_id: String,
+    config: config::Ledger,
+) -> Result<()> {
     let home_dir = config.tendermint;
     let home_dir_string = home_dir.to_string_lossy().to_string();
     // init and run a Tendermint node child process
@@ -121,13 +164,18 @@ pub fn run(sender: AbciSender, config: config::Ledger) -> Result<()> {
         .output()
         .map_err(Error::TendermintInit)?;
 
-    if cfg!(feature = "dev") {
-        // override the validator key file
-        write_validator_key(&home_dir, &genesis::genesis().validator)
-            .map_err(Error::TendermintValidatorKey)?;
+    #[cfg(feature = "dev")]
+    {
+        // override the validator key file with the development genesis
+        // validator's key
+        if !config.genesis.exists() {
+            write_validator_key(&home_dir, &genesis::genesis().validator)
+                .map_err(Error::TendermintValidatorKey)?;
+        }
     }
 
     update_tendermint_config(&home_dir)?;
+    write_chain_id(&home_dir, &chain_id)?;
 
     let mut tendermint_node = Command::new("tendermint")
         .args(&["node", "--home", &home_dir_string])
@@ -166,8 +214,87 @@ pub fn run(sender: AbciSender, config: config::Ledger) -> Result<()> {
     Ok(())
 }
 
+/// Stop the Tendermint node started by [`run`], as if the process received a
+/// termination signal.
+pub fn shutdown() {
+    if let Err(err) = signal_hook::low_level::raise(SIGTERM) {
+        tracing::error!("Failed to shut down the Tendermint node: {}", err);
+    }
+}
+
+/// Collect the validators that voted twice from the block's evidence and the
+/// validators that didn't sign the last block from its commit info.
+fn block_misbehavior(
+    evidence: Vec<tendermint_proto::abci::Evidence>,
+    last_commit_info: Option<tendermint_proto::abci::LastCommitInfo>,
+) -> Misbehavior {
+    let duplicate_votes = evidence
+        .into_iter()
+        .filter_map(|evidence| {
+            if evidence.r#type
+                != tendermint_proto::abci::EvidenceType::DuplicateVote as i32
+            {
+                tracing::warn!("Ignoring unsupported evidence {:?}", evidence);
+                return None;
+            }
+            let height = match BlockHeight::try_from(evidence.height) {
+                Ok(height) => height,
+                Err(err) => {
+                    tracing::warn!("Ignoring evidence {:?}: {}", evidence, err);
+                    return None;
+                }
+            };
+            evidence
+                .validator
+                .map(|validator| (hex::encode_upper(validator.address), height))
+        })
+        .collect();
+    let missed_votes = last_commit_info
+        .map(|info| info.votes)
+        .unwrap_or_default()
+        .into_iter()
+        .filter(|vote| !vote.signed_last_block)
+        .filter_map(|vote| {
+            vote.validator
+                .map(|validator| hex::encode_upper(validator.address))
+        })
+        .collect();
+    Misbehavior {
+        duplicate_votes,
+        missed_votes,
+    }
+}
+
+/// Convert a validator set change into its ABCI representation.
+fn abci_validator_update(
+    update: ValidatorUpdate,
+) -> tendermint_proto::abci::ValidatorUpdate {
+    let pk: ed25519_dalek::PublicKey = update.consensus_key.into();
+    let pub_key = tendermint_proto::crypto::PublicKey {
+        sum: Some(tendermint_proto::crypto::public_key::Sum::Ed25519(
+            pk.to_bytes().to_vec(),
+        )),
+    };
+    tendermint_proto::abci::ValidatorUpdate {
+        pub_key: Some(pub_key),
+        power: update
+            .voting_power
+            .try_into()
+            .expect("unexpected validator's voting power"),
+    }
+}
+
 pub fn reset(config: config::Ledger) {
-    // reset all the Tendermint state, if any
+    reset_state(&config);
+    fs::remove_dir_all(format!(
+        "{}/config",
+        &config.tendermint.to_string_lossy()
+    ))
+    .expect("Failed to reset tendermint node's config");
+}
+
+/// Reset all the Tendermint state, if any, but keep its config.
+pub fn reset_state(config: &config::Ledger) {
     Command::new("tendermint")
         .args(&[
             "unsafe_reset_all",
@@ -178,11 +305,6 @@ pub fn reset(config: config::Ledger) {
         ])
         .output()
         .expect("Failed to reset tendermint node's data");
-    fs::remove_dir_all(format!(
-        "{}/config",
-        &config.tendermint.to_string_lossy()
-    ))
-    .expect("Failed to reset tendermint node's config");
 }
 
 #[derive(Clone, Debug)]
@@ -233,26 +355,13 @@ impl tendermint_abci::Application for AbciWrapper {
         self.sender
             .send(AbciMsg::InitChain { reply, chain_id })
             .expect("failed to send InitChain request");
-        reply_receiver
+        let validators = reply_receiver
             .recv()
             .expect("failed to receive InitChain response");
 
         // Set the initial validator set
-        let genesis = genesis::genesis();
-        let mut abci_validator =
-            tendermint_proto::abci::ValidatorUpdate::default();
-        let pub_key = tendermint_proto::crypto::PublicKey {
-            sum: Some(tendermint_proto::crypto::public_key::Sum::Ed25519(
-                genesis.validator.keypair.public.to_bytes().to_vec(),
-            )),
-        };
-        abci_validator.pub_key = Some(pub_key);
-        abci_validator.power = genesis
-            .validator
-            .voting_power
-            .try_into()
-            .expect("unexpected validator's voting power");
-        resp.validators.push(abci_validator);
+        resp.validators =
+            validators.into_iter().map(abci_validator_update).collect();
         resp
     }
 
@@ -280,10 +389,13 @@ impl tendermint_abci::Application for AbciWrapper {
             .expect("failed to receive AbciQuery response");
 
         match result {
-            Ok(res) => resp.info = res,
-            Err(msg) => {
-                resp.code = 1;
-                resp.log = msg;
+            Ok(res) => {
+                resp.info = res.info;
+                resp.value = res.value;
+            }
+            Err(err) => {
+                resp.code = err.code;
+                resp.log = err.log;
             }
         }
 
@@ -329,8 +441,17 @@ impl tendermint_abci::Application for AbciWrapper {
                 tracing::error!("{:#?}", err);
             }
             Ok(hash) => {
-                let raw_height =
-                    req.header.expect("missing block's header").height;
+                let misbehavior = block_misbehavior(
+                    req.byzantine_validators,
+                    req.last_commit_info,
+                );
+                let header = req.header.expect("missing block's header");
+                let raw_height = header.height;
+                // blocks before the Unix epoch are treated as its start
+                let time = header
+                    .time
+                    .map(|time| time.seconds.max(0) as u64)
+                    .unwrap_or_default();
                 match raw_height.try_into() {
                     Err(_) => {
                         tracing::error!(
@@ -345,6 +466,8 @@ impl tendermint_abci::Application for AbciWrapper {
                                 reply,
                                 hash,
                                 height,
+                                time,
+                                misbehavior,
                             })
                             .expect("failed to send BeginBlock request");
                         reply_receiver
@@ -373,6 +496,7 @@ impl tendermint_abci::Application for AbciWrapper {
         match result {
             Ok(tx_result) => {
                 resp.info = tx_result.to_string();
+                resp.data = tx_result.initialized_accounts_data();
                 if !tx_result.is_accepted() {
                     resp.code = 1;
                 }
@@ -386,7 +510,7 @@ impl tendermint_abci::Application for AbciWrapper {
     }
 
     fn end_block(&self, req: RequestEndBlock) -> ResponseEndBlock {
-        let resp = ResponseEndBlock::default();
+        let mut resp = ResponseEndBlock::default();
 
         let raw_height = req.height;
         match BlockHeight::try_from(raw_height) {
@@ -398,9 +522,11 @@ impl tendermint_abci::Application for AbciWrapper {
                 self.sender
                     .send(AbciMsg::EndBlock { reply, height })
                     .expect("failed to send EndBlock request");
-                reply_receiver
+                let updates = reply_receiver
                     .recv()
                     .expect("failed to receive EndBlock response");
+                resp.validator_updates =
+                    updates.into_iter().map(abci_validator_update).collect();
             }
         }
         resp
@@ -470,18 +596,145 @@ fn update_tendermint_config(home_dir: impl AsRef<Path>) -> Result<()> {
     // again in the future.
     config.mempool.keep_invalid_txs_in_cache = false;
 
+    write_tendermint_config(path, &config)
+}
+
+fn write_tendermint_config(
+    path: impl AsRef<Path>,
+    config: &TendermintConfig,
+) -> Result<()> {
     let mut file = OpenOptions::new()
         .write(true)
         .truncate(true)
         .open(path)
         .map_err(Error::TendermintOpenWriteConfig)?;
-    let config_str = toml::to_string(&config)
+    let config_str = toml::to_string(config)
         .map_err(Error::TendermintConfigSerializeToml)?;
     file.write(config_str.as_bytes())
         .map(|_| ())
         .map_err(Error::TendermintWriteConfig)
 }
 
+/// The Tendermint identity of a node
+#[derive(Debug, Clone)]
+pub struct NodeIdentity {
+    /// The node ID used to connect to the node as a peer
+    pub node_id: String,
+    /// The node's consensus public key
+    pub validator_pk: ed25519_dalek::PublicKey,
+}
+
+/// Initialize a Tendermint home directory with freshly generated node and
+/// validator keys and return the node's identity.
+pub fn init_home(home_dir: impl AsRef<Path>) -> Result<NodeIdentity> {
+    let home_dir = home_dir.as_ref();
+    let home_dir_string = home_dir.to_string_lossy().to_string();
+    run_tendermint(&["init", "--home", &home_dir_string])?;
+    let output = run_tendermint(&["show-node-id", "--home", &home_dir_string])?;
+    let node_id = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+    let path = home_dir.join("config").join("priv_validator_key.json");
+    let key_str =
+        fs::read_to_string(path).map_err(Error::TendermintValidatorKey)?;
+    let key: serde_json::Value =
+        serde_json::from_str(&key_str).map_err(Error::TendermintGenesisJson)?;
+    let pk_bytes = key["pub_key"]["value"]
+        .as_str()
+        .and_then(|pk| base64::decode(pk).ok())
+        .ok_or(Error::InvalidValidatorKey)?;
+    let validator_pk = ed25519_dalek::PublicKey::from_bytes(&pk_bytes)
+        .map_err(|_| Error::InvalidValidatorKey)?;
+
+    Ok(NodeIdentity {
+        node_id,
+        validator_pk,
+    })
+}
+
+/// Run a Tendermint command and check that it has succeeded.
+fn run_tendermint(args: &[&str]) -> Result<std::process::Output> {
+    let output = Command::new("tendermint")
+        .args(args)
+        .output()
+        .map_err(Error::TendermintInit)?;
+    if output.status.success() {
+        Ok(output)
+    } else {
+        Err(Error::TendermintCommand {
+            command: args.join(" "),
+            stderr: String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        })
+    }
+}
+
+/// Configure the addresses of a node of a network running on a single
+/// machine and connect it to the given persistent peers, formatted as
+/// `tcp://<node_id>@<host>:<port>`.
+pub fn configure_local_node(
+    home_dir: impl AsRef<Path>,
+    p2p_port: u16,
+    rpc_port: u16,
+    abci_address: SocketAddr,
+    persistent_peers: &[String],
+) -> Result<()> {
+    let path = home_dir.as_ref().join("config").join("config.toml");
+    let mut config = TendermintConfig::load_toml_file(&path)
+        .map_err(Error::TendermintLoadConfig)?;
+
+    let parse_address = |address: String| {
+        net::Address::from_str(&address)
+            .map_err(|_| Error::InvalidNodeAddress(address))
+    };
+    config.p2p.laddr = parse_address(format!("tcp://0.0.0.0:{}", p2p_port))?;
+    config.rpc.laddr = parse_address(format!("tcp://127.0.0.1:{}", rpc_port))?;
+    config.proxy_app =
+        parse_address(format!("tcp://127.0.0.1:{}", abci_address.port()))?;
+    config.p2p.persistent_peers = persistent_peers
+        .iter()
+        .cloned()
+        .map(parse_address)
+        .collect::<Result<_>>()?;
+    // All the nodes share the same IP address
+    config.p2p.allow_duplicate_ip = true;
+    config.p2p.addr_book_strict = false;
+
+    write_tendermint_config(path, &config)
+}
+
+/// Share the Tendermint genesis file of the first of the given home
+/// directories with the others, so that all the nodes start from the same
+/// genesis time. The initial validator set is left to the ledger's genesis.
+pub fn share_genesis(home_dirs: &[PathBuf], chain_id: &str) -> Result<()> {
+    let genesis_path =
+        |home_dir: &PathBuf| home_dir.join("config").join("genesis.json");
+    let first = match home_dirs.first() {
+        Some(first) => first,
+        None => return Ok(()),
+    };
+    let genesis_str = fs::read_to_string(genesis_path(first))
+        .map_err(Error::TendermintGenesisFile)?;
+    let mut genesis: serde_json::Value = serde_json::from_str(&genesis_str)
+        .map_err(Error::TendermintGenesisJson)?;
+    genesis["chain_id"] = json!(chain_id);
+    genesis["validators"] = json!([]);
+    for home_dir in home_dirs {
+        fs::write(genesis_path(home_dir), genesis.to_string())
+            .map_err(Error::TendermintGenesisFile)?;
+    }
+    Ok(())
+}
+
+/// Set the chain ID from the genesis in Tendermint's genesis file.
+fn write_chain_id(home_dir: impl AsRef<Path>, chain_id: &str) -> Result<()> {
+    let path = home_dir.as_ref().join("config").join("genesis.json");
+    let genesis_str =
+        fs::read_to_string(&path).map_err(Error::TendermintGenesisFile)?;
+    let mut genesis: serde_json::Value = serde_json::from_str(&genesis_str)
+        .map_err(Error::TendermintGenesisJson)?;
+    genesis["chain_id"] = json!(chain_id);
+    fs::write(path, genesis.to_string()).map_err(Error::TendermintGenesisFile)
+}
+
 #[cfg(feature = "dev")]
 fn write_validator_key(
     home_dir: impl AsRef<Path>,
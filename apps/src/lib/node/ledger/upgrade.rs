@@ -0,0 +1,66 @@
+//! The protocol upgrades supported by this binary and their storage
+//! migrations.
+//!
+//! To support an upgrade, add its name to the [`SUPPORTED_UPGRADES`] and
+//! register its storage migration, if any, in the [`MIGRATIONS`]. The
+//! migration runs at the beginning of the first block at the upgrade height,
+//! so that its changes are committed with the block.
+
+use anoma_shared::ledger::upgrade::UpgradePlan;
+use anoma_shared::types::BlockHeight;
+
+use super::storage::PersistentStorage;
+
+/// A storage migration applied at the upgrade height
+type Migration = fn(&mut PersistentStorage);
+
+/// The names of the upgrades supported by this binary
+const SUPPORTED_UPGRADES: &[&str] = &[];
+
+/// The storage migrations registered in this binary by their names
+const MIGRATIONS: &[(&str, Migration)] = &[];
+
+/// Check if this binary supports the planned upgrade, including its
+/// migration.
+pub fn is_supported(plan: &UpgradePlan) -> bool {
+    SUPPORTED_UPGRADES.contains(&plan.name.as_str())
+        && plan
+            .migration
+            .as_ref()
+            .map_or(true, |name| find_migration(name).is_some())
+}
+
+/// Get the planned upgrade that is due at or before the given height, if this
+/// binary doesn't support it.
+pub fn unsupported_upgrade(
+    storage: &PersistentStorage,
+    height: BlockHeight,
+) -> Option<UpgradePlan> {
+    UpgradePlan::read(storage)
+        .filter(|plan| plan.height <= height && !is_supported(plan))
+}
+
+/// At the upgrade height, run the planned upgrade's migration, if any, and
+/// remove the plan. The node must have halted before the upgrade height if
+/// this binary doesn't support it.
+pub fn begin_block(storage: &mut PersistentStorage, height: BlockHeight) {
+    let plan = match UpgradePlan::read(storage) {
+        Some(plan) if plan.height == height => plan,
+        _ => return,
+    };
+    if let Some(name) = &plan.migration {
+        let migrate = find_migration(name)
+            .expect("The upgrade's migration should be registered");
+        tracing::info!("Running the storage migration {}", name);
+        migrate(storage);
+    }
+    UpgradePlan::delete(storage);
+    tracing::info!("Applied the upgrade {} at height {}", plan.name, height.0);
+}
+
+fn find_migration(name: &str) -> Option<Migration> {
+    MIGRATIONS
+        .iter()
+        .find(|(migration, _)| *migration == name)
+        .map(|(_, migrate)| *migrate)
+}
@@ -72,6 +72,8 @@ pub fn key_of(name: impl AsRef<str>) -> Keypair {
         "a1qq5qqqqqxv6yydz9xc6ry33589q5x33eggcnjs2xx9znydj9xuens3phxppnwvzpg4rrqdpswve4n9" => bertha_keypair(),
         "a1qq5qqqqqxsuygd2x8pq5yw2ygdryxs6xgsmrsdzx8pryxv34gfrrssfjgccyg3zpxezrqd2y2s3g5s" => christel_keypair(),
         "a1qq5qqqqqxu6rvdzpxymnqwfkxfznvsjxggunyd3jg5erg3p3geqnvv35gep5yvzxx5m5x3fsfje8td" => matchmaker_keypair(),
+        #[cfg(feature = "dev")]
+        crate::genesis::DEV_VALIDATOR => crate::genesis::genesis().validator.keypair,
         other => {
             panic!("Dont' have keys for: {}", other)
         }
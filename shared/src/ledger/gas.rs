@@ -5,6 +5,8 @@ use std::convert::TryFrom;
 
 use thiserror::Error;
 
+use crate::ledger::parameters::{Parameters, BASIS_POINTS};
+
 #[allow(missing_docs)]
 #[derive(Error, Debug, Clone, PartialEq, Eq)]
 pub enum Error {
@@ -16,15 +18,22 @@ pub enum Error {
     GasOverflow,
 }
 
-const TX_GAS_PER_BYTE: u64 = 2;
 const COMPILE_GAS_PER_BYTE: u64 = 1;
 const BASE_TRANSACTION_FEE: u64 = 2;
-const PARALLEL_GAS_MULTIPLIER: f64 = 0.1;
 
-/// The maximum value should be less or equal to i64::MAX
-/// to avoid the gas overflow when sending this to ABCI
-const BLOCK_GAS_LIMIT: u64 = 10_000_000_000_000;
-const TRANSACTION_GAS_LIMIT: u64 = 10_000_000_000;
+/// The default gas cost per byte of a transaction
+pub const TX_GAS_PER_BYTE: u64 = 2;
+/// The default gas cost per byte of the storage grown by a transaction
+pub const STORAGE_GAS_PER_BYTE: u64 = 10;
+/// The default share of the gas of the VPs run in parallel with the most
+/// expensive VP that is added to the transaction's gas, in basis points
+pub const PARALLEL_GAS_MULTIPLIER: u64 = 1_000;
+
+/// The default maximum gas of a block. The maximum value should be less or
+/// equal to i64::MAX to avoid the gas overflow when sending this to ABCI
+pub const BLOCK_GAS_LIMIT: u64 = 10_000_000_000_000;
+/// The default maximum gas of a transaction
+pub const TRANSACTION_GAS_LIMIT: u64 = 10_000_000_000;
 
 /// The minimum gas cost for accessing the storage
 pub const MIN_STORAGE_GAS: u64 = 1;
@@ -38,6 +47,9 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub struct BlockGasMeter {
     block_gas: u64,
     transaction_gas: u64,
+    tx_gas_limit: u64,
+    block_gas_limit: u64,
+    tx_gas_per_byte: u64,
 }
 
 /// Gas metering in a validity predicate
@@ -45,6 +57,7 @@ pub struct BlockGasMeter {
 pub struct VpGasMeter {
     /// The gas used in the transaction before the VP run
     initial_gas: u64,
+    tx_gas_limit: u64,
     /// The current gas usage in the VP
     pub current_gas: u64,
     /// We store the `error` inside here, because when we run out of gas in VP
@@ -60,9 +73,23 @@ pub struct VpGasMeter {
 pub struct VpsGas {
     max: Option<u64>,
     rest: Vec<u64>,
+    tx_gas_limit: u64,
+    parallel_gas_multiplier: u64,
 }
 
 impl BlockGasMeter {
+    /// Initialize a new block gas meter with the limits and costs from the
+    /// protocol parameters.
+    pub fn new(params: &Parameters) -> Self {
+        Self {
+            block_gas: 0,
+            transaction_gas: 0,
+            tx_gas_limit: params.tx_gas_limit,
+            block_gas_limit: params.block_gas_limit,
+            tx_gas_per_byte: params.tx_gas_per_byte,
+        }
+    }
+
     /// Add gas cost for the current transaction. It will return error when the
     /// consumed gas exceeds the transaction gas limit, but the state will still
     /// be updated.
@@ -72,7 +99,7 @@ impl BlockGasMeter {
             .checked_add(gas)
             .ok_or(Error::GasOverflow)?;
 
-        if self.transaction_gas > TRANSACTION_GAS_LIMIT {
+        if self.transaction_gas > self.tx_gas_limit {
             return Err(Error::TransactionGasExceedededError);
         }
         Ok(())
@@ -83,7 +110,7 @@ impl BlockGasMeter {
     pub fn add_base_transaction_fee(&mut self, bytes_len: usize) -> Result<()> {
         tracing::info!("add_base_transaction_fee {}", bytes_len);
         self.add(BASE_TRANSACTION_FEE)?;
-        self.add(bytes_len as u64 * TX_GAS_PER_BYTE)
+        self.add(bytes_len as u64 * self.tx_gas_per_byte)
     }
 
     /// Add the compiling cost proportionate to the code length
@@ -103,7 +130,7 @@ impl BlockGasMeter {
 
         let transaction_gas = self.transaction_gas;
         self.transaction_gas = 0;
-        if self.block_gas > BLOCK_GAS_LIMIT {
+        if self.block_gas > self.block_gas_limit {
             return Err(Error::BlockGasExceeded);
         }
         Ok(transaction_gas)
@@ -129,9 +156,10 @@ impl BlockGasMeter {
 impl VpGasMeter {
     /// Initialize a new VP gas meter, starting with the gas consumed in the
     /// transaction so far.
-    pub fn new(initial_gas: u64) -> Self {
+    pub fn new(initial_gas: u64, params: &Parameters) -> Self {
         Self {
             initial_gas,
+            tx_gas_limit: params.tx_gas_limit,
             current_gas: 0,
             error: None,
         }
@@ -162,7 +190,7 @@ impl VpGasMeter {
                 return Err(err);
             }
         };
-        if current_total > TRANSACTION_GAS_LIMIT {
+        if current_total > self.tx_gas_limit {
             self.error = Some(Error::TransactionGasExceedededError);
             return Err(Error::TransactionGasExceedededError);
         }
@@ -171,6 +199,17 @@ impl VpGasMeter {
 }
 
 impl VpsGas {
+    /// Initialize the gas meter of VPs run in parallel with the limit and the
+    /// parallel gas multiplier from the protocol parameters.
+    pub fn new(params: &Parameters) -> Self {
+        Self {
+            max: None,
+            rest: Vec::new(),
+            tx_gas_limit: params.tx_gas_limit,
+            parallel_gas_multiplier: params.parallel_gas_multiplier,
+        }
+    }
+
     /// Set the gas cost from a single VP run.
     pub fn set(&mut self, vp_gas_meter: &VpGasMeter) -> Result<()> {
         debug_assert_eq!(self.max, None);
@@ -208,7 +247,7 @@ impl VpsGas {
         let total = initial_gas
             .checked_add(self.get_current_gas()?)
             .ok_or(Error::GasOverflow)?;
-        if total > TRANSACTION_GAS_LIMIT {
+        if total > self.tx_gas_limit {
             return Err(Error::GasOverflow);
         }
         Ok(())
@@ -216,30 +255,31 @@ impl VpsGas {
 
     /// Get the gas consumed by the parallelized VPs
     fn get_current_gas(&self) -> Result<u64> {
+        let rest = self
+            .rest
+            .iter()
+            .try_fold(0_u64, |sum, gas| sum.checked_add(*gas))
+            .ok_or(Error::GasOverflow)?;
+        let parallel_gas = rest as u128 * self.parallel_gas_multiplier as u128
+            / BASIS_POINTS as u128;
         let parallel_gas =
-            self.rest.iter().sum::<u64>() as f64 * PARALLEL_GAS_MULTIPLIER;
+            u64::try_from(parallel_gas).map_err(|_| Error::GasOverflow)?;
         self.max
             .unwrap_or_default()
-            .checked_add(parallel_gas as u64)
+            .checked_add(parallel_gas)
             .ok_or(Error::GasOverflow)
     }
 }
 
 impl Default for BlockGasMeter {
     fn default() -> Self {
-        BlockGasMeter {
-            block_gas: 0,
-            transaction_gas: 0,
-        }
+        Self::new(&Parameters::default())
     }
 }
 
 impl Default for VpsGas {
     fn default() -> Self {
-        Self {
-            max: None,
-            rest: Vec::new(),
-        }
+        Self::new(&Parameters::default())
     }
 }
 
@@ -259,7 +299,7 @@ mod tests {
     proptest! {
         #[test]
         fn test_vp_gas_meter_add(gas in 0..TRANSACTION_GAS_LIMIT) {
-            let mut meter = VpGasMeter::new(0);
+            let mut meter = VpGasMeter::new(0, &Parameters::default());
             meter.add(gas).expect("cannot add the gas");
             assert_eq!(meter.error, None);
         }
@@ -275,7 +315,7 @@ mod tests {
 
     #[test]
     fn test_vp_gas_overflow() {
-        let mut meter = VpGasMeter::new(1);
+        let mut meter = VpGasMeter::new(1, &Parameters::default());
         match meter.add(u64::MAX).expect_err("unexpectedly succeeded") {
             err @ Error::GasOverflow => assert_eq!(meter.error, Some(err)),
             _ => panic!("unexpected error happened"),
@@ -284,7 +324,7 @@ mod tests {
 
     #[test]
     fn test_vp_gas_limit() {
-        let mut meter = VpGasMeter::new(1);
+        let mut meter = VpGasMeter::new(1, &Parameters::default());
         match meter
             .add(TRANSACTION_GAS_LIMIT)
             .expect_err("unexpectedly succeeded")
@@ -344,6 +384,53 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_gas_meters_use_parameters() {
+        let params = Parameters {
+            tx_gas_limit: 10,
+            block_gas_limit: 15,
+            tx_gas_per_byte: 3,
+            parallel_gas_multiplier: 5_000,
+            ..Parameters::default()
+        };
+        let mut meter = BlockGasMeter::new(&params);
+        meter
+            .add_base_transaction_fee(2)
+            .expect("under the tx gas limit");
+        assert_eq!(
+            meter.get_current_transaction_gas(),
+            BASE_TRANSACTION_FEE + 6
+        );
+        meter
+            .finalize_transaction()
+            .expect("under the block gas limit");
+        meter
+            .add(params.tx_gas_limit)
+            .expect("under the tx gas limit");
+        assert_eq!(meter.finalize_transaction(), Err(Error::BlockGasExceeded));
+
+        let mut vp_meter = VpGasMeter::new(1, &params);
+        assert_eq!(
+            vp_meter.add(params.tx_gas_limit),
+            Err(Error::TransactionGasExceedededError)
+        );
+
+        let mut vps_gas = VpsGas::new(&params);
+        let mut other = VpsGas::new(&params);
+        vps_gas.set(&VpGasMeter::new(0, &params)).unwrap();
+        let mut vp_meter = VpGasMeter::new(0, &params);
+        vp_meter.add(4).unwrap();
+        other.set(&vp_meter).unwrap();
+        let mut vp_meter = VpGasMeter::new(0, &params);
+        vp_meter.add(3).unwrap();
+        let mut another = VpsGas::new(&params);
+        another.set(&vp_meter).unwrap();
+        vps_gas.merge(&mut other, 0).unwrap();
+        vps_gas.merge(&mut another, 0).unwrap();
+        // the max is 4 and a half of the rest (0 + 3)
+        assert_eq!(vps_gas.get_current_gas(), Ok(5));
+    }
+
     /// Test that the function [`as_i64`] cannot fail for transaction and block
     /// gas limit + some "tolerance" for gas exhaustion.
     #[test]
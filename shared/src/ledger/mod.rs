@@ -1,4 +1,8 @@
 //! The ledger modules
 
 pub mod gas;
+pub mod native_vp;
+pub mod parameters;
 pub mod storage;
+pub mod storage_usage;
+pub mod upgrade;
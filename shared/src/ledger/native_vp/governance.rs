@@ -0,0 +1,106 @@
+//! The native validity predicate of the governance system.
+
+use super::{is_other_vp_key, Ctx, Result};
+use crate::ledger::storage::{self, StorageHasher};
+use crate::types::governance::{self, GovParams, Proposal, ProposalId};
+use crate::types::pos::{self, Bonds, Epoch, EpochState};
+use crate::types::token::{self, Amount, Change};
+use crate::types::Key;
+
+/// The governance validity predicate. The parameters and the proposals'
+/// results can only be changed by the protocol. A new proposal must take the
+/// next ID, start in the current epoch and lock the deposit in the governance
+/// account. The votes can only be cast in the proposals' voting period by
+/// accounts with bonded stake.
+pub fn validate_tx<DB, H>(
+    ctx: &Ctx<'_, DB, H>,
+    keys_changed: &[Key],
+) -> Result<bool>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let gov_addr = governance::governance_address();
+    let balance_key = token::balance_key(&pos::staking_token(), &gov_addr);
+    let params: GovParams = ctx
+        .read_pre_value(&governance::params_key())?
+        .unwrap_or_default();
+    let epoch: Epoch = ctx
+        .read_pre_value(&pos::epoch_key())?
+        .map(|state: EpochState| state.epoch)
+        .unwrap_or_default();
+
+    let mut new_proposals: Vec<ProposalId> = vec![];
+    let mut deposits: Change = 0;
+    let mut balance_change: Change = 0;
+    let mut next_id_changed = false;
+    for key in keys_changed {
+        if let Some(id) = governance::is_proposal_key(key) {
+            let proposal: Option<Proposal> = ctx.read_post_value(key)?;
+            let valid = match &proposal {
+                Some(proposal) if !ctx.has_key_pre(key)? => {
+                    proposal.start_epoch == epoch
+                        && proposal.end_epoch == epoch + params.voting_period
+                        && u64::from(proposal.deposit) >= params.min_deposit
+                        && params.check_content(&proposal.content).is_ok()
+                }
+                _ => false,
+            };
+            if !valid {
+                tracing::info!("Invalid proposal: {}", key);
+                return Ok(false);
+            }
+            if let Some(proposal) = proposal {
+                deposits += proposal.deposit.change();
+            }
+            new_proposals.push(id);
+        } else if let Some((id, voter)) = governance::is_vote_key(key) {
+            let proposal: Option<Proposal> =
+                ctx.read_pre_value(&governance::proposal_key(id))?;
+            let is_voting = matches!(
+                proposal,
+                Some(proposal) if proposal.is_voting(epoch)
+            );
+            let bonds: Bonds = ctx
+                .read_pre_value(&pos::bond_key(voter))?
+                .unwrap_or_default();
+            if !is_voting || bonds.is_empty() || !ctx.has_key_post(key)? {
+                tracing::info!("Invalid vote: {}", key);
+                return Ok(false);
+            }
+        } else if key == &governance::next_proposal_id_key() {
+            next_id_changed = true;
+        } else if key == &balance_key {
+            let pre: Amount = ctx.read_pre_value(key)?.unwrap_or_default();
+            let post: Amount = ctx.read_post_value(key)?.unwrap_or_default();
+            balance_change = post.change() - pre.change();
+        } else if !is_other_vp_key(&gov_addr, key) {
+            tracing::info!("Unexpected governance key: {}", key);
+            return Ok(false);
+        }
+    }
+
+    let id_key = governance::next_proposal_id_key();
+    let next_id: ProposalId = ctx.read_pre_value(&id_key)?.unwrap_or_default();
+    let next_id_post: ProposalId =
+        ctx.read_post_value(&id_key)?.unwrap_or_default();
+    let valid_ids = match &new_proposals[..] {
+        [] => !next_id_changed,
+        [id] => *id == next_id && next_id_post == next_id + 1,
+        _ => false,
+    };
+    if !valid_ids {
+        tracing::info!("The new proposal must take the next ID {}", next_id);
+        return Ok(false);
+    }
+    if balance_change != deposits {
+        tracing::info!(
+            "The change {} of the governance balance doesn't match the \
+             deposits {}",
+            balance_change,
+            deposits
+        );
+        return Ok(false);
+    }
+    Ok(true)
+}
@@ -0,0 +1,305 @@
+//! Native validity predicates of the internal addresses. They are executed by
+//! the ledger directly, rather than loaded from the storage and run as wasm,
+//! but they have the same access to the prior and posterior state as the wasm
+//! VPs and they are charged gas the same way.
+
+pub mod governance;
+pub mod pos;
+pub mod token_registry;
+
+use std::cell::RefCell;
+
+use borsh::BorshDeserialize;
+use thiserror::Error;
+
+use crate::ledger::gas::{self, VpGasMeter};
+use crate::ledger::storage::write_log::{StorageModification, WriteLog};
+use crate::ledger::storage::{self, Storage, StorageHasher};
+use crate::types::address::{Address, InternalAddress};
+use crate::types::{BlockHash, BlockHeight, Key};
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Storage error: {0}")]
+    StorageError(storage::Error),
+    #[error("Gas error: {0}")]
+    GasError(gas::Error),
+    #[error("Error decoding the value of the storage key {0}: {1}")]
+    DecodingError(Key, std::io::Error),
+}
+
+/// Result of a native validity predicate
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The host environment of a native validity predicate, with read-only access
+/// to the state before and after the transaction. Every access is charged to
+/// the VP's gas meter, as in the wasm VPs' host environment.
+pub struct Ctx<'a, DB, H>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    /// The storage with the state before the transaction
+    pub storage: &'a Storage<DB, H>,
+    /// The write log with the transaction's changes
+    pub write_log: &'a WriteLog,
+    gas_meter: RefCell<&'a mut VpGasMeter>,
+}
+
+impl<'a, DB, H> Ctx<'a, DB, H>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    /// Initialize a new context for a native VP run.
+    pub fn new(
+        storage: &'a Storage<DB, H>,
+        write_log: &'a WriteLog,
+        gas_meter: &'a mut VpGasMeter,
+    ) -> Self {
+        Self {
+            storage,
+            write_log,
+            gas_meter: RefCell::new(gas_meter),
+        }
+    }
+
+    /// Add a gas cost incured in the validity predicate.
+    pub fn add_gas(&self, used_gas: u64) -> Result<()> {
+        self.gas_meter
+            .borrow_mut()
+            .add(used_gas)
+            .map_err(Error::GasError)
+    }
+
+    /// Storage read prior state (before tx execution). It will try to read
+    /// from the storage.
+    pub fn read_pre(&self, key: &Key) -> Result<Option<Vec<u8>>> {
+        self.add_gas(key.len() as _)?;
+        let (value, gas) =
+            self.storage.read(key).map_err(Error::StorageError)?;
+        self.add_gas(gas)?;
+        if let Some(value) = &value {
+            self.add_gas(value.len() as _)?;
+        }
+        Ok(value)
+    }
+
+    /// Storage read posterior state (after tx execution). It will try to read
+    /// from the write log first and if no entry found then from the storage.
+    pub fn read_post(&self, key: &Key) -> Result<Option<Vec<u8>>> {
+        self.add_gas(key.len() as _)?;
+        let (log_val, gas) = self.write_log.read(key);
+        self.add_gas(gas)?;
+        let value = match log_val {
+            Some(StorageModification::Write { value }) => Some(value.clone()),
+            Some(StorageModification::Delete) => None,
+            Some(StorageModification::InitAccount { vp }) => Some(vp.clone()),
+            None => {
+                let (value, gas) =
+                    self.storage.read(key).map_err(Error::StorageError)?;
+                self.add_gas(gas)?;
+                value
+            }
+        };
+        if let Some(value) = &value {
+            self.add_gas(value.len() as _)?;
+        }
+        Ok(value)
+    }
+
+    /// Read and decode the prior state value of the key, if any.
+    pub fn read_pre_value<T: BorshDeserialize>(
+        &self,
+        key: &Key,
+    ) -> Result<Option<T>> {
+        self.read_pre(key)?
+            .map(|bytes| decode(key, &bytes[..]))
+            .transpose()
+    }
+
+    /// Read and decode the posterior state value of the key, if any.
+    pub fn read_post_value<T: BorshDeserialize>(
+        &self,
+        key: &Key,
+    ) -> Result<Option<T>> {
+        self.read_post(key)?
+            .map(|bytes| decode(key, &bytes[..]))
+            .transpose()
+    }
+
+    /// Storage `has_key` in prior state (before tx execution). It will try to
+    /// read from the storage.
+    pub fn has_key_pre(&self, key: &Key) -> Result<bool> {
+        self.add_gas(key.len() as _)?;
+        let (present, gas) =
+            self.storage.has_key(key).map_err(Error::StorageError)?;
+        self.add_gas(gas)?;
+        Ok(present)
+    }
+
+    /// Storage `has_key` in posterior state (after tx execution). It will try
+    /// to check the write log first and if no entry found then the storage.
+    pub fn has_key_post(&self, key: &Key) -> Result<bool> {
+        self.add_gas(key.len() as _)?;
+        let (log_val, gas) = self.write_log.read(key);
+        self.add_gas(gas)?;
+        match log_val {
+            Some(StorageModification::Write { .. })
+            | Some(StorageModification::InitAccount { .. }) => Ok(true),
+            Some(StorageModification::Delete) => Ok(false),
+            None => {
+                let (present, gas) =
+                    self.storage.has_key(key).map_err(Error::StorageError)?;
+                self.add_gas(gas)?;
+                Ok(present)
+            }
+        }
+    }
+
+    /// Getting the chain ID.
+    pub fn get_chain_id(&self) -> Result<String> {
+        let (chain_id, gas) = self.storage.get_chain_id();
+        self.add_gas(gas)?;
+        Ok(chain_id)
+    }
+
+    /// Getting the block height. The height is that of the block to which the
+    /// current transaction is being applied.
+    pub fn get_block_height(&self) -> Result<BlockHeight> {
+        let (height, gas) = self.storage.get_block_height();
+        self.add_gas(gas)?;
+        Ok(height)
+    }
+
+    /// Getting the block hash. The hash is that of the block to which the
+    /// current transaction is being applied.
+    pub fn get_block_hash(&self) -> Result<BlockHash> {
+        let (hash, gas) = self.storage.get_block_hash();
+        self.add_gas(gas)?;
+        Ok(hash)
+    }
+}
+
+fn decode<T: BorshDeserialize>(key: &Key, bytes: &[u8]) -> Result<T> {
+    T::try_from_slice(bytes)
+        .map_err(|err| Error::DecodingError(key.clone(), err))
+}
+
+/// Run the native validity predicate of the internal address on the keys
+/// changed by a transaction. Returns whether the transaction is accepted.
+pub fn validate_tx<DB, H>(
+    address: InternalAddress,
+    ctx: &Ctx<'_, DB, H>,
+    keys_changed: &[Key],
+) -> Result<bool>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    match address {
+        // only the protocol can change the parameters, the upgrade plan and
+        // the storage usage
+        InternalAddress::Parameters
+        | InternalAddress::Upgrade
+        | InternalAddress::StorageUsage => {
+            Ok(is_unchanged(&Address::Internal(address), keys_changed))
+        }
+        InternalAddress::PoS => pos::validate_tx(ctx, keys_changed),
+        InternalAddress::Governance => {
+            governance::validate_tx(ctx, keys_changed)
+        }
+        InternalAddress::TokenRegistry => {
+            token_registry::validate_tx(ctx, keys_changed)
+        }
+    }
+}
+
+/// Check that none of the changed keys belong to the address. The VPs of the
+/// accounts initialized by a transaction are given to every verifier, so they
+/// may be among the changed keys.
+fn is_unchanged(address: &Address, keys_changed: &[Key]) -> bool {
+    let unchanged = keys_changed
+        .iter()
+        .all(|key| !key.find_addresses().contains(address));
+    if !unchanged {
+        tracing::info!("The internal address {} cannot be changed", address);
+    }
+    unchanged
+}
+
+/// Check if the key is the VP of an account initialized by the transaction,
+/// other than the given internal address.
+fn is_other_vp_key(address: &Address, key: &Key) -> bool {
+    matches!(key.is_validity_predicate(), Some(owner) if owner != address)
+}
+
+#[cfg(test)]
+mod tests {
+    use borsh::BorshSerialize;
+
+    use super::*;
+    use crate::ledger::parameters::{self, Parameters};
+    use crate::ledger::storage::testing::TestStorage;
+    use crate::types::token;
+
+    #[test]
+    fn test_native_vp_ctx() {
+        let mut storage = TestStorage::default();
+        let key = parameters::parameters_key();
+        storage.write(&key, vec![1]).unwrap();
+        let mut write_log = WriteLog::default();
+        write_log.write(&key, vec![2]);
+        let mut gas_meter = VpGasMeter::new(0, &Parameters::default());
+
+        let ctx = Ctx::new(&storage, &write_log, &mut gas_meter);
+        assert_eq!(ctx.read_pre(&key).unwrap(), Some(vec![1]));
+        assert_eq!(ctx.read_post(&key).unwrap(), Some(vec![2]));
+        assert!(ctx.has_key_pre(&key).unwrap());
+        assert!(ctx.has_key_post(&key).unwrap());
+        // the parameters can only be changed by the protocol
+        let keys_changed = write_log.get_keys();
+        assert!(
+            !validate_tx(InternalAddress::Parameters, &ctx, &keys_changed)
+                .unwrap()
+        );
+        assert!(validate_tx(InternalAddress::Parameters, &ctx, &[]).unwrap());
+        drop(ctx);
+        assert!(gas_meter.current_gas > 0);
+    }
+
+    #[test]
+    fn test_token_registry_vp() {
+        let storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let (token, _gas) =
+            write_log.init_account(&storage.address_gen, vec![]);
+        let metadata = token::TokenMetadata::new("XAN", "Anoma", 6).unwrap();
+        let metadata = metadata.try_to_vec().unwrap();
+        write_log.write(&token::metadata_key(&token), metadata.clone());
+        let registry_key = token::registry_key(&token);
+        write_log.write(&registry_key, metadata);
+        let mut gas_meter = VpGasMeter::new(0, &Parameters::default());
+
+        let ctx = Ctx::new(&storage, &write_log, &mut gas_meter);
+        let keys_changed = vec![registry_key.clone()];
+        assert!(validate_tx(
+            InternalAddress::TokenRegistry,
+            &ctx,
+            &keys_changed
+        )
+        .unwrap());
+        drop(ctx);
+
+        // the entry must hold the token's metadata
+        write_log.write(&registry_key, vec![]);
+        let ctx = Ctx::new(&storage, &write_log, &mut gas_meter);
+        assert!(!validate_tx(
+            InternalAddress::TokenRegistry,
+            &ctx,
+            &keys_changed
+        )
+        .unwrap());
+    }
+}
@@ -0,0 +1,111 @@
+//! The native validity predicate of the proof-of-stake system.
+
+use super::{is_other_vp_key, Ctx, Result};
+use crate::ledger::storage::{self, StorageHasher};
+use crate::types::address::Address;
+use crate::types::pos::{
+    self, CommissionRate, Epoch, EpochState, PosChanges, PosParams,
+};
+use crate::types::token::{self, Amount};
+use crate::types::Key;
+
+/// The PoS validity predicate. The parameters, the validator set, the epoch,
+/// the reward pool and the slashes can only be changed by the protocol. A
+/// validator can register its consensus key only once, while its own validity
+/// predicate authorizes the registration, the changes of its commission rate
+/// and its unjailing after the jail period. The changes of the bonds, unbonds,
+/// rewards and the PoS account's balance must be consistent with each other.
+pub fn validate_tx<DB, H>(
+    ctx: &Ctx<'_, DB, H>,
+    keys_changed: &[Key],
+) -> Result<bool>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let pos_addr = pos::pos_address();
+    let balance_key = token::balance_key(&pos::staking_token(), &pos_addr);
+    let params: PosParams =
+        ctx.read_pre_value(&pos::params_key())?.unwrap_or_default();
+    let epoch = ctx
+        .read_pre_value(&pos::epoch_key())?
+        .map(|state: EpochState| state.epoch)
+        .unwrap_or_default();
+    let mut changes = PosChanges::default();
+    for key in keys_changed {
+        if let Some(source) = pos::is_bond_key(key) {
+            changes.bonds.push((
+                source.clone(),
+                ctx.read_pre_value(key)?.unwrap_or_default(),
+                ctx.read_post_value(key)?.unwrap_or_default(),
+            ));
+        } else if let Some(source) = pos::is_unbond_key(key) {
+            changes.unbonds.push((
+                source.clone(),
+                ctx.read_pre_value(key)?.unwrap_or_default(),
+                ctx.read_post_value(key)?.unwrap_or_default(),
+            ));
+        } else if key == &pos::total_bonds_key() {
+            changes.total_bonds_pre =
+                ctx.read_pre_value(key)?.unwrap_or_default();
+            changes.total_bonds_post =
+                ctx.read_post_value(key)?.unwrap_or_default();
+        } else if let Some(owner) = pos::is_rewards_key(key) {
+            changes.rewards.push((
+                owner.clone(),
+                ctx.read_pre_value(key)?.unwrap_or_default(),
+                ctx.read_post_value(key)?.unwrap_or_default(),
+            ));
+        } else if key == &balance_key {
+            let pre: Amount = ctx.read_pre_value(key)?.unwrap_or_default();
+            let post: Amount = ctx.read_post_value(key)?.unwrap_or_default();
+            changes.balance_change = post.change() - pre.change();
+        } else if pos::is_consensus_key_key(key).is_some() {
+            if ctx.has_key_pre(key)? || !ctx.has_key_post(key)? {
+                tracing::info!(
+                    "A consensus key cannot be changed or removed: {}",
+                    key
+                );
+                return Ok(false);
+            }
+        } else if let Some(validator) = pos::is_commission_rate_key(key) {
+            let registered =
+                ctx.has_key_post(&pos::consensus_key_key(validator))?;
+            let rate: Option<CommissionRate> = ctx.read_post_value(key)?;
+            match rate {
+                Some(rate) if registered && rate.validate().is_ok() => {}
+                _ => {
+                    tracing::info!("Invalid commission rate change: {}", key);
+                    return Ok(false);
+                }
+            }
+        } else if pos::is_jailed_until_key(key).is_some() {
+            let until: Option<Epoch> = ctx.read_pre_value(key)?;
+            let can_unjail = matches!(until, Some(until) if epoch >= until);
+            if !can_unjail || ctx.has_key_post(key)? {
+                tracing::info!(
+                    "A validator can only be unjailed after its jail period: \
+                     {}",
+                    key
+                );
+                return Ok(false);
+            }
+        } else if !is_other_vp_key(&pos_addr, key) {
+            tracing::info!("Unexpected PoS key: {}", key);
+            return Ok(false);
+        }
+    }
+
+    // a gas error is kept in the gas meter and checked after the VP's run
+    let is_registered = |validator: &Address| {
+        ctx.has_key_post(&pos::consensus_key_key(validator))
+            .unwrap_or(false)
+    };
+    match changes.validate(&params, epoch, is_registered) {
+        Ok(()) => Ok(true),
+        Err(err) => {
+            tracing::info!("Invalid PoS changes: {}", err);
+            Ok(false)
+        }
+    }
+}
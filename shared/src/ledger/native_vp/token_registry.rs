@@ -0,0 +1,40 @@
+//! The native validity predicate of the token registry.
+
+use super::{is_other_vp_key, Ctx, Result};
+use crate::ledger::storage::{self, StorageHasher};
+use crate::types::{token, Key};
+
+/// The token registry validity predicate. A token can only be registered by
+/// the transaction that initializes it, with the token's metadata, and the
+/// registered tokens cannot be changed or removed.
+pub fn validate_tx<DB, H>(
+    ctx: &Ctx<'_, DB, H>,
+    keys_changed: &[Key],
+) -> Result<bool>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let registry_addr = token::token_registry_address();
+    for key in keys_changed {
+        if let Some(token) = token::is_registry_key(key) {
+            let vp_key = Key::validity_predicate(token)
+                .expect("Unable to create a validity predicate key");
+            let initialized =
+                !ctx.has_key_pre(&vp_key)? && ctx.has_key_post(&vp_key)?;
+            let entry = ctx.read_post(key)?;
+            let valid = initialized
+                && !ctx.has_key_pre(key)?
+                && entry.is_some()
+                && entry == ctx.read_post(&token::metadata_key(token))?;
+            if !valid {
+                tracing::info!("Invalid token registration: {}", key);
+                return Ok(false);
+            }
+        } else if !is_other_vp_key(&registry_addr, key) {
+            tracing::info!("Unexpected token registry key: {}", key);
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
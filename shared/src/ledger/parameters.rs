@@ -0,0 +1,332 @@
+//! Protocol parameters, set at genesis and stored under a reserved address.
+//! The ledger reads them from the storage at the beginning of every block and
+//! passes them to the gas meters and the wasm runners, so they can be changed
+//! by a governance proposal.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use thiserror::Error;
+
+use crate::ledger::gas;
+use crate::ledger::storage::{self, Storage, StorageHasher};
+use crate::types::address::{self, InternalAddress};
+use crate::types::{token, Address, DbKeySeg, Key, KeySeg};
+
+// The bounds are set in number of pages, the actual size is multiplied by
+// `wasmer::WASM_PAGE_SIZE = 64kiB`.
+/// The default maximum memory of a transaction wasm instance in pages
+pub const TX_MEMORY_MAX_PAGES: u32 = 200; // 12.8 MiB
+/// The default maximum memory of a validity predicate wasm instance in pages
+pub const VP_MEMORY_MAX_PAGES: u32 = 200; // 12.8 MiB
+/// The maximum number of pages of a 32-bit wasm memory
+const WASM_MAX_PAGES: u32 = 65_536;
+
+/// 100% in basis points
+pub const BASIS_POINTS: u16 = 10_000;
+
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error("The transaction gas limit must be greater than zero")]
+    ZeroTxGasLimit,
+    #[error(
+        "The transaction gas limit {tx_gas_limit} must not exceed the block \
+         gas limit {block_gas_limit}"
+    )]
+    TxGasLimitExceedsBlock {
+        tx_gas_limit: u64,
+        block_gas_limit: u64,
+    },
+    #[error("The block gas limit {0} doesn't fit in a signed 64-bit integer")]
+    BlockGasLimitTooLarge(u64),
+    #[error("The parallel gas multiplier of {0} basis points exceeds 100%")]
+    InvalidParallelGasMultiplier(u64),
+    #[error("The {0} memory maximum of {1} pages must be between 1 and 65536")]
+    InvalidMemoryMaxPages(&'static str, u32),
+    #[error(
+        "Invalid code hash {0} in a whitelist, expected a hex-encoded SHA-256 \
+         hash"
+    )]
+    InvalidCodeHash(String),
+}
+
+/// Protocol parameters
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+#[serde(default)]
+pub struct Parameters {
+    /// The maximum gas that a single transaction may use
+    pub tx_gas_limit: u64,
+    /// The maximum gas that all the transactions in a block may use
+    pub block_gas_limit: u64,
+    /// The gas cost per byte of a transaction
+    pub tx_gas_per_byte: u64,
+    /// The share of the gas of the validity predicates run in parallel with
+    /// the most expensive one that is added to the transaction's gas, in
+    /// basis points
+    pub parallel_gas_multiplier: u64,
+    /// The maximum memory of a transaction wasm instance in pages
+    pub tx_memory_max_pages: u32,
+    /// The maximum memory of a validity predicate wasm instance in pages
+    pub vp_memory_max_pages: u32,
+    /// The hex-encoded SHA-256 hashes of the transaction codes allowed to be
+    /// applied. Any code is allowed when the list is empty.
+    pub tx_whitelist: Vec<String>,
+    /// The hex-encoded SHA-256 hashes of the validity predicate codes allowed
+    /// to be set for accounts. Any code is allowed when the list is empty.
+    pub vp_whitelist: Vec<String>,
+    /// The gas cost per byte of the storage grown by a transaction
+    pub storage_gas_per_byte: u64,
+    /// The refundable deposit per byte of storage used by an account, which
+    /// is held while the bytes are stored. The deposits are disabled when
+    /// it's zero.
+    pub storage_deposit_per_byte: token::Amount,
+    /// The token in which the storage deposits are paid
+    pub storage_deposit_token: Address,
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Self {
+            tx_gas_limit: gas::TRANSACTION_GAS_LIMIT,
+            block_gas_limit: gas::BLOCK_GAS_LIMIT,
+            tx_gas_per_byte: gas::TX_GAS_PER_BYTE,
+            parallel_gas_multiplier: gas::PARALLEL_GAS_MULTIPLIER,
+            tx_memory_max_pages: TX_MEMORY_MAX_PAGES,
+            vp_memory_max_pages: VP_MEMORY_MAX_PAGES,
+            tx_whitelist: vec![],
+            vp_whitelist: vec![],
+            storage_gas_per_byte: gas::STORAGE_GAS_PER_BYTE,
+            storage_deposit_per_byte: token::Amount::default(),
+            storage_deposit_token: address::xan(),
+        }
+    }
+}
+
+impl Parameters {
+    /// Check that the parameters are consistent.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.tx_gas_limit == 0 {
+            return Err(Error::ZeroTxGasLimit);
+        }
+        // the block gas is reported to Tendermint as `i64`
+        if self.block_gas_limit > i64::MAX as u64 {
+            return Err(Error::BlockGasLimitTooLarge(self.block_gas_limit));
+        }
+        if self.tx_gas_limit > self.block_gas_limit {
+            return Err(Error::TxGasLimitExceedsBlock {
+                tx_gas_limit: self.tx_gas_limit,
+                block_gas_limit: self.block_gas_limit,
+            });
+        }
+        if self.parallel_gas_multiplier > BASIS_POINTS as u64 {
+            return Err(Error::InvalidParallelGasMultiplier(
+                self.parallel_gas_multiplier,
+            ));
+        }
+        for &(kind, max_pages) in &[
+            ("transaction", self.tx_memory_max_pages),
+            ("validity predicate", self.vp_memory_max_pages),
+        ] {
+            if max_pages == 0 || max_pages > WASM_MAX_PAGES {
+                return Err(Error::InvalidMemoryMaxPages(kind, max_pages));
+            }
+        }
+        for hash in self.tx_whitelist.iter().chain(&self.vp_whitelist) {
+            let is_valid =
+                hash.len() == 64 && hash.chars().all(|c| c.is_ascii_hexdigit());
+            if !is_valid {
+                return Err(Error::InvalidCodeHash(hash.clone()));
+            }
+        }
+        Ok(())
+    }
+
+    /// Check if the storage deposits are enabled.
+    pub fn is_storage_deposit_enabled(&self) -> bool {
+        self.storage_deposit_per_byte != token::Amount::default()
+    }
+
+    /// Check if the transaction code is allowed by the transaction whitelist.
+    pub fn is_tx_allowed(&self, code: &[u8]) -> bool {
+        is_whitelisted(&self.tx_whitelist, code)
+    }
+
+    /// Check if the validity predicate code is allowed by the validity
+    /// predicate whitelist.
+    pub fn is_vp_allowed(&self, code: &[u8]) -> bool {
+        is_whitelisted(&self.vp_whitelist, code)
+    }
+
+    /// Write the parameters to the storage.
+    pub fn init_storage<DB, H>(&self, storage: &mut Storage<DB, H>)
+    where
+        DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+        H: StorageHasher,
+    {
+        let bytes = self
+            .try_to_vec()
+            .expect("Encoding the protocol parameters shouldn't fail");
+        storage
+            .write(&parameters_key(), bytes)
+            .expect("Unable to write the protocol parameters");
+    }
+
+    /// Read the parameters from the storage. Returns the default parameters
+    /// if they haven't been written.
+    pub fn read<DB, H>(storage: &Storage<DB, H>) -> Self
+    where
+        DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+        H: StorageHasher,
+    {
+        let (value, _gas) = storage
+            .read(&parameters_key())
+            .expect("Unable to read the protocol parameters");
+        value
+            .map(|bytes| {
+                Self::try_from_slice(&bytes[..])
+                    .expect("Decoding the protocol parameters shouldn't fail")
+            })
+            .unwrap_or_default()
+    }
+}
+
+/// Get the hex-encoded SHA-256 hash of a wasm code, as used in the code
+/// whitelists.
+pub fn code_hash(code: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(code))
+}
+
+fn is_whitelisted(whitelist: &[String], code: &[u8]) -> bool {
+    if whitelist.is_empty() {
+        return true;
+    }
+    let hash = code_hash(code);
+    whitelist
+        .iter()
+        .any(|allowed| allowed.eq_ignore_ascii_case(&hash))
+}
+
+/// The internal address of the protocol parameters. Its native validity
+/// predicate rejects any changes, so only the protocol can modify the
+/// parameters.
+pub fn parameters_address() -> Address {
+    Address::Internal(InternalAddress::Parameters)
+}
+
+const PARAMETERS_STORAGE_KEY: &str = "parameters";
+
+/// Obtain the storage key of the protocol parameters.
+pub fn parameters_key() -> Key {
+    Key::from(parameters_address().to_db_key())
+        .push(&PARAMETERS_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is the protocol parameters key.
+pub fn is_parameters_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(key)]
+        if addr == &parameters_address() && key == PARAMETERS_STORAGE_KEY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestStorage;
+
+    #[test]
+    fn test_parameters_validation() {
+        assert_eq!(Parameters::default().validate(), Ok(()));
+
+        let params = Parameters {
+            tx_gas_limit: 0,
+            ..Parameters::default()
+        };
+        assert_eq!(params.validate(), Err(Error::ZeroTxGasLimit));
+
+        let params = Parameters {
+            tx_gas_limit: 11,
+            block_gas_limit: 10,
+            ..Parameters::default()
+        };
+        assert!(matches!(
+            params.validate(),
+            Err(Error::TxGasLimitExceedsBlock { .. })
+        ));
+
+        let params = Parameters {
+            parallel_gas_multiplier: 10_001,
+            ..Parameters::default()
+        };
+        assert_eq!(
+            params.validate(),
+            Err(Error::InvalidParallelGasMultiplier(10_001))
+        );
+
+        let params = Parameters {
+            vp_memory_max_pages: 0,
+            ..Parameters::default()
+        };
+        assert_eq!(
+            params.validate(),
+            Err(Error::InvalidMemoryMaxPages("validity predicate", 0))
+        );
+
+        let params = Parameters {
+            vp_whitelist: vec!["not a hash".to_owned()],
+            ..Parameters::default()
+        };
+        assert_eq!(
+            params.validate(),
+            Err(Error::InvalidCodeHash("not a hash".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_code_whitelists() {
+        let code = b"some wasm code";
+        let other_code = b"some other wasm code";
+        let params = Parameters::default();
+        assert!(params.is_tx_allowed(code));
+        assert!(params.is_vp_allowed(code));
+
+        let params = Parameters {
+            tx_whitelist: vec![code_hash(code).to_uppercase()],
+            vp_whitelist: vec![code_hash(other_code)],
+            ..Parameters::default()
+        };
+        assert_eq!(params.validate(), Ok(()));
+        assert!(params.is_tx_allowed(code));
+        assert!(!params.is_tx_allowed(other_code));
+        assert!(!params.is_vp_allowed(code));
+        assert!(params.is_vp_allowed(other_code));
+    }
+
+    #[test]
+    fn test_parameters_storage() {
+        let mut storage = TestStorage::default();
+        assert_eq!(Parameters::read(&storage), Parameters::default());
+        assert!(!Parameters::default().is_storage_deposit_enabled());
+
+        let params = Parameters {
+            tx_gas_limit: 10,
+            block_gas_limit: 100,
+            tx_memory_max_pages: 300,
+            storage_deposit_per_byte: token::Amount::from(1),
+            ..Parameters::default()
+        };
+        params.init_storage(&mut storage);
+        assert_eq!(Parameters::read(&storage), params);
+        assert!(params.is_storage_deposit_enabled());
+        assert!(is_parameters_key(&parameters_key()));
+    }
+}
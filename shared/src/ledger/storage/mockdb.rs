@@ -1,4 +1,5 @@
-//! DB mock for testing
+//! An in-memory DB for testing and for the ephemeral ledger, whose state is
+//! not persisted.
 
 use std::collections::btree_map::Range;
 use std::collections::{BTreeMap, HashMap};
@@ -16,7 +17,7 @@ use crate::types::{
     RESERVED_VP_KEY,
 };
 
-/// An in-memory DB for testing.
+/// An in-memory DB.
 #[derive(Debug)]
 pub struct MockDB(BTreeMap<String, Vec<u8>>);
 
@@ -1,6 +1,5 @@
 //! Ledger's state storage with key-value backed store and a merkle tree
 
-#[cfg(any(test, feature = "testing"))]
 pub mod mockdb;
 pub mod types;
 pub mod write_log;
@@ -1,7 +1,8 @@
 //! Write log is temporary storage for modifications performed by a transaction.
 //! before they are committed to the ledger's storage.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, Mutex};
 
 use thiserror::Error;
 
@@ -37,24 +38,65 @@ pub enum StorageModification {
     },
 }
 
+impl StorageModification {
+    /// The size of the modified value in bytes, zero for a deletion
+    pub fn size(&self) -> usize {
+        match self {
+            StorageModification::Write { value } => value.len(),
+            StorageModification::Delete => 0,
+            StorageModification::InitAccount { vp } => vp.len(),
+        }
+    }
+}
+
+/// The storage keys read from a write log. The keys are read from the
+/// parallel validity predicates too, so the set is behind a mutex.
+#[derive(Debug, Default)]
+struct ReadKeys(Mutex<HashSet<Key>>);
+
+impl Clone for ReadKeys {
+    fn clone(&self) -> Self {
+        let keys = self.0.lock().expect("the read keys lock is poisoned");
+        Self(Mutex::new(keys.clone()))
+    }
+}
+
+/// A savepoint of the current transaction's write log, to which the
+/// transaction's modifications can be rolled back
+#[derive(Debug, Clone)]
+struct Savepoint {
+    tx_write_log: HashMap<Key, StorageModification>,
+    address_gen: Option<EstablishedAddressGen>,
+}
+
 /// The write log storage
 #[derive(Debug, Clone)]
 pub struct WriteLog {
     /// The generator of established addresses
     address_gen: Option<EstablishedAddressGen>,
     /// All the storage modification accepted by validity predicates are stored
-    /// in block write-log, before being committed to the storage
-    block_write_log: HashMap<Key, StorageModification>,
+    /// in block write-log, before being committed to the storage. It's shared
+    /// read-only with the forks of the write log and it's copied on the first
+    /// write after it has been forked.
+    block_write_log: Arc<HashMap<Key, StorageModification>>,
     /// The storage modifications for the current transaction
     tx_write_log: HashMap<Key, StorageModification>,
+    /// The keys read from a forked write log, which are tracked to detect
+    /// conflicts with the transactions that are executed before it
+    read_keys: Option<ReadKeys>,
+    /// The open savepoints of the current transaction, the last one is the
+    /// innermost
+    savepoints: Vec<Savepoint>,
 }
 
 impl Default for WriteLog {
     fn default() -> Self {
         Self {
             address_gen: None,
-            block_write_log: HashMap::with_capacity(100_000),
+            block_write_log: Arc::new(HashMap::with_capacity(100_000)),
             tx_write_log: HashMap::with_capacity(100),
+            read_keys: None,
+            savepoints: Vec::new(),
         }
     }
 }
@@ -63,6 +105,12 @@ impl WriteLog {
     /// Read a value at the given key and return the value and the gas cost,
     /// returns [`None`] if the key is not present in the write log
     pub fn read(&self, key: &Key) -> (Option<&StorageModification>, u64) {
+        if let Some(ReadKeys(read_keys)) = &self.read_keys {
+            read_keys
+                .lock()
+                .expect("the read keys lock is poisoned")
+                .insert(key.clone());
+        }
         // try to read from tx write log first
         match self.tx_write_log.get(&key).or_else(|| {
             // if not found, then try to read from block write log
@@ -84,36 +132,61 @@ impl WriteLog {
         }
     }
 
+    /// Read a value at the given key as it was before the current transaction
+    /// and return the value and the gas cost, returns [`None`] if the key is
+    /// not present in the block write log
+    pub fn read_before_tx(
+        &self,
+        key: &Key,
+    ) -> (Option<&StorageModification>, u64) {
+        if let Some(ReadKeys(read_keys)) = &self.read_keys {
+            read_keys
+                .lock()
+                .expect("the read keys lock is poisoned")
+                .insert(key.clone());
+        }
+        match self.block_write_log.get(&key) {
+            Some(v) => {
+                let gas = key.len() + v.size();
+                (Some(v), gas as _)
+            }
+            None => (None, key.len() as _),
+        }
+    }
+
     /// Write a key and a value and return the gas cost and the size difference
     pub fn write(&mut self, key: &Key, value: Vec<u8>) -> (u64, i64) {
         let len = value.len();
         let gas = key.len() + len;
-        let size_diff = match self
-            .tx_write_log
-            .insert(key.clone(), StorageModification::Write { value })
-        {
-            Some(prev) => match prev {
-                StorageModification::Write { ref value } => {
-                    len as i64 - value.len() as i64
-                }
-                StorageModification::Delete => len as i64,
-                StorageModification::InitAccount { .. } => {
-                    tracing::info!(
-                        "Trying to update the validity predicate that is just \
-                         initialized"
-                    );
-                    unreachable!()
-                }
-            },
-            // set just the length of the value because we don't know if
-            // the previous value exists on the storage
-            None => len as i64,
+        let modification = match self.tx_write_log.get(key) {
+            // an account initialized in the current transaction stays
+            // initialized with the updated validity predicate
+            Some(StorageModification::InitAccount { .. }) => {
+                StorageModification::InitAccount { vp: value }
+            }
+            _ => StorageModification::Write { value },
         };
+        let size_diff =
+            match self.tx_write_log.insert(key.clone(), modification) {
+                Some(prev) => match prev {
+                    StorageModification::Write { ref value } => {
+                        len as i64 - value.len() as i64
+                    }
+                    StorageModification::Delete => len as i64,
+                    StorageModification::InitAccount { ref vp } => {
+                        len as i64 - vp.len() as i64
+                    }
+                },
+                // set just the length of the value because we don't know if
+                // the previous value exists on the storage
+                None => len as i64,
+            };
         (gas as _, size_diff)
     }
 
     /// Delete a key and its value, and return the gas cost and the size
-    /// difference
+    /// difference. Deleting the validity predicate of an account initialized
+    /// in the current transaction drops the account's initialization.
     pub fn delete(&mut self, key: &Key) -> (u64, i64) {
         let size_diff = match self
             .tx_write_log
@@ -122,12 +195,10 @@ impl WriteLog {
             Some(prev) => match prev {
                 StorageModification::Write { ref value } => value.len() as i64,
                 StorageModification::Delete => 0,
-                StorageModification::InitAccount { .. } => {
-                    tracing::info!(
-                        "Trying to delete the validity predicate that is just \
-                         initialized"
-                    );
-                    unreachable!()
+                StorageModification::InitAccount { vp } => {
+                    // the validity predicate has never been in the storage
+                    self.tx_write_log.remove(key);
+                    vp.len() as i64
                 }
             },
             // set 0 because we don't know if the previous value exists on the
@@ -181,6 +252,69 @@ impl WriteLog {
             })
     }
 
+    /// Get the storage modifications of the current transaction.
+    pub fn get_tx_modifications(
+        &self,
+    ) -> impl Iterator<Item = (&Key, &StorageModification)> {
+        self.tx_write_log.iter()
+    }
+
+    /// Remove the modification of a key by the current transaction, so that
+    /// it won't be committed. This is used by the protocol for the keys that
+    /// a transaction writes only to communicate with the protocol.
+    pub fn remove_tx_modification(
+        &mut self,
+        key: &Key,
+    ) -> Option<StorageModification> {
+        self.tx_write_log.remove(key)
+    }
+
+    /// Open a new savepoint of the current transaction's modifications and
+    /// return the gas cost. The savepoints can be nested, a rollback or a
+    /// release applies to the last open savepoint.
+    pub fn open_savepoint(&mut self) -> u64 {
+        let gas: usize = self
+            .tx_write_log
+            .iter()
+            .map(|(key, modification)| {
+                key.len()
+                    + match modification {
+                        StorageModification::Write { value } => value.len(),
+                        StorageModification::Delete => 0,
+                        StorageModification::InitAccount { vp } => vp.len(),
+                    }
+            })
+            .sum();
+        self.savepoints.push(Savepoint {
+            tx_write_log: self.tx_write_log.clone(),
+            address_gen: self.address_gen.clone(),
+        });
+        gas as _
+    }
+
+    /// Roll back the current transaction's modifications to the last open
+    /// savepoint and close it. Returns `false` if there is no open savepoint.
+    pub fn rollback_savepoint(&mut self) -> bool {
+        match self.savepoints.pop() {
+            Some(Savepoint {
+                tx_write_log,
+                address_gen,
+            }) => {
+                self.tx_write_log = tx_write_log;
+                self.address_gen = address_gen;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Close the last open savepoint, keeping the current transaction's
+    /// modifications made since it has been opened. Returns `false` if there
+    /// is no open savepoint.
+    pub fn release_savepoint(&mut self) -> bool {
+        self.savepoints.pop().is_some()
+    }
+
     /// Commit the current transaction's write log to the block when it's
     /// accepted by all the triggered validity predicates. Starts a new
     /// transaction write log.
@@ -189,13 +323,58 @@ impl WriteLog {
             &mut self.tx_write_log,
             HashMap::with_capacity(100),
         );
-        self.block_write_log.extend(tx_write_log);
+        Arc::make_mut(&mut self.block_write_log).extend(tx_write_log);
+        self.savepoints.clear();
     }
 
     /// Drop the current transaction's write log when it's declined by any of
     /// the triggered validity predicates. Starts a new transaction write log.
     pub fn drop_tx(&mut self) {
         self.tx_write_log.clear();
+        self.savepoints.clear();
+    }
+
+    /// Fork the write log for a speculative execution of a transaction. The
+    /// fork shares the block's modifications without copying them, it has an
+    /// empty transaction write log and it tracks all the keys read from it.
+    pub fn fork(&self) -> Self {
+        Self {
+            address_gen: self.address_gen.clone(),
+            block_write_log: Arc::clone(&self.block_write_log),
+            tx_write_log: HashMap::with_capacity(100),
+            read_keys: Some(ReadKeys::default()),
+            savepoints: Vec::new(),
+        }
+    }
+
+    /// Get the keys read from a forked write log. Returns an empty set if the
+    /// write log is not a fork.
+    pub fn get_read_keys(&self) -> HashSet<Key> {
+        match &self.read_keys {
+            Some(ReadKeys(read_keys)) => read_keys
+                .lock()
+                .expect("the read keys lock is poisoned")
+                .clone(),
+            None => HashSet::default(),
+        }
+    }
+
+    /// Check if new addresses have been generated in this write log since the
+    /// given write log was forked from it, or since it has been forked from
+    /// the given write log.
+    pub fn has_new_addresses(&self, base: &Self) -> bool {
+        self.address_gen != base.address_gen
+    }
+
+    /// Merge the current transaction's modifications from a fork of the given
+    /// base write log into the current transaction's write log. If the fork
+    /// has generated new addresses, no new addresses should have been
+    /// generated in this write log since the base was forked from it.
+    pub fn merge_fork(&mut self, fork: Self, base: &Self) {
+        if fork.has_new_addresses(base) {
+            self.address_gen = fork.address_gen;
+        }
+        self.tx_write_log.extend(fork.tx_write_log);
     }
 
     /// Commit the current block's write log to the storage. Starts a new block
@@ -228,7 +407,10 @@ impl WriteLog {
         if let Some(address_gen) = self.address_gen.take() {
             storage.address_gen = address_gen
         }
-        self.block_write_log.clear();
+        match Arc::get_mut(&mut self.block_write_log) {
+            Some(block_write_log) => block_write_log.clear(),
+            None => self.block_write_log = Arc::default(),
+        }
         Ok(())
     }
 }
@@ -236,6 +418,7 @@ impl WriteLog {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::types::KeySeg;
 
     #[test]
     fn test_crud_value() {
@@ -327,34 +510,58 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
     fn test_update_initialized_account() {
         let mut write_log = WriteLog::default();
         let address_gen = EstablishedAddressGen::new("test");
 
         let init_vp = "initialized".as_bytes().to_vec();
-        let (addr, _) = write_log.init_account(&address_gen, init_vp);
+        let (addr, _) = write_log.init_account(&address_gen, init_vp.clone());
         let vp_key =
             Key::validity_predicate(&addr).expect("cannot create the vp key");
 
-        // update should fail
+        // update the VP, the account stays initialized
         let updated_vp = "updated".as_bytes().to_vec();
-        write_log.write(&vp_key, updated_vp);
+        let (gas, diff) = write_log.write(&vp_key, updated_vp.clone());
+        assert_eq!(gas, (vp_key.len() + updated_vp.len()) as u64);
+        assert_eq!(diff, updated_vp.len() as i64 - init_vp.len() as i64);
+        match write_log.read(&vp_key).0.expect("no read value") {
+            StorageModification::InitAccount { vp } => {
+                assert_eq!(*vp, updated_vp)
+            }
+            _ => panic!("unexpected result"),
+        }
+
+        // write into the account's sub-space
+        let key = Key::from(addr.to_db_key())
+            .push(&"balance".to_owned())
+            .expect("cannot create the key");
+        let value = "value".as_bytes().to_vec();
+        let (_gas, diff) = write_log.write(&key, value.clone());
+        assert_eq!(diff, value.len() as i64);
+
+        let (changed_keys, accounts) = write_log.get_partitioned_keys();
+        assert_eq!(changed_keys, vec![&key]);
+        assert_eq!(accounts, vec![&vp_key]);
     }
 
     #[test]
-    #[should_panic]
     fn test_delete_initialized_account() {
         let mut write_log = WriteLog::default();
         let address_gen = EstablishedAddressGen::new("test");
 
         let init_vp = "initialized".as_bytes().to_vec();
-        let (addr, _) = write_log.init_account(&address_gen, init_vp);
+        let (addr, _) = write_log.init_account(&address_gen, init_vp.clone());
         let vp_key =
             Key::validity_predicate(&addr).expect("cannot create the vp key");
 
-        // delete should fail
-        write_log.delete(&vp_key);
+        // the deletion drops the account's initialization
+        let (gas, diff) = write_log.delete(&vp_key);
+        assert_eq!(gas, (vp_key.len() + init_vp.len()) as u64);
+        assert_eq!(diff, -(init_vp.len() as i64));
+        assert!(write_log.read(&vp_key).0.is_none());
+        let (changed_keys, accounts) = write_log.get_partitioned_keys();
+        assert!(changed_keys.is_empty());
+        assert!(accounts.is_empty());
     }
 
     #[test]
@@ -409,4 +616,116 @@ mod tests {
         let (value, _) = storage.read(&key3).expect("read failed");
         assert_eq!(value.expect("no read value"), val3);
     }
+
+    #[test]
+    fn test_fork() {
+        let mut write_log = WriteLog::default();
+        let address_gen = EstablishedAddressGen::new("test");
+
+        let key1 =
+            Key::parse("key1".to_owned()).expect("cannot parse the key string");
+        let key2 =
+            Key::parse("key2".to_owned()).expect("cannot parse the key string");
+        let val1 = "val1".as_bytes().to_vec();
+        write_log.write(&key1, val1.clone());
+        write_log.commit_tx();
+
+        // the fork reads the block's modifications and tracks the read keys
+        let base = write_log.fork();
+        let mut fork = base.fork();
+        match fork.read(&key1).0.expect("no read value") {
+            StorageModification::Write { value } => assert_eq!(*value, val1),
+            _ => panic!("unexpected read result"),
+        }
+        assert!(fork.read(&key2).0.is_none());
+        let read_keys = fork.get_read_keys();
+        assert!(read_keys.contains(&key1));
+        assert!(read_keys.contains(&key2));
+        assert_eq!(read_keys.len(), 2);
+        assert!(write_log.get_read_keys().is_empty());
+
+        // the modifications of the fork are not visible in the write log
+        // until it's merged
+        let val2 = "val2".as_bytes().to_vec();
+        fork.write(&key2, val2.clone());
+        let (addr, _) = fork.init_account(&address_gen, vec![]);
+        assert!(fork.has_new_addresses(&base));
+        assert!(!write_log.has_new_addresses(&base));
+        assert!(write_log.read(&key2).0.is_none());
+        write_log.merge_fork(fork, &base);
+        assert!(write_log.has_new_addresses(&base));
+        match write_log.read(&key2).0.expect("no read value") {
+            StorageModification::Write { value } => assert_eq!(*value, val2),
+            _ => panic!("unexpected read result"),
+        }
+        let (_changed_keys, accounts) = write_log.get_partitioned_keys();
+        let vp_key =
+            Key::validity_predicate(&addr).expect("cannot create the vp key");
+        assert_eq!(accounts, vec![&vp_key]);
+        assert!(write_log.get_read_keys().is_empty());
+
+        // the block's modifications committed after forking are not visible
+        // in the fork
+        write_log.commit_tx();
+        assert!(write_log.read(&key2).0.is_some());
+        assert!(base.read(&key2).0.is_none());
+    }
+
+    #[test]
+    fn test_savepoints() {
+        let mut write_log = WriteLog::default();
+        let address_gen = EstablishedAddressGen::new("test");
+
+        let key1 =
+            Key::parse("key1".to_owned()).expect("cannot parse the key string");
+        let key2 =
+            Key::parse("key2".to_owned()).expect("cannot parse the key string");
+        let val1 = "val1".as_bytes().to_vec();
+        let val2 = "val2".as_bytes().to_vec();
+
+        // there's nothing to rollback or release without a savepoint
+        assert!(!write_log.rollback_savepoint());
+        assert!(!write_log.release_savepoint());
+
+        write_log.write(&key1, val1.clone());
+        let gas = write_log.open_savepoint();
+        assert_eq!(gas, (key1.len() + val1.len()) as u64);
+
+        // the modifications in a savepoint are rolled back
+        write_log.write(&key1, val2.clone());
+        write_log.write(&key2, val2.clone());
+        write_log.init_account(&address_gen, vec![]);
+        assert!(write_log.rollback_savepoint());
+        match write_log.read(&key1).0.expect("no read value") {
+            StorageModification::Write { value } => assert_eq!(*value, val1),
+            _ => panic!("unexpected read result"),
+        }
+        assert!(write_log.read(&key2).0.is_none());
+        assert!(write_log.get_partitioned_keys().1.is_empty());
+        assert!(write_log.address_gen.is_none());
+
+        // the modifications in a released nested savepoint are rolled back
+        // with the outer savepoint
+        write_log.open_savepoint();
+        write_log.write(&key2, val1.clone());
+        write_log.open_savepoint();
+        write_log.delete(&key1);
+        assert!(write_log.release_savepoint());
+        assert!(matches!(
+            write_log.read(&key1).0,
+            Some(StorageModification::Delete)
+        ));
+        assert!(write_log.rollback_savepoint());
+        assert!(!write_log.rollback_savepoint());
+        match write_log.read(&key1).0.expect("no read value") {
+            StorageModification::Write { value } => assert_eq!(*value, val1),
+            _ => panic!("unexpected read result"),
+        }
+        assert!(write_log.read(&key2).0.is_none());
+
+        // the savepoints are closed with the transaction
+        write_log.open_savepoint();
+        write_log.commit_tx();
+        assert!(!write_log.rollback_savepoint());
+    }
 }
@@ -0,0 +1,624 @@
+//! The storage usage of accounts. The protocol tracks the number of bytes
+//! stored by each account, charges gas for the growth of the storage and, when
+//! enabled by the protocol parameters, holds a refundable token deposit per
+//! byte stored.
+//!
+//! The deposits are paid by the account that a transaction declares as its
+//! payer with [`deposit_payer_key`], rather than by the accounts whose storage
+//! grows, so that e.g. a new account or the receiver of a new token don't need
+//! a balance to be written to. The key is written under the payer's address,
+//! so the payer's validity predicate has to accept the transaction before any
+//! deposit can be taken from it. The key is never committed.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use thiserror::Error;
+
+use crate::ledger::parameters::Parameters;
+use crate::ledger::storage::write_log::{StorageModification, WriteLog};
+use crate::ledger::storage::{self, Storage, StorageHasher};
+use crate::types::address::InternalAddress;
+use crate::types::{token, Address, DbKeySeg, Key, KeySeg};
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum Error {
+    #[error("Storage error: {0}")]
+    StorageError(storage::Error),
+    #[error("Error decoding a storage value: {0}")]
+    DecodingError(std::io::Error),
+    #[error("The payer {payer} cannot pay the storage deposit: {error}")]
+    InsufficientDeposit { payer: Address, error: token::Error },
+    #[error("The storage deposit paid by {0} overflowed")]
+    DepositOverflow(Address),
+    #[error(
+        "The transaction changes the deposited storage by {0} bytes, but it \
+         doesn't declare a storage deposit payer"
+    )]
+    MissingDepositPayer(i64),
+    #[error("The transaction declares multiple storage deposit payers: {0:?}")]
+    MultipleDepositPayers(BTreeSet<Address>),
+}
+
+/// Result for functions that may fail
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The internal address of the storage usage. The escrowed storage deposits
+/// are held in its balances. Its native validity predicate rejects any
+/// changes, so only the protocol can modify the storage usage.
+pub fn storage_usage_address() -> Address {
+    Address::Internal(InternalAddress::StorageUsage)
+}
+
+const USAGE_STORAGE_KEY: &str = "usage";
+const DEPOSIT_PAYER_STORAGE_KEY: &str = "storage_deposit_payer";
+
+/// Obtain the storage key of the storage usage of an account, in bytes.
+pub fn usage_key(owner: &Address) -> Key {
+    Key::from(storage_usage_address().to_db_key())
+        .push(&USAGE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&owner.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a storage usage key. If it is, returns
+/// the owner.
+pub fn is_usage_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(key), DbKeySeg::AddressSeg(owner)]
+            if addr == &storage_usage_address() && key == USAGE_STORAGE_KEY =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
+/// Obtain the storage key that a transaction writes to declare the given
+/// account as the payer of its storage deposits.
+pub fn deposit_payer_key(payer: &Address) -> Key {
+    Key::from(payer.to_db_key())
+        .push(&DEPOSIT_PAYER_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a storage deposit payer key. If it is,
+/// returns the payer.
+pub fn is_deposit_payer_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(payer), DbKeySeg::StringSeg(key)]
+            if key == DEPOSIT_PAYER_STORAGE_KEY =>
+        {
+            Some(payer)
+        }
+        _ => None,
+    }
+}
+
+/// Get the account to which the storage of the given key is attributed. This
+/// is the first address of the key, except for the token balances which are
+/// attributed to the owner of the balance. Returns [`None`] for keys that
+/// don't start with an address.
+pub fn key_owner(key: &Key) -> Option<&Address> {
+    token::is_any_token_balance_key(key).or_else(|| {
+        match key.segments.first() {
+            Some(DbKeySeg::AddressSeg(addr)) => Some(addr),
+            _ => None,
+        }
+    })
+}
+
+/// Update the storage usage of the accounts whose storage is modified by the
+/// current transaction in the write log and, if the storage deposits are
+/// enabled, move the deposit for the total difference between the
+/// transaction's payer and the escrow. The updates are written into the
+/// current transaction's write log. Returns the gas cost, including the gas
+/// for the storage growth.
+///
+/// This must only be called once the validity predicates have accepted the
+/// transaction, because the payer is charged without any further checks.
+pub fn apply_tx<DB, H>(
+    parameters: &Parameters,
+    storage: &Storage<DB, H>,
+    write_log: &mut WriteLog,
+) -> Result<u64>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let payer = take_deposit_payer(write_log)?;
+    let (size_diffs, mut gas) = tx_size_diffs(storage, write_log)?;
+    // the difference of the storage that requires a deposit
+    let mut deposit_diff: i64 = 0;
+    for (owner, size_diff) in size_diffs {
+        if size_diff == 0 {
+            continue;
+        }
+        let usage_key = usage_key(&owner);
+        let (usage, read_gas) =
+            read_value::<u64, _, _>(storage, write_log, &usage_key)?;
+        gas = gas.saturating_add(read_gas);
+        let usage = usage.unwrap_or_default();
+        // the storage written before its usage has been tracked (e.g. at
+        // genesis) is not accounted for, so the usage cannot go below zero
+        let new_usage = if size_diff > 0 {
+            gas = gas.saturating_add(
+                (size_diff as u64)
+                    .saturating_mul(parameters.storage_gas_per_byte),
+            );
+            usage.saturating_add(size_diff as u64)
+        } else {
+            usage.saturating_sub((-size_diff) as u64)
+        };
+
+        if !matches!(owner, Address::Internal(_)) {
+            deposit_diff = deposit_diff
+                .saturating_add(new_usage as i64)
+                .saturating_sub(usage as i64);
+        }
+
+        let (write_gas, _) = write_log.write(
+            &usage_key,
+            new_usage
+                .try_to_vec()
+                .expect("Encoding the storage usage shouldn't fail"),
+        );
+        gas = gas.saturating_add(write_gas);
+    }
+
+    if parameters.is_storage_deposit_enabled() && deposit_diff != 0 {
+        let payer = payer.ok_or(Error::MissingDepositPayer(deposit_diff))?;
+        let deposit_gas =
+            move_deposit(parameters, storage, write_log, &payer, deposit_diff)?;
+        gas = gas.saturating_add(deposit_gas);
+    }
+    Ok(gas)
+}
+
+/// Find the storage deposit payer declared by the current transaction and
+/// remove its key from the write log. A transaction can declare at most one
+/// payer.
+fn take_deposit_payer(write_log: &mut WriteLog) -> Result<Option<Address>> {
+    let payers: BTreeSet<Address> = write_log
+        .get_tx_modifications()
+        .filter_map(|(key, _)| is_deposit_payer_key(key).cloned())
+        .collect();
+    for payer in &payers {
+        write_log.remove_tx_modification(&deposit_payer_key(payer));
+    }
+    if payers.len() > 1 {
+        return Err(Error::MultipleDepositPayers(payers));
+    }
+    Ok(payers.into_iter().next())
+}
+
+/// Get the differences of the storage size of every account modified by the
+/// current transaction in the write log. Returns the differences and the gas
+/// cost of reading the previous values.
+pub fn tx_size_diffs<DB, H>(
+    storage: &Storage<DB, H>,
+    write_log: &WriteLog,
+) -> Result<(BTreeMap<Address, i64>, u64)>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let mut size_diffs = BTreeMap::<Address, i64>::new();
+    let mut gas: u64 = 0;
+    for (key, modification) in write_log.get_tx_modifications() {
+        let owner = match key_owner(key) {
+            Some(owner) => owner,
+            None => continue,
+        };
+        let prev_size = match write_log.read_before_tx(key) {
+            (Some(prev), read_gas) => {
+                gas = gas.saturating_add(read_gas);
+                entry_size(key, prev)
+            }
+            (None, read_gas) => {
+                let (prev, storage_gas) =
+                    storage.read(key).map_err(Error::StorageError)?;
+                gas = gas.saturating_add(read_gas).saturating_add(storage_gas);
+                prev.map(|value| (key.len() + value.len()) as u64)
+                    .unwrap_or_default()
+            }
+        };
+        let size = entry_size(key, modification);
+        *size_diffs.entry(owner.clone()).or_default() +=
+            size as i64 - prev_size as i64;
+    }
+    Ok((size_diffs, gas))
+}
+
+/// The number of bytes stored by an entry, i.e. its key and value lengths.
+fn entry_size(key: &Key, modification: &StorageModification) -> u64 {
+    match modification {
+        StorageModification::Delete => 0,
+        _ => (key.len() + modification.size()) as u64,
+    }
+}
+
+/// Move the storage deposit for the given difference of the storage usage, in
+/// bytes, between the payer's balance and the escrow. Returns the gas cost.
+fn move_deposit<DB, H>(
+    parameters: &Parameters,
+    storage: &Storage<DB, H>,
+    write_log: &mut WriteLog,
+    payer: &Address,
+    deposit_diff: i64,
+) -> Result<u64>
+where
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let token = &parameters.storage_deposit_token;
+    let payer_key = token::balance_key(token, payer);
+    let escrow_key = token::balance_key(token, &storage_usage_address());
+    let (payer_balance, payer_gas) =
+        read_value::<token::Amount, _, _>(storage, write_log, &payer_key)?;
+    let (escrow_balance, escrow_gas) =
+        read_value::<token::Amount, _, _>(storage, write_log, &escrow_key)?;
+    let mut payer_balance = payer_balance.unwrap_or_default();
+    let mut escrow_balance = escrow_balance.unwrap_or_default();
+
+    let per_byte = u64::from(parameters.storage_deposit_per_byte);
+    let amount = per_byte
+        .checked_mul(deposit_diff.unsigned_abs())
+        .map(token::Amount::from)
+        .ok_or_else(|| Error::DepositOverflow(payer.clone()))?;
+    if deposit_diff > 0 {
+        payer_balance.spend(&amount).map_err(|error| {
+            Error::InsufficientDeposit {
+                payer: payer.clone(),
+                error,
+            }
+        })?;
+        escrow_balance
+            .receive(&amount)
+            .map_err(|_| Error::DepositOverflow(payer.clone()))?;
+    } else {
+        // the deposit per byte may have been lowered since the deposit has
+        // been made, so the refund is bounded by the escrow
+        let amount = std::cmp::min(amount, escrow_balance);
+        escrow_balance
+            .spend(&amount)
+            .expect("The refund is bounded by the escrow");
+        payer_balance
+            .receive(&amount)
+            .map_err(|_| Error::DepositOverflow(payer.clone()))?;
+    }
+
+    let (payer_write_gas, _) = write_log.write(
+        &payer_key,
+        payer_balance
+            .try_to_vec()
+            .expect("Encoding an amount shouldn't fail"),
+    );
+    let (escrow_write_gas, _) = write_log.write(
+        &escrow_key,
+        escrow_balance
+            .try_to_vec()
+            .expect("Encoding an amount shouldn't fail"),
+    );
+    Ok(payer_gas
+        .saturating_add(escrow_gas)
+        .saturating_add(payer_write_gas)
+        .saturating_add(escrow_write_gas))
+}
+
+/// Read and decode a value with the modifications of the current transaction
+/// applied. Returns the value and the gas cost.
+fn read_value<T, DB, H>(
+    storage: &Storage<DB, H>,
+    write_log: &WriteLog,
+    key: &Key,
+) -> Result<(Option<T>, u64)>
+where
+    T: BorshDeserialize,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    let (bytes, gas) = match write_log.read(key) {
+        (Some(StorageModification::Write { value }), gas) => {
+            (Some(value.clone()), gas)
+        }
+        (Some(_), gas) => (None, gas),
+        (None, gas) => {
+            let (value, storage_gas) =
+                storage.read(key).map_err(Error::StorageError)?;
+            (value, gas.saturating_add(storage_gas))
+        }
+    };
+    let value = bytes
+        .map(|bytes| T::try_from_slice(&bytes[..]))
+        .transpose()
+        .map_err(Error::DecodingError)?;
+    Ok((value, gas))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestStorage;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+    use crate::types::key;
+
+    fn read_usage(
+        storage: &TestStorage,
+        write_log: &WriteLog,
+        owner: &Address,
+    ) -> u64 {
+        read_value::<u64, _, _>(storage, write_log, &usage_key(owner))
+            .expect("reading the usage shouldn't fail")
+            .0
+            .unwrap_or_default()
+    }
+
+    fn read_balance(
+        storage: &TestStorage,
+        write_log: &WriteLog,
+        key: &Key,
+    ) -> token::Amount {
+        read_value::<token::Amount, _, _>(storage, write_log, key)
+            .expect("reading the balance shouldn't fail")
+            .0
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_usage_keys() {
+        let owner = established_address_1();
+        let key = usage_key(&owner);
+        assert_eq!(is_usage_key(&key), Some(&owner));
+        assert_eq!(key_owner(&key), Some(&storage_usage_address()));
+
+        let token = established_address_2();
+        let balance_key = token::balance_key(&token, &owner);
+        assert_eq!(key_owner(&balance_key), Some(&owner));
+        assert_eq!(is_usage_key(&balance_key), None);
+        let key = Key::from(token.to_db_key())
+            .push(&"counter".to_owned())
+            .expect("Cannot obtain a storage key");
+        assert_eq!(key_owner(&key), Some(&token));
+    }
+
+    #[test]
+    fn test_tx_size_diffs() {
+        let mut storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let owner = established_address_1();
+        let stored_key = Key::from(owner.to_db_key())
+            .push(&"stored".to_owned())
+            .expect("Cannot obtain a storage key");
+        let new_key = Key::from(owner.to_db_key())
+            .push(&"new".to_owned())
+            .expect("Cannot obtain a storage key");
+        storage
+            .write(&stored_key, vec![0; 10])
+            .expect("write failed");
+
+        // shrink a stored value and add a new one
+        write_log.write(&stored_key, vec![0; 4]);
+        write_log.write(&new_key, vec![0; 3]);
+        let (diffs, _gas) =
+            tx_size_diffs(&storage, &write_log).expect("diffs failed");
+        assert_eq!(diffs.get(&owner), Some(&(-6 + new_key.len() as i64 + 3)));
+        write_log.commit_tx();
+
+        // the previous size is read from the block write log
+        write_log.delete(&new_key);
+        let (diffs, _gas) =
+            tx_size_diffs(&storage, &write_log).expect("diffs failed");
+        assert_eq!(diffs.get(&owner), Some(&-(new_key.len() as i64 + 3)));
+    }
+
+    #[test]
+    fn test_apply_tx() {
+        let mut storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let owner = established_address_1();
+        let key = Key::from(owner.to_db_key())
+            .push(&"data".to_owned())
+            .expect("Cannot obtain a storage key");
+        let parameters = Parameters::default();
+
+        write_log.write(&key, vec![0; 100]);
+        let growth = key.len() as u64 + 100;
+        let gas = apply_tx(&parameters, &storage, &mut write_log)
+            .expect("apply failed");
+        assert!(gas >= growth * parameters.storage_gas_per_byte);
+        assert_eq!(read_usage(&storage, &write_log, &owner), growth);
+        write_log.commit_tx();
+        write_log
+            .commit_block(&mut storage)
+            .expect("commit block failed");
+
+        // the deletion frees the storage
+        write_log.delete(&key);
+        apply_tx(&parameters, &storage, &mut write_log).expect("apply failed");
+        assert_eq!(read_usage(&storage, &write_log, &owner), 0);
+    }
+
+    fn deposit_parameters() -> Parameters {
+        Parameters {
+            storage_deposit_per_byte: token::Amount::from(2),
+            ..Parameters::default()
+        }
+    }
+
+    fn declare_payer(write_log: &mut WriteLog, payer: &Address) {
+        write_log.write(&deposit_payer_key(payer), true.try_to_vec().unwrap());
+    }
+
+    #[test]
+    fn test_deposit_payer_keys() {
+        let payer = established_address_1();
+        let key = deposit_payer_key(&payer);
+        assert_eq!(is_deposit_payer_key(&key), Some(&payer));
+        assert_eq!(key_owner(&key), Some(&payer));
+        assert_eq!(is_deposit_payer_key(&usage_key(&payer)), None);
+    }
+
+    #[test]
+    fn test_storage_deposits() {
+        let mut storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let owner = established_address_1();
+        let payer = established_address_2();
+        let key = Key::from(owner.to_db_key())
+            .push(&"data".to_owned())
+            .expect("Cannot obtain a storage key");
+        let parameters = deposit_parameters();
+        let token = &parameters.storage_deposit_token;
+        let payer_key = token::balance_key(token, &payer);
+        let escrow_key = token::balance_key(token, &storage_usage_address());
+
+        // the storage growth requires a payer
+        write_log.write(&key, vec![0; 10]);
+        assert!(matches!(
+            apply_tx(&parameters, &storage, &mut write_log),
+            Err(Error::MissingDepositPayer(_))
+        ));
+        write_log.drop_tx();
+
+        // the payer has no balance to pay the deposit
+        write_log.write(&key, vec![0; 10]);
+        declare_payer(&mut write_log, &payer);
+        assert!(matches!(
+            apply_tx(&parameters, &storage, &mut write_log),
+            Err(Error::InsufficientDeposit { .. })
+        ));
+        write_log.drop_tx();
+
+        let initial = token::Amount::from(1_000);
+        storage
+            .write(&payer_key, initial.try_to_vec().unwrap())
+            .expect("write failed");
+        write_log.write(&key, vec![0; 10]);
+        declare_payer(&mut write_log, &payer);
+        apply_tx(&parameters, &storage, &mut write_log).expect("apply failed");
+        let deposit = token::Amount::from(2 * (key.len() as u64 + 10));
+        assert_eq!(read_balance(&storage, &write_log, &escrow_key), deposit);
+        assert_eq!(
+            read_balance(&storage, &write_log, &payer_key),
+            initial.checked_sub(&deposit).unwrap()
+        );
+        // the owner's balance is untouched and the payer's key is not
+        // committed
+        assert_eq!(
+            read_balance(
+                &storage,
+                &write_log,
+                &token::balance_key(token, &owner)
+            ),
+            token::Amount::default()
+        );
+        assert!(write_log.read(&deposit_payer_key(&payer)).0.is_none());
+        write_log.commit_tx();
+
+        // the deposit is refunded to the payer when the storage is freed
+        write_log.delete(&key);
+        declare_payer(&mut write_log, &payer);
+        apply_tx(&parameters, &storage, &mut write_log).expect("apply failed");
+        assert_eq!(
+            read_balance(&storage, &write_log, &escrow_key),
+            token::Amount::default()
+        );
+        assert_eq!(read_balance(&storage, &write_log, &payer_key), initial);
+    }
+
+    #[test]
+    fn test_multiple_deposit_payers() {
+        let storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        declare_payer(&mut write_log, &established_address_1());
+        declare_payer(&mut write_log, &established_address_2());
+        assert!(matches!(
+            apply_tx(&deposit_parameters(), &storage, &mut write_log),
+            Err(Error::MultipleDepositPayers(_))
+        ));
+    }
+
+    #[test]
+    fn test_init_account_deposit() {
+        let mut storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let payer = established_address_1();
+        let parameters = deposit_parameters();
+        let token = &parameters.storage_deposit_token;
+        let payer_key = token::balance_key(token, &payer);
+        let initial = token::Amount::from(1_000);
+        storage
+            .write(&payer_key, initial.try_to_vec().unwrap())
+            .expect("write failed");
+
+        // the new account has no balance, its storage is paid by the payer
+        let vp = vec![0; 10];
+        let (address, _gas) = write_log.init_account(&storage.address_gen, vp);
+        let pk_key = key::ed25519::pk_key(&address);
+        write_log.write(&pk_key, vec![0; 32]);
+        declare_payer(&mut write_log, &payer);
+        apply_tx(&parameters, &storage, &mut write_log).expect("apply failed");
+
+        let vp_key = Key::validity_predicate(&address)
+            .expect("Cannot obtain a storage key");
+        let usage = vp_key.len() as u64 + 10 + pk_key.len() as u64 + 32;
+        assert_eq!(read_usage(&storage, &write_log, &address), usage);
+        assert_eq!(
+            read_balance(&storage, &write_log, &payer_key),
+            initial
+                .checked_sub(&token::Amount::from(2 * usage))
+                .unwrap()
+        );
+        assert_eq!(
+            read_balance(
+                &storage,
+                &write_log,
+                &token::balance_key(token, &address)
+            ),
+            token::Amount::default()
+        );
+    }
+
+    #[test]
+    fn test_receive_new_token_deposit() {
+        let mut storage = TestStorage::default();
+        let mut write_log = WriteLog::default();
+        let sender = established_address_1();
+        let receiver = established_address_2();
+        let new_token = established_address_3();
+        let parameters = deposit_parameters();
+        let token = &parameters.storage_deposit_token;
+        let sender_key = token::balance_key(token, &sender);
+        let receiver_key = token::balance_key(token, &receiver);
+        let initial = token::Amount::from(1_000);
+        storage
+            .write(&sender_key, initial.try_to_vec().unwrap())
+            .expect("write failed");
+
+        // the receiver doesn't hold any of the deposit token, the new balance
+        // is paid by the sender
+        let balance_key = token::balance_key(&new_token, &receiver);
+        let amount = token::Amount::from(1);
+        write_log.write(&balance_key, amount.try_to_vec().unwrap());
+        declare_payer(&mut write_log, &sender);
+        apply_tx(&parameters, &storage, &mut write_log).expect("apply failed");
+
+        let usage = balance_key.len() as u64 + 8;
+        assert_eq!(read_usage(&storage, &write_log, &receiver), usage);
+        assert_eq!(
+            read_balance(&storage, &write_log, &receiver_key),
+            token::Amount::default()
+        );
+        assert_eq!(
+            read_balance(&storage, &write_log, &sender_key),
+            initial
+                .checked_sub(&token::Amount::from(2 * usage))
+                .unwrap()
+        );
+    }
+}
@@ -0,0 +1,173 @@
+//! Coordinated protocol upgrades. An upgrade is planned at a block height by
+//! a governance proposal. The nodes whose binary doesn't support the planned
+//! upgrade halt before the upgrade height, while the binaries that support it
+//! run the plan's storage migration, if any, at the beginning of the block at
+//! the upgrade height.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ledger::storage::{self, Storage, StorageHasher};
+use crate::types::address::InternalAddress;
+use crate::types::{Address, BlockHeight, DbKeySeg, Key, KeySeg};
+
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error("The upgrade name must not be empty")]
+    EmptyName,
+    #[error(
+        "The upgrade height {height} must be after the next block height \
+         {next}"
+    )]
+    HeightTooLow { height: u64, next: u64 },
+}
+
+/// A planned upgrade
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct UpgradePlan {
+    /// The name of the upgrade, which the binaries supporting it recognize
+    pub name: String,
+    /// The height of the first block processed by the upgraded binary
+    pub height: BlockHeight,
+    /// The name of the storage migration to run at the upgrade height
+    pub migration: Option<String>,
+}
+
+impl UpgradePlan {
+    /// Check that the plan can be scheduled at the given current height. The
+    /// nodes must have the chance to halt at the end of a block before the
+    /// upgrade height.
+    pub fn validate(&self, current_height: BlockHeight) -> Result<(), Error> {
+        if self.name.trim().is_empty() {
+            return Err(Error::EmptyName);
+        }
+        let next = current_height.0 + 1;
+        if self.height.0 <= next {
+            return Err(Error::HeightTooLow {
+                height: self.height.0,
+                next,
+            });
+        }
+        Ok(())
+    }
+
+    /// Write the plan to the storage, replacing the previous plan, if any.
+    pub fn write<DB, H>(&self, storage: &mut Storage<DB, H>)
+    where
+        DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+        H: StorageHasher,
+    {
+        let bytes = self
+            .try_to_vec()
+            .expect("Encoding the upgrade plan shouldn't fail");
+        storage
+            .write(&plan_key(), bytes)
+            .expect("Unable to write the upgrade plan");
+    }
+
+    /// Read the planned upgrade from the storage, if any.
+    pub fn read<DB, H>(storage: &Storage<DB, H>) -> Option<Self>
+    where
+        DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+        H: StorageHasher,
+    {
+        let (value, _gas) = storage
+            .read(&plan_key())
+            .expect("Unable to read the upgrade plan");
+        value.map(|bytes| {
+            Self::try_from_slice(&bytes[..])
+                .expect("Decoding the upgrade plan shouldn't fail")
+        })
+    }
+
+    /// Remove the plan from the storage once the upgrade has been applied.
+    pub fn delete<DB, H>(storage: &mut Storage<DB, H>)
+    where
+        DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+        H: StorageHasher,
+    {
+        storage
+            .delete(&plan_key())
+            .expect("Unable to delete the upgrade plan");
+    }
+}
+
+/// The internal address of the upgrade plan. Its native validity predicate
+/// rejects any changes, so transactions cannot modify the plan.
+pub fn upgrade_address() -> Address {
+    Address::Internal(InternalAddress::Upgrade)
+}
+
+const PLAN_STORAGE_KEY: &str = "plan";
+
+/// Obtain the storage key of the [`UpgradePlan`].
+pub fn plan_key() -> Key {
+    Key::from(upgrade_address().to_db_key())
+        .push(&PLAN_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is the upgrade plan key.
+pub fn is_plan_key(key: &Key) -> bool {
+    matches!(&key.segments[..], [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(key)]
+        if addr == &upgrade_address() && key == PLAN_STORAGE_KEY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ledger::storage::testing::TestStorage;
+
+    fn plan(height: u64) -> UpgradePlan {
+        UpgradePlan {
+            name: "v2".to_owned(),
+            height: BlockHeight(height),
+            migration: None,
+        }
+    }
+
+    #[test]
+    fn test_upgrade_plan_validation() {
+        assert_eq!(plan(12).validate(BlockHeight(10)), Ok(()));
+        assert_eq!(
+            plan(11).validate(BlockHeight(10)),
+            Err(Error::HeightTooLow {
+                height: 11,
+                next: 11
+            })
+        );
+        let empty = UpgradePlan {
+            name: " ".to_owned(),
+            ..plan(12)
+        };
+        assert_eq!(empty.validate(BlockHeight(10)), Err(Error::EmptyName));
+    }
+
+    #[test]
+    fn test_upgrade_plan_storage() {
+        let mut storage = TestStorage::default();
+        assert_eq!(UpgradePlan::read(&storage), None);
+
+        let plan = UpgradePlan {
+            migration: Some("v2-balances".to_owned()),
+            ..plan(100)
+        };
+        plan.write(&mut storage);
+        assert_eq!(UpgradePlan::read(&storage), Some(plan));
+        assert!(is_plan_key(&plan_key()));
+
+        UpgradePlan::delete(&mut storage);
+        assert_eq!(UpgradePlan::read(&storage), None);
+    }
+}
@@ -64,6 +64,9 @@ pub enum Address {
     Established(EstablishedAddress),
     /// An implicit address is derived from a cryptographic key
     Implicit(ImplicitAddress),
+    /// An internal address of a protocol module with a native validity
+    /// predicate
+    Internal(InternalAddress),
 }
 
 impl Address {
@@ -109,6 +112,7 @@ impl Address {
                     return Err(Error::UnexpectedHashLength(pkh.0.len()));
                 }
             }
+            Address::Internal(_) => {}
         }
         Ok(address)
     }
@@ -124,6 +128,9 @@ impl Address {
                 Address::Implicit(_) => {
                     "Implicit"
                 }
+                Address::Internal(_) => {
+                    "Internal"
+                }
             },
             self.encode(),
         )
@@ -213,6 +220,39 @@ pub enum ImplicitAddress {
     Ed25519(key::ed25519::PublicKeyHash),
 }
 
+/// An internal address of a protocol module. Its validity predicate is
+/// built into the ledger rather than loaded from the storage, so it cannot be
+/// changed by transactions.
+#[derive(
+    Debug,
+    Clone,
+    Copy,
+    BorshSerialize,
+    BorshDeserialize,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    Serialize,
+    Deserialize,
+)]
+pub enum InternalAddress {
+    /// The protocol parameters
+    Parameters,
+    /// The proof-of-stake system
+    PoS,
+    /// The governance system
+    Governance,
+    /// The planned protocol upgrades
+    Upgrade,
+    /// The registry of the tokens
+    TokenRegistry,
+    /// The storage usage of the accounts and the escrow of their storage
+    /// deposits
+    StorageUsage,
+}
+
 /// Temporary helper for testing
 pub fn xan() -> Address {
     Address::decode("a1qq5qqqqqxuc5gvz9gycryv3sgye5v3j9gvurjv34g9prsd6x8qu5xs2ygdzrzsf38q6rss33xf42f3").expect("The token address decoding shouldn't fail")
@@ -288,6 +328,14 @@ pub mod tests {
         let address = key_gen.generate_address(rng_source);
         println!("address {}", address);
     }
+
+    #[test]
+    fn test_internal_address_encoding() {
+        let address = Address::Internal(InternalAddress::Governance);
+        let decoded =
+            Address::decode(address.encode()).expect("decoding should work");
+        assert_eq!(decoded, address);
+    }
 }
 
 /// Helpers for testing with addresses.
@@ -304,4 +352,9 @@ pub mod testing {
     pub fn established_address_2() -> Address {
         Address::decode("a1qq5qqqqqgcuyxv2pxgcrzdecx4prq3pexccr2vj9xse5gvf3gvmnv3f3xqcyyvjyxv6yvv34e393x7").expect("The token address decoding shouldn't fail")
     }
+
+    /// A sampled established address for tests
+    pub fn established_address_3() -> Address {
+        Address::decode("a1qq5qqqqqgdpyzd6zxfpnqvpnxq6yv3f3xepnxd2yxq6nwv6yxumr2sfc89pnqdzrgsurzsfc2yug7g").expect("The token address decoding shouldn't fail")
+    }
 }
@@ -0,0 +1,531 @@
+//! On-chain governance.
+//!
+//! Anyone can submit a proposal by locking a deposit of the staking token in
+//! the governance account. The proposal is open for voting for a number of
+//! epochs, during which accounts with bonded stake can vote on it. When the
+//! voting period is over, the votes are weighted by the voters' bonds and the
+//! proposal is tallied by the protocol. The deposit is returned if the quorum
+//! has been reached and burned otherwise. A passed proposal is executed by the
+//! protocol.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ledger::parameters::{Parameters, BASIS_POINTS};
+use crate::ledger::upgrade::UpgradePlan;
+use crate::types::address::{Address, InternalAddress};
+use crate::types::key::ed25519::{Keypair, SignedTxData};
+use crate::types::pos::{Epoch, PosParams};
+use crate::types::token::{self, Amount};
+use crate::types::{DbKeySeg, Key, KeySeg};
+
+/// The internal address of the governance system
+pub fn governance_address() -> Address {
+    Address::Internal(InternalAddress::Governance)
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error("The voting period must be at least one epoch")]
+    ZeroVotingPeriod,
+    #[error("The {0} of {1} basis points exceeds 100%")]
+    InvalidRate(&'static str, u16),
+    #[error("The proposal {0} doesn't exist")]
+    UnknownProposal(ProposalId),
+    #[error("The voting period of the proposal {0} is over")]
+    VotingPeriodOver(ProposalId),
+    #[error("The voter {0} has no bonded stake")]
+    NoStake(Address),
+    #[error("The proposal code size {size} exceeds the maximum {max}")]
+    ProposalCodeTooLarge { size: usize, max: u64 },
+    #[error("Token error: {0}")]
+    Token(token::Error),
+}
+
+/// Governance parameters
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+#[serde(default)]
+pub struct GovParams {
+    /// The deposit of the staking token required to submit a proposal in
+    /// micro units
+    pub min_deposit: u64,
+    /// The number of epochs in which a proposal can be voted on
+    pub voting_period: u64,
+    /// The share of the total bonded stake that has to vote for the proposal
+    /// to be valid, in basis points
+    pub quorum: u16,
+    /// The share of the stake voting yay out of the stake voting yay or nay
+    /// that has to be exceeded for the proposal to pass, in basis points
+    pub threshold: u16,
+    /// The maximum size of a proposal's wasm code in bytes
+    pub max_proposal_code_size: u64,
+}
+
+impl Default for GovParams {
+    fn default() -> Self {
+        Self {
+            min_deposit: token::SCALE * 1000,
+            voting_period: 2,
+            quorum: 3_340,
+            threshold: 5_000,
+            max_proposal_code_size: 1024 * 1024,
+        }
+    }
+}
+
+impl GovParams {
+    /// Check that the parameters are consistent.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.voting_period == 0 {
+            return Err(Error::ZeroVotingPeriod);
+        }
+        if self.quorum > BASIS_POINTS {
+            return Err(Error::InvalidRate("quorum", self.quorum));
+        }
+        if self.threshold > BASIS_POINTS {
+            return Err(Error::InvalidRate("threshold", self.threshold));
+        }
+        Ok(())
+    }
+
+    /// Check that a proposal's content is within the limits.
+    pub fn check_content(
+        &self,
+        content: &ProposalContent,
+    ) -> Result<(), Error> {
+        match content {
+            ProposalContent::Code(code)
+                if code.len() as u64 > self.max_proposal_code_size =>
+            {
+                Err(Error::ProposalCodeTooLarge {
+                    size: code.len(),
+                    max: self.max_proposal_code_size,
+                })
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The ID of a proposal, assigned in the order of submission
+pub type ProposalId = u64;
+
+/// The change that a proposal applies when it passes
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub enum ProposalContent {
+    /// A proposal without any effect
+    Text,
+    /// Replace the protocol parameters
+    Parameters(Parameters),
+    /// Replace the proof-of-stake parameters
+    PosParams(PosParams),
+    /// Run the wasm code as a transaction that is not checked by any validity
+    /// predicates
+    Code(Vec<u8>),
+    /// Schedule a protocol upgrade, replacing the previous plan, if any
+    Upgrade(UpgradePlan),
+}
+
+/// A submitted proposal
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Proposal {
+    /// The account that submitted the proposal and locked its deposit
+    pub author: Address,
+    /// A short description of the proposal
+    pub title: String,
+    /// The proposed change
+    pub content: ProposalContent,
+    /// The locked deposit
+    pub deposit: Amount,
+    /// The epoch in which the proposal was submitted
+    pub start_epoch: Epoch,
+    /// The first epoch in which the proposal cannot be voted on
+    pub end_epoch: Epoch,
+}
+
+impl Proposal {
+    /// Check if the proposal can be voted on in the given epoch.
+    pub fn is_voting(&self, epoch: Epoch) -> bool {
+        self.start_epoch <= epoch && epoch < self.end_epoch
+    }
+}
+
+/// A vote on a proposal
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub enum Vote {
+    /// In favour of the proposal
+    Yay,
+    /// Against the proposal
+    Nay,
+    /// Counted only towards the quorum
+    Abstain,
+}
+
+impl std::str::FromStr for Vote {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "yay" => Ok(Vote::Yay),
+            "nay" => Ok(Vote::Nay),
+            "abstain" => Ok(Vote::Abstain),
+            _ => {
+                Err(format!("Unknown vote {}, expected yay, nay or abstain", s))
+            }
+        }
+    }
+}
+
+/// The outcome of a tallied proposal
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub enum ProposalStatus {
+    /// The votes didn't reach the quorum and the deposit has been burned
+    NoQuorum,
+    /// The proposal has been rejected
+    Rejected,
+    /// The proposal has passed and its change has been applied
+    Passed,
+    /// The proposal has passed, but its code failed
+    Failed,
+}
+
+impl std::fmt::Display for ProposalStatus {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ProposalStatus::NoQuorum => write!(f, "no quorum"),
+            ProposalStatus::Rejected => write!(f, "rejected"),
+            ProposalStatus::Passed => write!(f, "passed"),
+            ProposalStatus::Failed => write!(f, "failed"),
+        }
+    }
+}
+
+/// The stake that voted on a proposal
+#[derive(
+    Clone,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Tally {
+    /// The stake voting yay
+    pub yay: Amount,
+    /// The stake voting nay
+    pub nay: Amount,
+    /// The stake voting abstain
+    pub abstain: Amount,
+}
+
+impl Tally {
+    /// Count the votes weighted by the voters' stake.
+    pub fn count(
+        votes: impl IntoIterator<Item = (Vote, Amount)>,
+    ) -> Result<Self, token::Error> {
+        let mut tally = Tally::default();
+        for (vote, stake) in votes {
+            match vote {
+                Vote::Yay => tally.yay.receive(&stake)?,
+                Vote::Nay => tally.nay.receive(&stake)?,
+                Vote::Abstain => tally.abstain.receive(&stake)?,
+            }
+        }
+        Ok(tally)
+    }
+
+    /// Get the proposal's outcome from the tally and the total bonded stake.
+    /// A passed proposal's change hasn't been applied yet.
+    pub fn status(
+        &self,
+        params: &GovParams,
+        total_stake: Amount,
+    ) -> ProposalStatus {
+        let micro = |amount: Amount| u64::from(amount) as u128;
+        let (yay, nay, abstain, total) = (
+            micro(self.yay),
+            micro(self.nay),
+            micro(self.abstain),
+            micro(total_stake),
+        );
+        let voted = yay + nay + abstain;
+        let bp = BASIS_POINTS as u128;
+        if total == 0 || voted * bp < params.quorum as u128 * total {
+            ProposalStatus::NoQuorum
+        } else if yay * bp > params.threshold as u128 * (yay + nay) {
+            ProposalStatus::Passed
+        } else {
+            ProposalStatus::Rejected
+        }
+    }
+}
+
+/// The result of a tallied proposal. It is only written by the protocol.
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct ProposalResult {
+    /// The proposal's outcome
+    pub status: ProposalStatus,
+    /// The counted votes
+    pub tally: Tally,
+}
+
+/// A tx data type to submit a proposal. It has to be signed by the author.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct SubmitProposal {
+    /// The account that locks the deposit
+    pub author: Address,
+    /// A short description of the proposal
+    pub title: String,
+    /// The proposed change
+    pub content: ProposalContent,
+}
+
+/// A tx data type to vote on a proposal. It has to be signed by the voter.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct VoteProposal {
+    /// The voting account
+    pub voter: Address,
+    /// The proposal
+    pub proposal_id: ProposalId,
+    /// The vote, which replaces the voter's previous vote, if any
+    pub vote: Vote,
+}
+
+impl SubmitProposal {
+    /// Sign a proposal submission with the author's keypair.
+    pub fn sign(
+        self,
+        tx_code: impl AsRef<[u8]>,
+        keypair: &Keypair,
+    ) -> SignedTxData {
+        let bytes = self
+            .try_to_vec()
+            .expect("Encoding unsigned proposal submission shouldn't fail");
+        SignedTxData::new(keypair, bytes, tx_code)
+    }
+}
+
+impl VoteProposal {
+    /// Sign a vote with the voter's keypair.
+    pub fn sign(
+        self,
+        tx_code: impl AsRef<[u8]>,
+        keypair: &Keypair,
+    ) -> SignedTxData {
+        let bytes = self
+            .try_to_vec()
+            .expect("Encoding unsigned vote shouldn't fail");
+        SignedTxData::new(keypair, bytes, tx_code)
+    }
+}
+
+const PARAMS_STORAGE_KEY: &str = "params";
+const NEXT_PROPOSAL_ID_STORAGE_KEY: &str = "next_proposal_id";
+const PROPOSAL_STORAGE_KEY: &str = "proposal";
+const VOTE_STORAGE_KEY: &str = "vote";
+const RESULT_STORAGE_KEY: &str = "result";
+
+fn gov_key(segment: &str) -> Key {
+    Key::from(governance_address().to_db_key())
+        .push(&segment.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Obtain the storage key of the governance parameters.
+pub fn params_key() -> Key {
+    gov_key(PARAMS_STORAGE_KEY)
+}
+
+/// Obtain the storage key of the ID of the next submitted proposal.
+pub fn next_proposal_id_key() -> Key {
+    gov_key(NEXT_PROPOSAL_ID_STORAGE_KEY)
+}
+
+/// Obtain the storage key of a [`Proposal`].
+pub fn proposal_key(id: ProposalId) -> Key {
+    gov_key(PROPOSAL_STORAGE_KEY)
+        .push(&id.to_string())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a proposal. If it is, returns the
+/// proposal's ID.
+pub fn is_proposal_key(key: &Key) -> Option<ProposalId> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(gov), DbKeySeg::StringSeg(prefix), DbKeySeg::StringSeg(id)]
+            if gov == &governance_address()
+                && prefix == PROPOSAL_STORAGE_KEY =>
+        {
+            id.parse().ok()
+        }
+        _ => None,
+    }
+}
+
+/// Obtain the storage key prefix of all the [`Vote`]s on a proposal.
+pub fn votes_prefix(id: ProposalId) -> Key {
+    proposal_key(id)
+        .push(&VOTE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Obtain the storage key of a voter's [`Vote`] on a proposal. The key
+/// contains the voter's address, so the vote has to be approved by the
+/// voter's VP.
+pub fn vote_key(id: ProposalId, voter: &Address) -> Key {
+    votes_prefix(id)
+        .push(&voter.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a vote. If it is, returns the proposal's
+/// ID and the voter.
+pub fn is_vote_key(key: &Key) -> Option<(ProposalId, &Address)> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(gov), DbKeySeg::StringSeg(prefix), DbKeySeg::StringSeg(id), DbKeySeg::StringSeg(vote), DbKeySeg::AddressSeg(voter)]
+            if gov == &governance_address()
+                && prefix == PROPOSAL_STORAGE_KEY
+                && vote == VOTE_STORAGE_KEY =>
+        {
+            id.parse().ok().map(|id| (id, voter))
+        }
+        _ => None,
+    }
+}
+
+/// Obtain the storage key of a tallied proposal's [`ProposalResult`].
+pub fn result_key(id: ProposalId) -> Key {
+    proposal_key(id)
+        .push(&RESULT_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::testing::established_address_1;
+
+    #[test]
+    fn test_governance_keys() {
+        let voter = established_address_1();
+        assert_eq!(is_proposal_key(&proposal_key(7)), Some(7));
+        assert_eq!(is_proposal_key(&result_key(7)), None);
+        assert_eq!(is_vote_key(&vote_key(7, &voter)), Some((7, &voter)));
+        assert_eq!(is_vote_key(&proposal_key(7)), None);
+        assert_eq!(is_vote_key(&votes_prefix(7)), None);
+        assert!(vote_key(7, &voter)
+            .to_string()
+            .starts_with(&votes_prefix(7).to_string()));
+    }
+
+    #[test]
+    fn test_tally() {
+        let params = GovParams {
+            quorum: 5_000,
+            threshold: 5_000,
+            ..GovParams::default()
+        };
+        let total = Amount::whole(100);
+
+        let tally = Tally::count(vec![
+            (Vote::Yay, Amount::whole(30)),
+            (Vote::Nay, Amount::whole(10)),
+        ])
+        .unwrap();
+        assert_eq!(tally.status(&params, total), ProposalStatus::NoQuorum);
+
+        // abstaining counts towards the quorum
+        let tally = Tally::count(vec![
+            (Vote::Yay, Amount::whole(30)),
+            (Vote::Nay, Amount::whole(10)),
+            (Vote::Abstain, Amount::whole(10)),
+        ])
+        .unwrap();
+        assert_eq!(tally.status(&params, total), ProposalStatus::Passed);
+
+        // a tie doesn't pass
+        let tally = Tally::count(vec![
+            (Vote::Yay, Amount::whole(30)),
+            (Vote::Nay, Amount::whole(30)),
+        ])
+        .unwrap();
+        assert_eq!(tally.status(&params, total), ProposalStatus::Rejected);
+    }
+}
@@ -0,0 +1,222 @@
+//! Hash-time-locked contracts for escrowed token balances.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+
+use crate::types::key::ed25519::{Keypair, SignedTxData};
+use crate::types::token::Amount;
+use crate::types::{Address, BlockHeight, DbKeySeg, Key, KeySeg};
+
+/// The length of the SHA-256 hash of a preimage
+pub const HASH_LOCK_LENGTH: usize = 32;
+
+/// A SHA-256 hash of the preimage that unlocks an escrow
+pub type HashLock = [u8; HASH_LOCK_LENGTH];
+
+/// Compute the hash lock of the given preimage.
+pub fn hash_lock(preimage: impl AsRef<[u8]>) -> HashLock {
+    let mut hasher = Sha256::new();
+    hasher.update(preimage.as_ref());
+    let mut hash_lock = [0; HASH_LOCK_LENGTH];
+    hash_lock.copy_from_slice(&hasher.finalize());
+    hash_lock
+}
+
+/// The terms of an escrow. The locked `amount` of the `token` is released to
+/// the `beneficiary` on presentation of a preimage of the `hash_lock` before
+/// the `deadline` height. From the `deadline` height on, it can only be
+/// refunded to the `sender`.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Htlc {
+    /// The account that locked the tokens and receives the refund
+    pub sender: Address,
+    /// The account that receives the tokens on a valid claim
+    pub beneficiary: Address,
+    /// The locked token
+    pub token: Address,
+    /// The locked amount
+    pub amount: Amount,
+    /// The SHA-256 hash of the secret preimage
+    pub hash_lock: HashLock,
+    /// The height from which the tokens can no longer be claimed and can be
+    /// refunded instead
+    pub deadline: BlockHeight,
+}
+
+impl Htlc {
+    /// Check if the preimage matches the hash lock.
+    pub fn is_valid_preimage(&self, preimage: impl AsRef<[u8]>) -> bool {
+        hash_lock(preimage) == self.hash_lock
+    }
+
+    /// Check if the tokens can be claimed at the given height.
+    pub fn can_claim_at(&self, height: BlockHeight) -> bool {
+        height < self.deadline
+    }
+
+    /// Check if the tokens can be refunded at the given height.
+    pub fn can_refund_at(&self, height: BlockHeight) -> bool {
+        height >= self.deadline
+    }
+}
+
+/// A tx data type to lock tokens in a new escrow account. It has to be signed
+/// by the sender.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct LockHtlc {
+    /// The terms of the escrow
+    pub htlc: Htlc,
+    /// The escrow's VP code
+    pub vp_code: Vec<u8>,
+}
+
+impl LockHtlc {
+    /// Sign the lock with the sender's keypair.
+    pub fn sign(
+        self,
+        tx_code: impl AsRef<[u8]>,
+        keypair: &Keypair,
+    ) -> SignedTxData {
+        let bytes = self
+            .try_to_vec()
+            .expect("Encoding unsigned HTLC lock shouldn't fail");
+        SignedTxData::new(keypair, bytes, tx_code)
+    }
+}
+
+/// A tx data type to release the escrowed tokens to the beneficiary. It
+/// doesn't need a signature, the preimage is the authorization.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct ClaimHtlc {
+    /// The escrow account
+    pub escrow: Address,
+    /// The preimage of the escrow's hash lock
+    pub preimage: Vec<u8>,
+}
+
+/// A tx data type to refund the escrowed tokens to the sender after the
+/// deadline.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct RefundHtlc {
+    /// The escrow account
+    pub escrow: Address,
+}
+
+const HTLC_STORAGE_KEY: &str = "htlc";
+const PREIMAGE_STORAGE_KEY: &str = "htlc_preimage";
+
+/// Obtain a storage key for the escrow's terms.
+pub fn htlc_key(escrow: &Address) -> Key {
+    Key::from(escrow.to_db_key())
+        .push(&HTLC_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is an escrow's terms key. If it is, returns
+/// the escrow.
+pub fn is_htlc_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(escrow), DbKeySeg::StringSeg(key)]
+            if key == HTLC_STORAGE_KEY =>
+        {
+            Some(escrow)
+        }
+        _ => None,
+    }
+}
+
+/// Obtain a storage key for the preimage revealed by a claim, so that the
+/// counterparty of a swap can read it.
+pub fn preimage_key(escrow: &Address) -> Key {
+    Key::from(escrow.to_db_key())
+        .push(&PREIMAGE_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is an escrow's revealed preimage key. If it
+/// is, returns the escrow.
+pub fn is_preimage_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(escrow), DbKeySeg::StringSeg(key)]
+            if key == PREIMAGE_STORAGE_KEY =>
+        {
+            Some(escrow)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address;
+
+    fn htlc(preimage: &[u8]) -> Htlc {
+        Htlc {
+            sender: address::testing::established_address_1(),
+            beneficiary: address::testing::established_address_2(),
+            token: address::xan(),
+            amount: Amount::whole(10),
+            hash_lock: hash_lock(preimage),
+            deadline: BlockHeight(100),
+        }
+    }
+
+    #[test]
+    fn test_htlc_preimage_and_deadline() {
+        let htlc = htlc(b"secret");
+        assert!(htlc.is_valid_preimage(b"secret"));
+        assert!(!htlc.is_valid_preimage(b"guess"));
+
+        assert!(htlc.can_claim_at(BlockHeight(99)));
+        assert!(!htlc.can_refund_at(BlockHeight(99)));
+        assert!(!htlc.can_claim_at(BlockHeight(100)));
+        assert!(htlc.can_refund_at(BlockHeight(100)));
+    }
+
+    #[test]
+    fn test_htlc_keys() {
+        let escrow = address::testing::established_address_1();
+        let key = htlc_key(&escrow);
+        assert_eq!(is_htlc_key(&key), Some(&escrow));
+        assert_eq!(is_preimage_key(&key), None);
+
+        let key = preimage_key(&escrow);
+        assert_eq!(is_preimage_key(&key), Some(&escrow));
+        assert_eq!(is_htlc_key(&key), None);
+    }
+}
@@ -61,6 +61,63 @@ pub fn is_pk_key(key: &Key) -> Option<&Address> {
     }
 }
 
+const RECOVERY_PK_STORAGE_KEY: &str = "ed25519_recovery_pk";
+
+/// Obtain a storage key for user's recovery public key. The recovery key can
+/// be used to rotate the user's public key when it's lost or compromised.
+pub fn recovery_pk_key(owner: &Address) -> Key {
+    Key::from(owner.to_db_key())
+        .push(&RECOVERY_PK_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a recovery public key. If it is, returns
+/// the owner.
+pub fn is_recovery_pk_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(key)]
+            if key == RECOVERY_PK_STORAGE_KEY =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
+/// A tx data type to rotate an account's keys. It has to be signed by the
+/// account's current public key or by its recovery public key.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct UpdateKey {
+    /// An address of the account
+    pub addr: Address,
+    /// The new public key, if it should be changed
+    pub pk: Option<PublicKey>,
+    /// The new recovery public key, if it should be changed
+    pub recovery_pk: Option<PublicKey>,
+}
+
+impl UpdateKey {
+    /// Sign data for transaction with a given keypair.
+    pub fn sign(
+        self,
+        tx_code: impl AsRef<[u8]>,
+        keypair: &Keypair,
+    ) -> SignedTxData {
+        let bytes = self
+            .try_to_vec()
+            .expect("Encoding data to update a key shouldn't fail");
+        SignedTxData::new(keypair, bytes, tx_code)
+    }
+}
+
 /// Sign the data with a key.
 pub fn sign(keypair: &Keypair, data: impl AsRef<[u8]>) -> Signature {
     Signature(keypair.sign(&data.as_ref()))
@@ -284,6 +341,12 @@ impl From<ed25519_dalek::PublicKey> for PublicKey {
     }
 }
 
+impl From<PublicKey> for ed25519_dalek::PublicKey {
+    fn from(pk: PublicKey) -> Self {
+        pk.0
+    }
+}
+
 impl From<PublicKey> for PublicKeyHash {
     fn from(pk: PublicKey) -> Self {
         let pk_bytes =
@@ -298,3 +361,33 @@ impl From<PublicKey> for PublicKeyHash {
         ))
     }
 }
+
+/// Helpers for testing with keys.
+#[cfg(any(test, feature = "testing"))]
+pub mod testing {
+    use super::*;
+
+    /// A keypair for tests
+    pub fn keypair_1() -> Keypair {
+        let bytes = [
+            3, 10, 17, 24, 31, 38, 45, 52, 59, 66, 73, 80, 87, 94, 101, 108,
+            115, 122, 129, 136, 143, 150, 157, 164, 171, 178, 185, 192, 199,
+            206, 213, 220, 117, 92, 76, 185, 37, 108, 167, 205, 196, 172, 253,
+            198, 207, 238, 218, 132, 144, 23, 229, 185, 249, 81, 78, 153, 25,
+            27, 214, 126, 11, 13, 66, 118,
+        ];
+        Keypair::from_bytes(&bytes).unwrap()
+    }
+
+    /// Another keypair for tests
+    pub fn keypair_2() -> Keypair {
+        let bytes = [
+            101, 114, 127, 140, 153, 166, 179, 192, 205, 218, 231, 244, 1, 14,
+            27, 40, 53, 66, 79, 92, 105, 118, 131, 144, 157, 170, 183, 196,
+            209, 222, 235, 248, 184, 241, 108, 23, 164, 214, 47, 164, 38, 189,
+            155, 92, 104, 107, 73, 74, 95, 192, 235, 224, 234, 42, 170, 24, 23,
+            189, 17, 200, 20, 47, 8, 64,
+        ];
+        Keypair::from_bytes(&bytes).unwrap()
+    }
+}
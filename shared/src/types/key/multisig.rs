@@ -0,0 +1,322 @@
+//! Multi-signature accounts controlled by a threshold of ed25519 keys
+
+use std::collections::HashSet;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use super::ed25519::{
+    self, verify_signature_raw, Keypair, PublicKey, Signature, SignedTxData,
+};
+use crate::types::{Address, DbKeySeg, Key, KeySeg};
+
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error(
+        "The threshold must be between 1 and the number of public keys \
+         {keys}, got {threshold}"
+    )]
+    InvalidThreshold { threshold: u8, keys: usize },
+    #[error("At most {max} public keys are allowed, got {keys}")]
+    TooManyKeys { max: usize, keys: usize },
+    #[error("The public keys must be unique")]
+    DuplicateKey,
+    #[error(
+        "Not enough valid signatures, got {valid}, the threshold is \
+         {threshold}"
+    )]
+    InsufficientSignatures { valid: usize, threshold: u8 },
+}
+
+/// Result for functions that may fail
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// The maximum number of public keys of a multi-signature account
+pub const MAX_KEYS: usize = u8::MAX as usize;
+
+/// The public keys of a multi-signature account and the number of them that
+/// have to sign a transaction to authorize it.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Multisig {
+    /// The public keys that can sign on behalf of the account
+    pub public_keys: Vec<PublicKey>,
+    /// The minimum number of signatures required
+    pub threshold: u8,
+}
+
+impl Multisig {
+    /// Create a new multi-signature keys set, checking that it's valid.
+    pub fn new(public_keys: Vec<PublicKey>, threshold: u8) -> Result<Self> {
+        let multisig = Self {
+            public_keys,
+            threshold,
+        };
+        multisig.validate()?;
+        Ok(multisig)
+    }
+
+    /// Check that the public keys are unique, that there are not too many of
+    /// them and that the threshold can be reached.
+    pub fn validate(&self) -> Result<()> {
+        let keys = self.public_keys.len();
+        if keys > MAX_KEYS {
+            return Err(Error::TooManyKeys {
+                max: MAX_KEYS,
+                keys,
+            });
+        }
+        if self.threshold == 0 || self.threshold as usize > keys {
+            return Err(Error::InvalidThreshold {
+                threshold: self.threshold,
+                keys,
+            });
+        }
+        let unique: HashSet<&PublicKey> = self.public_keys.iter().collect();
+        if unique.len() != keys {
+            return Err(Error::DuplicateKey);
+        }
+        Ok(())
+    }
+}
+
+const MULTISIG_STORAGE_KEY: &str = "multisig";
+
+/// Obtain a storage key for user's multi-signature keys set.
+pub fn multisig_key(owner: &Address) -> Key {
+    Key::from(owner.to_db_key())
+        .push(&MULTISIG_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a multi-signature keys set. If it is,
+/// returns the owner.
+pub fn is_multisig_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(key)]
+            if key == MULTISIG_STORAGE_KEY =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
+/// A signature of a single signer of a multi-signature transaction. It can be
+/// produced offline by each signer and then collected into a
+/// [`MultiSignedTxData`].
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct PartialSignature {
+    /// The signer's public key
+    pub pk: PublicKey,
+    /// The signature is produced on the tx data concatenated with the tx code.
+    pub sig: Signature,
+}
+
+impl PartialSignature {
+    /// Sign the transaction data with one of the keys.
+    pub fn new(
+        keypair: &Keypair,
+        data: &[u8],
+        tx_code: impl AsRef<[u8]>,
+    ) -> Self {
+        let to_sign = [data, tx_code.as_ref()].concat();
+        let sig = ed25519::sign(keypair, &to_sign);
+        let pk = PublicKey::from(keypair.public);
+        Self { pk, sig }
+    }
+}
+
+/// Like [`SignedTxData`], but signed by multiple keys. The signatures are
+/// checked by the validity predicate of a multi-signature account against its
+/// [`Multisig`] keys set.
+#[derive(Clone, Debug, BorshSerialize, BorshDeserialize)]
+pub struct MultiSignedTxData {
+    /// The tx data bytes
+    pub data: Vec<u8>,
+    /// The signatures collected from the signers
+    pub sigs: Vec<PartialSignature>,
+}
+
+impl MultiSignedTxData {
+    /// Collect the signatures on the given transaction data.
+    pub fn new(data: Vec<u8>, sigs: Vec<PartialSignature>) -> Self {
+        Self { data, sigs }
+    }
+
+    /// Verify that the transaction has been signed by at least the threshold
+    /// of the keys in the given keys set. Signatures by unknown keys and
+    /// repeated signatures of the same key are ignored.
+    pub fn verify(
+        &self,
+        multisig: &Multisig,
+        tx_code: impl AsRef<[u8]>,
+    ) -> Result<()> {
+        let tx_code = tx_code.as_ref();
+        self.verify_with(multisig, |pk, data, sig| {
+            let data = [data, tx_code].concat();
+            verify_signature_raw(pk, &data, sig).is_ok()
+        })
+    }
+
+    /// Like [`MultiSignedTxData::verify`], but each signature of the tx data
+    /// is checked with the given function. This is used by the validity
+    /// predicates, which don't have access to the tx code themselves and so
+    /// they delegate the signature verification to the host.
+    pub fn verify_with(
+        &self,
+        multisig: &Multisig,
+        verify_sig: impl Fn(&PublicKey, &[u8], &Signature) -> bool,
+    ) -> Result<()> {
+        let threshold = multisig.threshold as usize;
+        let mut signers: HashSet<&PublicKey> = HashSet::new();
+        for PartialSignature { pk, sig } in &self.sigs {
+            if signers.len() >= threshold {
+                break;
+            }
+            if multisig.public_keys.contains(pk)
+                && !signers.contains(pk)
+                && verify_sig(pk, &self.data, sig)
+            {
+                signers.insert(pk);
+            }
+        }
+        if signers.len() >= threshold {
+            Ok(())
+        } else {
+            Err(Error::InsufficientSignatures {
+                valid: signers.len(),
+                threshold: multisig.threshold,
+            })
+        }
+    }
+}
+
+/// A tx data type to initialize a new established multi-signature account
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct InitMultisigAccount {
+    /// The keys set to be written into the account's storage
+    pub multisig: Multisig,
+    /// The VP code
+    pub vp_code: Vec<u8>,
+}
+
+/// Get the data from transaction data signed either by a single key
+/// ([`SignedTxData`]) or by multiple keys ([`MultiSignedTxData`]). The
+/// signatures are not checked here, that's up to the validity predicates.
+pub fn signed_data(tx_data: &[u8]) -> Option<Vec<u8>> {
+    if let Ok(signed) = SignedTxData::try_from_slice(tx_data) {
+        Some(signed.data)
+    } else if let Ok(signed) = MultiSignedTxData::try_from_slice(tx_data) {
+        Some(signed.data)
+    } else {
+        None
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn keypair(seed: u8) -> Keypair {
+        let secret = ed25519::SecretKey::from_bytes(&[seed; 32])
+            .expect("the secret key should be valid");
+        let public = ed25519_dalek::PublicKey::from(&secret);
+        Keypair { secret, public }
+    }
+
+    fn pk(keypair: &Keypair) -> PublicKey {
+        PublicKey::from(keypair.public)
+    }
+
+    #[test]
+    fn test_invalid_multisig() {
+        let keys = vec![pk(&keypair(1)), pk(&keypair(2))];
+        assert_eq!(
+            Multisig::new(keys.clone(), 0),
+            Err(Error::InvalidThreshold {
+                threshold: 0,
+                keys: 2
+            })
+        );
+        assert_eq!(
+            Multisig::new(keys, 3),
+            Err(Error::InvalidThreshold {
+                threshold: 3,
+                keys: 2
+            })
+        );
+        let keys = vec![pk(&keypair(1)), pk(&keypair(1))];
+        assert_eq!(Multisig::new(keys, 1), Err(Error::DuplicateKey));
+    }
+
+    #[test]
+    fn test_multisig_threshold() {
+        let keypairs: Vec<Keypair> = (1..=3).map(keypair).collect();
+        let multisig = Multisig::new(keypairs.iter().map(pk).collect(), 2)
+            .expect("the multisig should be valid");
+        let tx_code = b"tx code";
+        let data = b"tx data".to_vec();
+
+        // a single signature is not enough
+        let sigs = vec![PartialSignature::new(&keypairs[0], &data, tx_code)];
+        let tx = MultiSignedTxData::new(data.clone(), sigs.clone());
+        assert_eq!(
+            tx.verify(&multisig, tx_code),
+            Err(Error::InsufficientSignatures {
+                valid: 1,
+                threshold: 2
+            })
+        );
+
+        // repeating the same signature doesn't count
+        let mut repeated_sigs = sigs.clone();
+        repeated_sigs.push(sigs[0].clone());
+        let tx = MultiSignedTxData::new(data.clone(), repeated_sigs);
+        assert!(tx.verify(&multisig, tx_code).is_err());
+
+        // a signature from an unknown key doesn't count
+        let mut unknown_sigs = sigs.clone();
+        unknown_sigs.push(PartialSignature::new(&keypair(4), &data, tx_code));
+        let tx = MultiSignedTxData::new(data.clone(), unknown_sigs);
+        assert!(tx.verify(&multisig, tx_code).is_err());
+
+        // a signature on a different code doesn't count
+        let mut wrong_code_sigs = sigs.clone();
+        wrong_code_sigs.push(PartialSignature::new(
+            &keypairs[1],
+            &data,
+            b"other code",
+        ));
+        let tx = MultiSignedTxData::new(data.clone(), wrong_code_sigs);
+        assert!(tx.verify(&multisig, tx_code).is_err());
+
+        // two valid signatures reach the threshold
+        let mut valid_sigs = sigs;
+        valid_sigs.push(PartialSignature::new(&keypairs[2], &data, tx_code));
+        let tx = MultiSignedTxData::new(data, valid_sigs);
+        assert_eq!(tx.verify(&multisig, tx_code), Ok(()));
+
+        // the data can be read without knowing how it's been signed
+        let tx_data = tx.try_to_vec().expect("encoding shouldn't fail");
+        assert_eq!(signed_data(&tx_data), Some(b"tx data".to_vec()));
+    }
+}
@@ -3,19 +3,25 @@
 use std::convert::{TryFrom, TryInto};
 use std::fmt::Display;
 
-pub use address::{Address, EstablishedAddress, ImplicitAddress};
+pub use address::{
+    Address, EstablishedAddress, ImplicitAddress, InternalAddress,
+};
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 
 use crate::bytes::ByteBuf;
-use crate::types::key::ed25519::{Keypair, SignedTxData};
+use crate::types::key::ed25519::{Keypair, PublicKey, SignedTxData};
 
 pub mod address;
+pub mod governance;
+pub mod htlc;
 pub mod intent;
 pub mod internal;
 pub mod key;
+pub mod pos;
 pub mod token;
+pub mod vesting;
 
 #[allow(missing_docs)]
 #[derive(Error, Debug)]
@@ -161,6 +167,19 @@ impl Key {
         segments.push(DbKeySeg::StringSeg(RESERVED_VP_KEY.to_owned()));
         Ok(Key { segments })
     }
+
+    /// Check if the key is a key of a validity predicate. If it is, returns the
+    /// address of the account.
+    pub fn is_validity_predicate(&self) -> Option<&Address> {
+        match &self.segments[..] {
+            [DbKeySeg::AddressSeg(address), DbKeySeg::StringSeg(sub_key)]
+                if sub_key == RESERVED_VP_KEY =>
+            {
+                Some(address)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl Display for Key {
@@ -395,6 +414,40 @@ impl UpdateVp {
     }
 }
 
+/// A tx data type to initialize a new established account
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct InitAccount {
+    /// The account that signs the transaction and pays the new account's
+    /// storage deposit
+    pub source: Address,
+    /// The public key to be written into the account's storage
+    pub public_key: PublicKey,
+    /// The VP code
+    pub vp_code: Vec<u8>,
+}
+
+impl InitAccount {
+    /// Sign data for transaction with a given keypair.
+    pub fn sign(
+        self,
+        tx_code: impl AsRef<[u8]>,
+        keypair: &Keypair,
+    ) -> SignedTxData {
+        let bytes = self
+            .try_to_vec()
+            .expect("Encoding data to initialize an account shouldn't fail");
+        SignedTxData::new(keypair, bytes, tx_code)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use proptest::prelude::*;
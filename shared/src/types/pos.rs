@@ -0,0 +1,1467 @@
+//! Proof-of-stake: validator registration, bonds of the staking token and the
+//! validator set computed from them at every epoch.
+//!
+//! All the state is stored under the reserved [`pos_address`]. The bonded
+//! tokens are held in the PoS account's balance of the [`staking_token`].
+//!
+//! At the end of every epoch, new tokens are minted as rewards for the
+//! validator set of the epoch, proportionally to the validators' voting power.
+//! A validator takes its commission from its rewards and the rest is shared
+//! between the validator's bonds. The rewards are held by the PoS account until
+//! they're claimed by their owners.
+//!
+//! A validator that votes twice in the same round or misses too many blocks in
+//! an epoch is slashed. A share of all the tokens bonded to it, including those
+//! that are still unbonding, is burned and the validator is jailed, which
+//! removes it from the validator set until it's unjailed with a transaction.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::ops::Add;
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::ledger::parameters::BASIS_POINTS;
+use crate::types::address::{self, Address, InternalAddress};
+use crate::types::key::ed25519::{Keypair, PublicKey, SignedTxData};
+use crate::types::token::{self, Amount, Change};
+use crate::types::{BlockHeight, DbKeySeg, Key, KeySeg};
+
+/// The internal address of the proof-of-stake system
+pub fn pos_address() -> Address {
+    Address::Internal(InternalAddress::PoS)
+}
+
+/// The token that can be bonded to validators
+pub fn staking_token() -> Address {
+    address::xan()
+}
+
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error("The validator {0} is already registered")]
+    AlreadyRegistered(Address),
+    #[error("The validator {0} is not registered")]
+    NotRegistered(Address),
+    #[error(
+        "Insufficient bond {bonded} to the validator, requested {requested}"
+    )]
+    InsufficientBond { bonded: Amount, requested: Amount },
+    #[error("There are no withdrawable unbonds from the validator {0}")]
+    NothingToWithdraw(Address),
+    #[error("Token error: {0}")]
+    Token(token::Error),
+    #[error("The epoch length must be greater than zero")]
+    ZeroEpochLength,
+    #[error("The maximum number of validators must be greater than zero")]
+    ZeroMaxValidators,
+    #[error("The commission rate of {0} basis points exceeds 100%")]
+    InvalidCommissionRate(u16),
+    #[error("There are no rewards to claim")]
+    NothingToClaim,
+    #[error("The slash rate of {0} basis points exceeds 100%")]
+    InvalidSlashRate(u16),
+    #[error("The validator {0} is not jailed")]
+    NotJailed(Address),
+    #[error("The validator can only be unjailed from the epoch {0}")]
+    StillJailed(Epoch),
+}
+
+/// A transaction's changes of the PoS state that cannot be accepted
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum ValidationError {
+    #[error("Cannot bond to an unregistered validator {0}")]
+    UnregisteredValidator(Address),
+    #[error(
+        "An unbond can only be added to the epoch {expected}, got {actual:?}"
+    )]
+    InvalidUnbondEpoch { expected: Epoch, actual: Epoch },
+    #[error("The unbond of the epoch {0:?} cannot be withdrawn yet")]
+    UnbondNotWithdrawable(Epoch),
+    #[error(
+        "The unbonded amount doesn't match the decrease of the bond of {owner} \
+         to {validator}"
+    )]
+    UnbondMismatch { owner: Address, validator: Address },
+    #[error("The total bonds of the validator {0} don't match its bonds")]
+    TotalBondsMismatch(Address),
+    #[error("The rewards of {0} can only be claimed")]
+    RewardsIncrease(Address),
+    #[error(
+        "The change {actual} of the PoS balance doesn't match the change \
+         {expected} of the bonds, unbonds and rewards"
+    )]
+    BalanceMismatch { expected: Change, actual: Change },
+}
+
+/// An epoch is a range of consecutive blocks with the same validator set
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    Hash,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Epoch(pub u64);
+
+impl Add<u64> for Epoch {
+    type Output = Epoch;
+
+    fn add(self, rhs: u64) -> Self::Output {
+        Epoch(self.0 + rhs)
+    }
+}
+
+impl std::fmt::Display for Epoch {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+/// The current epoch with the block height and time at which it started. It
+/// is only written by the protocol.
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct EpochState {
+    /// The current epoch
+    pub epoch: Epoch,
+    /// The height of the first block of the epoch
+    pub start_height: BlockHeight,
+    /// The time of the first block of the epoch in seconds since the Unix
+    /// epoch
+    pub start_time: u64,
+}
+
+/// Proof-of-stake parameters
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+#[serde(default)]
+pub struct PosParams {
+    /// The minimum number of blocks in an epoch
+    pub epoch_length: u64,
+    /// The minimum duration of an epoch in seconds. An epoch ends with the
+    /// first block that satisfies both its minimum length and duration.
+    pub epoch_duration: u64,
+    /// The number of epochs after which unbonded tokens can be withdrawn
+    pub unbonding_epochs: u64,
+    /// The maximum number of validators in the validator set
+    pub max_validators: u64,
+    /// The reward minted for every block of an epoch in micro units of the
+    /// staking token
+    pub block_reward: u64,
+    /// The share of the stake slashed for a duplicate vote in basis points
+    pub duplicate_vote_slash_rate: u16,
+    /// The share of the stake slashed for downtime in basis points
+    pub downtime_slash_rate: u16,
+    /// The number of blocks that a validator can miss in an epoch before it's
+    /// slashed for downtime
+    pub max_missed_blocks: u64,
+    /// The number of epochs for which a slashed validator is jailed
+    pub jail_epochs: u64,
+}
+
+impl Default for PosParams {
+    fn default() -> Self {
+        Self {
+            epoch_length: 100,
+            epoch_duration: 0,
+            unbonding_epochs: 2,
+            max_validators: 100,
+            block_reward: 0,
+            duplicate_vote_slash_rate: 500,
+            downtime_slash_rate: 10,
+            max_missed_blocks: 50,
+            jail_epochs: 2,
+        }
+    }
+}
+
+impl PosParams {
+    /// Check that the parameters are consistent.
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.epoch_length == 0 {
+            return Err(Error::ZeroEpochLength);
+        }
+        if self.max_validators == 0 {
+            return Err(Error::ZeroMaxValidators);
+        }
+        for rate in &[self.duplicate_vote_slash_rate, self.downtime_slash_rate]
+        {
+            if *rate > BASIS_POINTS {
+                return Err(Error::InvalidSlashRate(*rate));
+            }
+        }
+        Ok(())
+    }
+
+    /// Get the slash rate of the given misbehavior in basis points.
+    pub fn slash_rate(&self, slash_type: SlashType) -> u16 {
+        match slash_type {
+            SlashType::DuplicateVote => self.duplicate_vote_slash_rate,
+            SlashType::Downtime => self.downtime_slash_rate,
+        }
+    }
+
+    /// Check if the block at the given height and time is the last one of the
+    /// current epoch.
+    pub fn is_epoch_end(
+        &self,
+        state: &EpochState,
+        height: BlockHeight,
+        time: u64,
+    ) -> bool {
+        let blocks = height.0.saturating_sub(state.start_height.0) + 1;
+        let duration = time.saturating_sub(state.start_time);
+        blocks >= self.epoch_length && duration >= self.epoch_duration
+    }
+
+    /// Get the reward for an epoch that ended at the given height.
+    pub fn epoch_reward(
+        &self,
+        state: &EpochState,
+        height: BlockHeight,
+    ) -> Amount {
+        let blocks = height.0.saturating_sub(state.start_height.0) + 1;
+        Amount::from(self.block_reward.saturating_mul(blocks))
+    }
+
+    /// Get the epoch from which the tokens unbonded in the given epoch can be
+    /// withdrawn.
+    pub fn withdrawable_epoch(&self, unbonded_at: Epoch) -> Epoch {
+        unbonded_at + self.unbonding_epochs
+    }
+}
+
+/// The bonds of a source by the validators they are bonded to
+pub type Bonds = BTreeMap<Address, Amount>;
+
+/// The unbonded amounts of a source by the validators they were bonded to and
+/// the epochs from which they can be withdrawn
+pub type Unbonds = BTreeMap<Address, BTreeMap<Epoch, Amount>>;
+
+/// The total bonded stake of the validators
+pub type TotalBonds = BTreeMap<Address, Amount>;
+
+/// A validator in the validator set
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct ActiveValidator {
+    /// The validator's consensus key
+    pub consensus_key: PublicKey,
+    /// The validator's voting power
+    pub voting_power: u64,
+}
+
+/// The validator set of an epoch
+pub type ValidatorSet = BTreeMap<Address, ActiveValidator>;
+
+/// A validator set that has been replaced. It's kept to find the validators
+/// reported for misbehavior at a height at which it was still in use.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct PastValidatorSet {
+    /// The epoch in which the validator set was replaced
+    pub epoch: Epoch,
+    /// The replaced validator set
+    pub validator_set: ValidatorSet,
+}
+
+/// The past validator sets keyed by the first height at which Tendermint no
+/// longer uses them
+pub type PastValidatorSets = BTreeMap<BlockHeight, PastValidatorSet>;
+
+/// Find the validator set used by Tendermint at the given height, which is
+/// either one of the past validator sets or the current one.
+pub fn validator_set_at<'a>(
+    current: &'a ValidatorSet,
+    past: &'a PastValidatorSets,
+    height: BlockHeight,
+) -> &'a ValidatorSet {
+    past.range(BlockHeight(height.0 + 1)..)
+        .next()
+        .map(|(_, past)| &past.validator_set)
+        .unwrap_or(current)
+}
+
+/// The maximum commission rate in basis points, i.e. 100%
+pub const MAX_COMMISSION_RATE: u16 = BASIS_POINTS;
+
+/// A validator's misbehavior that is punished by slashing
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub enum SlashType {
+    /// Signing two different blocks in the same round
+    DuplicateVote,
+    /// Missing more than the maximum number of blocks in an epoch
+    Downtime,
+}
+
+impl std::fmt::Display for SlashType {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SlashType::DuplicateVote => write!(f, "duplicate vote"),
+            SlashType::Downtime => write!(f, "downtime"),
+        }
+    }
+}
+
+/// A record of a validator's slash
+#[derive(
+    Clone,
+    Debug,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Slash {
+    /// The epoch in which the validator was slashed
+    pub epoch: Epoch,
+    /// The height of the block that processed the misbehavior
+    pub height: BlockHeight,
+    /// The misbehavior
+    pub slash_type: SlashType,
+    /// The slashed share of the stake in basis points
+    pub rate: u16,
+    /// The total amount of the burned tokens
+    pub amount: Amount,
+}
+
+/// The slashes of a validator
+pub type Slashes = Vec<Slash>;
+
+/// The number of blocks missed by the validators in the current epoch
+pub type MissedBlocks = BTreeMap<Address, u64>;
+
+/// Get the slash rate for a misbehavior of a validator with the given voting
+/// power at the time of the infraction. When the validator's current voting
+/// power is higher, the rate is reduced proportionally, so that the tokens
+/// bonded after the infraction are not slashed.
+pub fn infraction_slash_rate(
+    rate: u16,
+    infraction_voting_power: u64,
+    voting_power: u64,
+) -> u16 {
+    if voting_power <= infraction_voting_power {
+        rate
+    } else {
+        let rate = rate as u128 * infraction_voting_power as u128
+            / voting_power as u128;
+        // the reduced rate is less than the given rate
+        rate as u16
+    }
+}
+
+/// Get the amount slashed from a bond with the given rate in basis points,
+/// rounded down.
+pub fn slashed_amount(amount: Amount, rate: u16) -> Amount {
+    let slashed =
+        u64::from(amount) as u128 * rate as u128 / BASIS_POINTS as u128;
+    // the rate is at most 100%, so the result fits in `u64`
+    Amount::from(slashed as u64)
+}
+
+/// The share of a validator's rewards that the validator takes before the rest
+/// is shared between its bonds, in basis points
+#[derive(
+    Clone,
+    Copy,
+    Debug,
+    Default,
+    PartialEq,
+    Eq,
+    PartialOrd,
+    Ord,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct CommissionRate(pub u16);
+
+impl CommissionRate {
+    /// Check that the rate is at most [`MAX_COMMISSION_RATE`].
+    pub fn validate(&self) -> Result<(), Error> {
+        if self.0 > MAX_COMMISSION_RATE {
+            return Err(Error::InvalidCommissionRate(self.0));
+        }
+        Ok(())
+    }
+}
+
+impl std::fmt::Display for CommissionRate {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{:02}%", self.0 / 100, self.0 % 100)
+    }
+}
+
+/// The rewards of an ended epoch for its validator set, waiting to be
+/// distributed. It is only written by the protocol.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct RewardPool {
+    /// The total reward of the epoch
+    pub reward: Amount,
+    /// The validator set of the epoch
+    pub validator_set: ValidatorSet,
+}
+
+/// Share the pool's reward between the validators proportionally to their
+/// voting power. From every validator's share, the validator takes its
+/// commission and the rest is shared between the bonds to the validator
+/// proportionally to their amounts. Returns the rewards by their owners. The
+/// amounts are rounded down, so their sum may be less than the pool's reward.
+pub fn distribute_rewards(
+    pool: &RewardPool,
+    commission_rate: impl Fn(&Address) -> CommissionRate,
+    bonds: &BTreeMap<Address, BTreeMap<Address, Amount>>,
+) -> BTreeMap<Address, Amount> {
+    let mut rewards: BTreeMap<Address, u128> = BTreeMap::new();
+    let total_power: u128 = pool
+        .validator_set
+        .values()
+        .map(|active| active.voting_power as u128)
+        .sum();
+    if total_power == 0 {
+        return BTreeMap::new();
+    }
+    let reward = u64::from(pool.reward) as u128;
+    for (validator, active) in &pool.validator_set {
+        let validator_reward =
+            reward * active.voting_power as u128 / total_power;
+        let commission = validator_reward
+            * commission_rate(validator).0 as u128
+            / MAX_COMMISSION_RATE as u128;
+        *rewards.entry(validator.clone()).or_default() += commission;
+
+        let delegated = validator_reward - commission;
+        let validator_bonds = match bonds.get(validator) {
+            Some(validator_bonds) => validator_bonds,
+            None => continue,
+        };
+        let total_bonded: u128 = validator_bonds
+            .values()
+            .map(|amount| u64::from(*amount) as u128)
+            .sum();
+        if total_bonded == 0 {
+            continue;
+        }
+        for (source, amount) in validator_bonds {
+            *rewards.entry(source.clone()).or_default() +=
+                delegated * u64::from(*amount) as u128 / total_bonded;
+        }
+    }
+    rewards
+        .into_iter()
+        .filter(|(_, reward)| *reward > 0)
+        .map(|(owner, reward)| {
+            // the shares cannot exceed the pool's reward, which fits in `u64`
+            (owner, Amount::from(reward as u64))
+        })
+        .collect()
+}
+
+/// A change of a validator's voting power to be applied by the consensus.
+/// Zero voting power removes the validator.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ValidatorUpdate {
+    /// The validator's consensus key
+    pub consensus_key: PublicKey,
+    /// The validator's new voting power
+    pub voting_power: u64,
+}
+
+/// The voting power of the given stake, one per whole token.
+pub fn voting_power(stake: Amount) -> u64 {
+    u64::from(stake) / token::SCALE
+}
+
+/// Compute the validator set from the validators' total bonds. The validators
+/// with the most voting power are selected, up to the maximum number of
+/// validators. Validators without any voting power or without a consensus key
+/// are left out.
+pub fn compute_validator_set(
+    params: &PosParams,
+    total_bonds: &TotalBonds,
+    consensus_key: impl Fn(&Address) -> Option<PublicKey>,
+) -> ValidatorSet {
+    let mut candidates: Vec<(&Address, u64)> = total_bonds
+        .iter()
+        .map(|(validator, stake)| (validator, voting_power(*stake)))
+        .filter(|(_, voting_power)| *voting_power > 0)
+        .collect();
+    // the most voting power first, ties are broken by the address
+    candidates.sort_by(|(addr_a, power_a), (addr_b, power_b)| {
+        power_b.cmp(power_a).then(addr_a.cmp(addr_b))
+    });
+    candidates
+        .into_iter()
+        .filter_map(|(validator, voting_power)| {
+            consensus_key(validator).map(|consensus_key| {
+                (
+                    validator.clone(),
+                    ActiveValidator {
+                        consensus_key,
+                        voting_power,
+                    },
+                )
+            })
+        })
+        .take(params.max_validators as usize)
+        .collect()
+}
+
+/// Get the updates that turn the old validator set into the new one.
+pub fn validator_set_updates(
+    old: &ValidatorSet,
+    new: &ValidatorSet,
+) -> Vec<ValidatorUpdate> {
+    let mut updates = vec![];
+    for (validator, active) in old {
+        // removed validators and replaced consensus keys
+        let is_removed = match new.get(validator) {
+            Some(new_active) => {
+                new_active.consensus_key != active.consensus_key
+            }
+            None => true,
+        };
+        if is_removed {
+            updates.push(ValidatorUpdate {
+                consensus_key: active.consensus_key.clone(),
+                voting_power: 0,
+            });
+        }
+    }
+    for (validator, active) in new {
+        if old.get(validator) != Some(active) {
+            updates.push(ValidatorUpdate {
+                consensus_key: active.consensus_key.clone(),
+                voting_power: active.voting_power,
+            });
+        }
+    }
+    updates
+}
+
+/// The changes of the PoS state made by a transaction
+#[derive(Debug, Clone, Default)]
+pub struct PosChanges {
+    /// The changed bonds by their sources, before and after the transaction
+    pub bonds: Vec<(Address, Bonds, Bonds)>,
+    /// The changed unbonds by their sources, before and after the transaction
+    pub unbonds: Vec<(Address, Unbonds, Unbonds)>,
+    /// The total bonds before the transaction
+    pub total_bonds_pre: TotalBonds,
+    /// The total bonds after the transaction
+    pub total_bonds_post: TotalBonds,
+    /// The changed rewards by their owners, before and after the transaction
+    pub rewards: Vec<(Address, Amount, Amount)>,
+    /// The change of the PoS balance of the staking token
+    pub balance_change: Change,
+}
+
+fn amount_change(pre: Option<&Amount>, post: Option<&Amount>) -> Change {
+    post.map(Amount::change).unwrap_or_default()
+        - pre.map(Amount::change).unwrap_or_default()
+}
+
+impl PosChanges {
+    /// Check that the changes are consistent: bonds can only be added to
+    /// registered validators, a decrease of a bond must be matched by an
+    /// unbond withdrawable after the unbonding period, only matured unbonds
+    /// can be withdrawn, the total bonds must follow the bonds, rewards can
+    /// only be claimed and the PoS balance must hold exactly the bonded and
+    /// unbonded tokens and the rewards.
+    pub fn validate(
+        &self,
+        params: &PosParams,
+        current_epoch: Epoch,
+        is_registered: impl Fn(&Address) -> bool,
+    ) -> Result<(), ValidationError> {
+        // bond changes by their (source, validator)
+        let mut bond_changes: BTreeMap<(&Address, &Address), Change> =
+            BTreeMap::new();
+        // total bond changes by their validator
+        let mut total_changes: BTreeMap<&Address, Change> = BTreeMap::new();
+        for (source, pre, post) in &self.bonds {
+            let validators: BTreeSet<&Address> =
+                pre.keys().chain(post.keys()).collect();
+            for validator in validators {
+                let change =
+                    amount_change(pre.get(validator), post.get(validator));
+                if change > 0 && !is_registered(validator) {
+                    return Err(ValidationError::UnregisteredValidator(
+                        validator.clone(),
+                    ));
+                }
+                *bond_changes.entry((source, validator)).or_default() += change;
+                *total_changes.entry(validator).or_default() += change;
+            }
+        }
+
+        // added unbonds by their (source, validator)
+        let mut unbonded: BTreeMap<(&Address, &Address), Change> =
+            BTreeMap::new();
+        let mut unbonds_change: Change = 0;
+        let expected_epoch = params.withdrawable_epoch(current_epoch);
+        for (source, pre, post) in &self.unbonds {
+            let validators: BTreeSet<&Address> =
+                pre.keys().chain(post.keys()).collect();
+            for validator in validators {
+                let empty = BTreeMap::new();
+                let pre = pre.get(validator).unwrap_or(&empty);
+                let post = post.get(validator).unwrap_or(&empty);
+                let epochs: BTreeSet<&Epoch> =
+                    pre.keys().chain(post.keys()).collect();
+                for epoch in epochs {
+                    let change = amount_change(pre.get(epoch), post.get(epoch));
+                    if change > 0 {
+                        if *epoch != expected_epoch {
+                            return Err(ValidationError::InvalidUnbondEpoch {
+                                expected: expected_epoch,
+                                actual: *epoch,
+                            });
+                        }
+                        *unbonded.entry((source, validator)).or_default() +=
+                            change;
+                    } else if change < 0 && *epoch > current_epoch {
+                        return Err(ValidationError::UnbondNotWithdrawable(
+                            *epoch,
+                        ));
+                    }
+                    unbonds_change += change;
+                }
+            }
+        }
+
+        let pairs: BTreeSet<&(&Address, &Address)> =
+            bond_changes.keys().chain(unbonded.keys()).collect();
+        for pair in pairs {
+            let bond_change =
+                bond_changes.get(pair).copied().unwrap_or_default();
+            let unbond_change = unbonded.get(pair).copied().unwrap_or_default();
+            let expected_unbond =
+                if bond_change < 0 { -bond_change } else { 0 };
+            if unbond_change != expected_unbond {
+                let (owner, validator) = pair;
+                return Err(ValidationError::UnbondMismatch {
+                    owner: (*owner).clone(),
+                    validator: (*validator).clone(),
+                });
+            }
+        }
+
+        let validators: BTreeSet<&Address> = self
+            .total_bonds_pre
+            .keys()
+            .chain(self.total_bonds_post.keys())
+            .chain(total_changes.keys().copied())
+            .collect();
+        let mut bonds_change: Change = 0;
+        for validator in validators {
+            let change = amount_change(
+                self.total_bonds_pre.get(validator),
+                self.total_bonds_post.get(validator),
+            );
+            if change
+                != total_changes.get(validator).copied().unwrap_or_default()
+            {
+                return Err(ValidationError::TotalBondsMismatch(
+                    validator.clone(),
+                ));
+            }
+            bonds_change += change;
+        }
+
+        let mut rewards_change: Change = 0;
+        for (owner, pre, post) in &self.rewards {
+            let change = post.change() - pre.change();
+            if change > 0 {
+                return Err(ValidationError::RewardsIncrease(owner.clone()));
+            }
+            rewards_change += change;
+        }
+
+        let expected = bonds_change + unbonds_change + rewards_change;
+        if self.balance_change != expected {
+            return Err(ValidationError::BalanceMismatch {
+                expected,
+                actual: self.balance_change,
+            });
+        }
+        Ok(())
+    }
+}
+
+/// A tx data type to register a validator with its consensus key. It has to
+/// be signed by the validator.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct RegisterValidator {
+    /// The validator's account
+    pub validator: Address,
+    /// The key that the validator uses to sign blocks
+    pub consensus_key: PublicKey,
+    /// The validator's commission rate
+    pub commission_rate: CommissionRate,
+}
+
+/// A tx data type to change a registered validator's commission rate. It has
+/// to be signed by the validator.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct ChangeCommission {
+    /// The validator's account
+    pub validator: Address,
+    /// The new commission rate
+    pub commission_rate: CommissionRate,
+}
+
+/// A tx data type to bond tokens from the source to a validator. It has to be
+/// signed by the source.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Bond {
+    /// The owner of the bonded tokens
+    pub source: Address,
+    /// The validator to bond to
+    pub validator: Address,
+    /// The amount of the staking token
+    pub amount: Amount,
+}
+
+/// A tx data type to unbond tokens bonded from the source to a validator. It
+/// has to be signed by the source.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Unbond {
+    /// The owner of the bonded tokens
+    pub source: Address,
+    /// The validator to unbond from
+    pub validator: Address,
+    /// The amount of the staking token
+    pub amount: Amount,
+}
+
+/// A tx data type to withdraw the source's unbonded tokens, whose unbonding
+/// period is over, back to the source. It has to be signed by the source.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Withdraw {
+    /// The owner of the unbonded tokens
+    pub source: Address,
+    /// The validator the tokens were bonded to
+    pub validator: Address,
+}
+
+/// A tx data type to unjail a validator after its jail period is over. It has
+/// to be signed by the validator.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Unjail {
+    /// The validator's account
+    pub validator: Address,
+}
+
+/// A tx data type to transfer all the owner's rewards to the owner. It has to
+/// be signed by the owner.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct ClaimRewards {
+    /// The owner of the rewards
+    pub owner: Address,
+}
+
+macro_rules! impl_sign {
+    ($ty:ty, $name:literal, $doc:literal) => {
+        impl $ty {
+            #[doc = $doc]
+            pub fn sign(
+                self,
+                tx_code: impl AsRef<[u8]>,
+                keypair: &Keypair,
+            ) -> SignedTxData {
+                let bytes = self.try_to_vec().expect(concat!(
+                    "Encoding unsigned ",
+                    $name,
+                    " shouldn't fail"
+                ));
+                SignedTxData::new(keypair, bytes, tx_code)
+            }
+        }
+    };
+}
+
+impl_sign!(
+    RegisterValidator,
+    "validator registration",
+    "Sign a validator registration with the validator's keypair."
+);
+impl_sign!(Bond, "bond", "Sign a bond with the source's keypair.");
+impl_sign!(
+    Unbond,
+    "unbond",
+    "Sign an unbond with the source's keypair."
+);
+impl_sign!(
+    ChangeCommission,
+    "commission change",
+    "Sign a commission change with the validator's keypair."
+);
+impl_sign!(
+    Unjail,
+    "unjail",
+    "Sign an unjail with the validator's keypair."
+);
+impl_sign!(
+    ClaimRewards,
+    "rewards claim",
+    "Sign a rewards claim with the owner's keypair."
+);
+impl_sign!(
+    Withdraw,
+    "withdrawal",
+    "Sign a withdrawal with the source's keypair."
+);
+
+const PARAMS_STORAGE_KEY: &str = "params";
+const VALIDATOR_STORAGE_KEY: &str = "validator";
+const CONSENSUS_KEY_STORAGE_KEY: &str = "consensus_key";
+const COMMISSION_RATE_STORAGE_KEY: &str = "commission_rate";
+const JAILED_UNTIL_STORAGE_KEY: &str = "jailed_until";
+const SLASHES_STORAGE_KEY: &str = "slashes";
+const MISSED_BLOCKS_STORAGE_KEY: &str = "missed_blocks";
+const EPOCH_STORAGE_KEY: &str = "epoch";
+const REWARD_POOL_STORAGE_KEY: &str = "reward_pool";
+const REWARDS_STORAGE_KEY: &str = "rewards";
+const BOND_STORAGE_KEY: &str = "bond";
+const UNBOND_STORAGE_KEY: &str = "unbond";
+const TOTAL_BONDS_STORAGE_KEY: &str = "total_bonds";
+const VALIDATOR_SET_STORAGE_KEY: &str = "validator_set";
+const PAST_VALIDATOR_SETS_STORAGE_KEY: &str = "past_validator_sets";
+
+fn pos_key(segment: &str) -> Key {
+    Key::from(pos_address().to_db_key())
+        .push(&segment.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+fn validator_key(validator: &Address, segment: &str) -> Key {
+    pos_key(VALIDATOR_STORAGE_KEY)
+        .push(&validator.to_db_key())
+        .expect("Cannot obtain a storage key")
+        .push(&segment.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+fn is_validator_key<'a>(key: &'a Key, segment: &str) -> Option<&'a Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(pos), DbKeySeg::StringSeg(prefix), DbKeySeg::AddressSeg(validator), DbKeySeg::StringSeg(key)]
+            if pos == &pos_address()
+                && prefix == VALIDATOR_STORAGE_KEY
+                && key == segment =>
+        {
+            Some(validator)
+        }
+        _ => None,
+    }
+}
+
+fn is_pos_key_with_address<'a>(
+    key: &'a Key,
+    segment: &str,
+) -> Option<&'a Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(pos), DbKeySeg::StringSeg(key), DbKeySeg::AddressSeg(owner)]
+            if pos == &pos_address() && key == segment =>
+        {
+            Some(owner)
+        }
+        _ => None,
+    }
+}
+
+/// Obtain the storage key of the PoS parameters.
+pub fn params_key() -> Key {
+    pos_key(PARAMS_STORAGE_KEY)
+}
+
+/// Obtain the storage key of a validator's consensus key. The key contains the
+/// validator's address, so the registration has to be approved by the
+/// validator's VP.
+pub fn consensus_key_key(validator: &Address) -> Key {
+    validator_key(validator, CONSENSUS_KEY_STORAGE_KEY)
+}
+
+/// Check if the given storage key is a validator's consensus key. If it is,
+/// returns the validator.
+pub fn is_consensus_key_key(key: &Key) -> Option<&Address> {
+    is_validator_key(key, CONSENSUS_KEY_STORAGE_KEY)
+}
+
+/// Obtain the storage key of a validator's [`CommissionRate`]. The key
+/// contains the validator's address, so changing it has to be approved by the
+/// validator's VP.
+pub fn commission_rate_key(validator: &Address) -> Key {
+    validator_key(validator, COMMISSION_RATE_STORAGE_KEY)
+}
+
+/// Check if the given storage key is a validator's commission rate. If it is,
+/// returns the validator.
+pub fn is_commission_rate_key(key: &Key) -> Option<&Address> {
+    is_validator_key(key, COMMISSION_RATE_STORAGE_KEY)
+}
+
+/// Obtain the storage key of the epoch from which a jailed validator can be
+/// unjailed. The key is only set while the validator is jailed.
+pub fn jailed_until_key(validator: &Address) -> Key {
+    validator_key(validator, JAILED_UNTIL_STORAGE_KEY)
+}
+
+/// Check if the given storage key is a validator's jail key. If it is, returns
+/// the validator.
+pub fn is_jailed_until_key(key: &Key) -> Option<&Address> {
+    is_validator_key(key, JAILED_UNTIL_STORAGE_KEY)
+}
+
+/// Obtain the storage key of a validator's [`Slashes`]. It is only written by
+/// the protocol.
+pub fn slashes_key(validator: &Address) -> Key {
+    validator_key(validator, SLASHES_STORAGE_KEY)
+}
+
+/// Obtain the storage key of the current epoch's [`MissedBlocks`]. It is only
+/// written by the protocol.
+pub fn missed_blocks_key() -> Key {
+    pos_key(MISSED_BLOCKS_STORAGE_KEY)
+}
+
+/// Obtain the storage key of the source's [`Bonds`]. The key contains only
+/// the source's address, so that bonding to a validator doesn't require the
+/// validator's approval.
+pub fn bond_key(source: &Address) -> Key {
+    pos_key(BOND_STORAGE_KEY)
+        .push(&source.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a source's bonds key. If it is, returns
+/// the source.
+pub fn is_bond_key(key: &Key) -> Option<&Address> {
+    is_pos_key_with_address(key, BOND_STORAGE_KEY)
+}
+
+/// Obtain the storage key prefix of all the sources' [`Bonds`].
+pub fn bonds_prefix() -> Key {
+    pos_key(BOND_STORAGE_KEY)
+}
+
+/// Obtain the storage key of the source's [`Unbonds`].
+pub fn unbond_key(source: &Address) -> Key {
+    pos_key(UNBOND_STORAGE_KEY)
+        .push(&source.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a source's unbonds key. If it is,
+/// returns the source.
+pub fn is_unbond_key(key: &Key) -> Option<&Address> {
+    is_pos_key_with_address(key, UNBOND_STORAGE_KEY)
+}
+
+/// Obtain the storage key prefix of all the sources' [`Unbonds`].
+pub fn unbonds_prefix() -> Key {
+    pos_key(UNBOND_STORAGE_KEY)
+}
+
+/// Obtain the storage key of the validators' [`TotalBonds`].
+pub fn total_bonds_key() -> Key {
+    pos_key(TOTAL_BONDS_STORAGE_KEY)
+}
+
+/// Obtain the storage key of the current epoch's [`ValidatorSet`]. It is only
+/// written by the protocol.
+pub fn validator_set_key() -> Key {
+    pos_key(VALIDATOR_SET_STORAGE_KEY)
+}
+
+/// Obtain the storage key of the [`PastValidatorSets`]. It is only written by
+/// the protocol.
+pub fn past_validator_sets_key() -> Key {
+    pos_key(PAST_VALIDATOR_SETS_STORAGE_KEY)
+}
+
+/// Obtain the storage key of the current [`EpochState`]. It is only written by
+/// the protocol.
+pub fn epoch_key() -> Key {
+    pos_key(EPOCH_STORAGE_KEY)
+}
+
+/// Obtain the storage key of the [`RewardPool`] waiting to be distributed. It
+/// is only written by the protocol.
+pub fn reward_pool_key() -> Key {
+    pos_key(REWARD_POOL_STORAGE_KEY)
+}
+
+/// Obtain the storage key of the owner's unclaimed rewards.
+pub fn rewards_key(owner: &Address) -> Key {
+    pos_key(REWARDS_STORAGE_KEY)
+        .push(&owner.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is an owner's rewards key. If it is, returns
+/// the owner.
+pub fn is_rewards_key(key: &Key) -> Option<&Address> {
+    is_pos_key_with_address(key, REWARDS_STORAGE_KEY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address::testing::{
+        established_address_1, established_address_2, established_address_3,
+    };
+    use crate::types::key::ed25519::testing::{keypair_1, keypair_2};
+
+    fn params() -> PosParams {
+        PosParams {
+            epoch_length: 10,
+            epoch_duration: 60,
+            unbonding_epochs: 2,
+            max_validators: 1,
+            block_reward: 100,
+            duplicate_vote_slash_rate: 500,
+            downtime_slash_rate: 10,
+            max_missed_blocks: 5,
+            jail_epochs: 2,
+        }
+    }
+
+    #[test]
+    fn test_pos_keys() {
+        let addr = established_address_1();
+        assert_eq!(is_bond_key(&bond_key(&addr)), Some(&addr));
+        assert_eq!(is_unbond_key(&bond_key(&addr)), None);
+        assert_eq!(is_unbond_key(&unbond_key(&addr)), Some(&addr));
+        assert_eq!(
+            is_consensus_key_key(&consensus_key_key(&addr)),
+            Some(&addr)
+        );
+        assert_eq!(is_bond_key(&consensus_key_key(&addr)), None);
+        assert_eq!(
+            is_commission_rate_key(&commission_rate_key(&addr)),
+            Some(&addr)
+        );
+        assert_eq!(is_consensus_key_key(&commission_rate_key(&addr)), None);
+        assert_eq!(is_rewards_key(&rewards_key(&addr)), Some(&addr));
+        assert_eq!(is_bond_key(&rewards_key(&addr)), None);
+        assert_eq!(is_jailed_until_key(&jailed_until_key(&addr)), Some(&addr));
+        assert_eq!(is_commission_rate_key(&jailed_until_key(&addr)), None);
+    }
+
+    #[test]
+    fn test_epochs() {
+        let params = params();
+        let state = EpochState {
+            epoch: Epoch(1),
+            start_height: BlockHeight(10),
+            start_time: 1000,
+        };
+        // both the minimum length and duration have to pass
+        assert!(!params.is_epoch_end(&state, BlockHeight(18), 1100));
+        assert!(!params.is_epoch_end(&state, BlockHeight(19), 1059));
+        assert!(params.is_epoch_end(&state, BlockHeight(19), 1060));
+        assert!(params.is_epoch_end(&state, BlockHeight(25), 1100));
+        assert_eq!(
+            params.epoch_reward(&state, BlockHeight(25)),
+            Amount::from(1600)
+        );
+        assert_eq!(params.withdrawable_epoch(Epoch(1)), Epoch(3));
+    }
+
+    #[test]
+    fn test_slashes() {
+        let mut params = params();
+        assert_eq!(params.validate(), Ok(()));
+        assert_eq!(
+            slashed_amount(
+                Amount::from(1999),
+                params.duplicate_vote_slash_rate
+            ),
+            Amount::from(99)
+        );
+        assert_eq!(
+            slashed_amount(Amount::whole(10), BASIS_POINTS),
+            Amount::whole(10)
+        );
+        params.downtime_slash_rate = BASIS_POINTS + 1;
+        assert_eq!(
+            params.validate(),
+            Err(Error::InvalidSlashRate(BASIS_POINTS + 1))
+        );
+
+        // the tokens bonded after the infraction are not slashed
+        assert_eq!(infraction_slash_rate(500, 10, 10), 500);
+        assert_eq!(infraction_slash_rate(500, 10, 5), 500);
+        assert_eq!(infraction_slash_rate(500, 10, 20), 250);
+        assert_eq!(infraction_slash_rate(500, 0, 20), 0);
+    }
+
+    #[test]
+    fn test_validator_set_at() {
+        let validator = |address: Address, keypair: Keypair| {
+            let active = ActiveValidator {
+                consensus_key: PublicKey::from(keypair.public),
+                voting_power: 1,
+            };
+            let mut set = ValidatorSet::new();
+            set.insert(address, active);
+            set
+        };
+        let first = validator(established_address_1(), keypair_1());
+        let second = validator(established_address_2(), keypair_2());
+        let current = validator(established_address_3(), keypair_1());
+        let mut past = PastValidatorSets::new();
+        // the first set was used until height 10, the second until 20
+        past.insert(
+            BlockHeight(10),
+            PastValidatorSet {
+                epoch: Epoch::default(),
+                validator_set: first.clone(),
+            },
+        );
+        past.insert(
+            BlockHeight(20),
+            PastValidatorSet {
+                epoch: Epoch::default() + 1,
+                validator_set: second.clone(),
+            },
+        );
+
+        assert_eq!(validator_set_at(&current, &past, BlockHeight(1)), &first);
+        assert_eq!(validator_set_at(&current, &past, BlockHeight(9)), &first);
+        assert_eq!(validator_set_at(&current, &past, BlockHeight(10)), &second);
+        assert_eq!(validator_set_at(&current, &past, BlockHeight(19)), &second);
+        assert_eq!(
+            validator_set_at(&current, &past, BlockHeight(20)),
+            &current
+        );
+    }
+
+    #[test]
+    fn test_validator_set_updates() {
+        let params = params();
+        let (val_1, val_2) = (established_address_1(), established_address_2());
+        let key_of = |addr: &Address| {
+            Some(PublicKey::from(if addr == &established_address_1() {
+                keypair_1().public
+            } else {
+                keypair_2().public
+            }))
+        };
+
+        let mut total_bonds = TotalBonds::new();
+        total_bonds.insert(val_1.clone(), Amount::whole(10));
+        // less than a whole token gives no voting power
+        total_bonds.insert(val_2.clone(), Amount::from(1));
+        let old = compute_validator_set(&params, &total_bonds, key_of);
+        assert_eq!(old.len(), 1);
+        assert_eq!(old[&val_1].voting_power, 10);
+
+        // the validator with more stake replaces the other one
+        total_bonds.insert(val_2.clone(), Amount::whole(20));
+        let new = compute_validator_set(&params, &total_bonds, key_of);
+        assert_eq!(new.len(), 1);
+        assert_eq!(new[&val_2].voting_power, 20);
+
+        let updates = validator_set_updates(&old, &new);
+        assert_eq!(
+            updates,
+            vec![
+                ValidatorUpdate {
+                    consensus_key: old[&val_1].consensus_key.clone(),
+                    voting_power: 0,
+                },
+                ValidatorUpdate {
+                    consensus_key: new[&val_2].consensus_key.clone(),
+                    voting_power: 20,
+                },
+            ]
+        );
+        assert!(validator_set_updates(&new, &new).is_empty());
+    }
+
+    #[test]
+    fn test_validate_bond_and_unbond() {
+        let params = params();
+        let epoch = Epoch(1);
+        let (source, validator) =
+            (established_address_1(), established_address_2());
+        let registered = |addr: &Address| addr == &established_address_2();
+        let bonds = |amount: u64| {
+            let mut bonds = Bonds::new();
+            bonds.insert(validator.clone(), Amount::whole(amount));
+            bonds
+        };
+        let total = |amount: u64| {
+            let mut total = TotalBonds::new();
+            total.insert(validator.clone(), Amount::whole(amount));
+            total
+        };
+
+        // bond 10
+        let bond = PosChanges {
+            bonds: vec![(source.clone(), Bonds::new(), bonds(10))],
+            unbonds: vec![],
+            total_bonds_pre: TotalBonds::new(),
+            total_bonds_post: total(10),
+            rewards: vec![],
+            balance_change: Amount::whole(10).change(),
+        };
+        assert_eq!(bond.validate(&params, epoch, registered), Ok(()));
+        assert_eq!(
+            bond.validate(&params, epoch, |_| false),
+            Err(ValidationError::UnregisteredValidator(validator.clone()))
+        );
+        let stolen = PosChanges {
+            balance_change: 0,
+            ..bond.clone()
+        };
+        assert!(matches!(
+            stolen.validate(&params, epoch, registered),
+            Err(ValidationError::BalanceMismatch { .. })
+        ));
+
+        // unbond 4
+        let mut unbonds = Unbonds::new();
+        unbonds
+            .entry(validator.clone())
+            .or_default()
+            .insert(params.withdrawable_epoch(epoch), Amount::whole(4));
+        let unbond = PosChanges {
+            bonds: vec![(source.clone(), bonds(10), bonds(6))],
+            unbonds: vec![(source.clone(), Unbonds::new(), unbonds.clone())],
+            total_bonds_pre: total(10),
+            total_bonds_post: total(6),
+            rewards: vec![],
+            balance_change: 0,
+        };
+        assert_eq!(unbond.validate(&params, epoch, registered), Ok(()));
+        // unbonding without locking the tokens
+        let no_unbond = PosChanges {
+            unbonds: vec![],
+            balance_change: -Amount::whole(4).change(),
+            ..unbond.clone()
+        };
+        assert!(matches!(
+            no_unbond.validate(&params, epoch, registered),
+            Err(ValidationError::UnbondMismatch { .. })
+        ));
+
+        // withdraw the unbond
+        let withdraw = PosChanges {
+            bonds: vec![],
+            unbonds: vec![(source.clone(), unbonds, Unbonds::new())],
+            total_bonds_pre: total(6),
+            total_bonds_post: total(6),
+            rewards: vec![],
+            balance_change: -Amount::whole(4).change(),
+        };
+        assert_eq!(
+            withdraw.validate(&params, Epoch(2), registered),
+            Err(ValidationError::UnbondNotWithdrawable(Epoch(3)))
+        );
+        assert_eq!(withdraw.validate(&params, Epoch(3), registered), Ok(()));
+    }
+
+    #[test]
+    fn test_validate_rewards_claim() {
+        let params = params();
+        let owner = established_address_1();
+        let claim = PosChanges {
+            rewards: vec![(owner.clone(), Amount::from(50), Amount::default())],
+            balance_change: -Amount::from(50).change(),
+            ..PosChanges::default()
+        };
+        assert_eq!(claim.validate(&params, Epoch(0), |_| true), Ok(()));
+        let overdrawn = PosChanges {
+            balance_change: -Amount::from(51).change(),
+            ..claim.clone()
+        };
+        assert!(matches!(
+            overdrawn.validate(&params, Epoch(0), |_| true),
+            Err(ValidationError::BalanceMismatch { .. })
+        ));
+        let increase = PosChanges {
+            rewards: vec![(owner.clone(), Amount::default(), Amount::from(50))],
+            balance_change: Amount::from(50).change(),
+            ..PosChanges::default()
+        };
+        assert_eq!(
+            increase.validate(&params, Epoch(0), |_| true),
+            Err(ValidationError::RewardsIncrease(owner))
+        );
+    }
+
+    #[test]
+    fn test_distribute_rewards() {
+        let (val_1, val_2) = (established_address_1(), established_address_2());
+        let delegator = established_address_3();
+        let mut validator_set = ValidatorSet::new();
+        validator_set.insert(
+            val_1.clone(),
+            ActiveValidator {
+                consensus_key: PublicKey::from(keypair_1().public),
+                voting_power: 30,
+            },
+        );
+        validator_set.insert(
+            val_2.clone(),
+            ActiveValidator {
+                consensus_key: PublicKey::from(keypair_2().public),
+                voting_power: 10,
+            },
+        );
+        let pool = RewardPool {
+            reward: Amount::from(1000),
+            validator_set,
+        };
+        let commission_rate = |addr: &Address| {
+            if addr == &established_address_1() {
+                // 10%
+                CommissionRate(1000)
+            } else {
+                CommissionRate(0)
+            }
+        };
+        let mut bonds: BTreeMap<Address, BTreeMap<Address, Amount>> =
+            BTreeMap::new();
+        let val_1_bonds = bonds.entry(val_1.clone()).or_default();
+        val_1_bonds.insert(val_1.clone(), Amount::whole(20));
+        val_1_bonds.insert(delegator.clone(), Amount::whole(10));
+        bonds
+            .entry(val_2.clone())
+            .or_default()
+            .insert(delegator.clone(), Amount::whole(10));
+
+        let rewards = distribute_rewards(&pool, commission_rate, &bonds);
+        // val_1 gets 750 of which 75 is the commission and the rest is shared
+        // 2:1 with the delegator, who also gets the whole 250 of val_2
+        assert_eq!(rewards[&val_1], Amount::from(75 + 450));
+        assert_eq!(rewards[&delegator], Amount::from(225 + 250));
+        assert!(!rewards.contains_key(&val_2));
+    }
+}
@@ -1,10 +1,15 @@
 //! A basic fungible token
 
+use std::fmt::Display;
+use std::str::FromStr;
+
 use borsh::{BorshDeserialize, BorshSerialize};
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 
+use crate::types::address::{Address, InternalAddress};
 use crate::types::key::ed25519::{Keypair, SignedTxData};
-use crate::types::{Address, DbKeySeg, Key, KeySeg};
+use crate::types::{DbKeySeg, Key, KeySeg};
 
 /// Amount in micro units. For different granularity another representation
 /// might be more appropriate.
@@ -26,9 +31,42 @@ pub struct Amount {
     micro: u64,
 }
 
+/// Decimal places of a token [`Amount`], unless the token's [`TokenMetadata`]
+/// specifies otherwise.
+pub const DEFAULT_DECIMALS: u8 = 6;
+/// Decimal scale of token [`Amount`] with the [`DEFAULT_DECIMALS`].
+pub const SCALE: u64 = 1_000_000;
+/// The maximum decimal places of a token, limited by the precision of
+/// [`Amount`].
+pub const MAX_DECIMALS: u8 = 18;
+
 /// A change in tokens amount
 pub type Change = i128;
 
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error("The amount overflowed")]
+    Overflow,
+    #[error("Insufficient amount {available}, required {required}")]
+    InsufficientAmount { available: Amount, required: Amount },
+    #[error("Invalid decimal amount string \"{0}\"")]
+    InvalidDecimal(String),
+    #[error(
+        "The amount string \"{amount}\" has more than {decimals} decimal \
+         places"
+    )]
+    TooManyDecimalPlaces { amount: String, decimals: u8 },
+    #[error(
+        "A token can have at most {} decimal places, got {0}",
+        MAX_DECIMALS
+    )]
+    InvalidDecimals(u8),
+}
+
+/// Result for functions that may fail
+pub type Result<T> = std::result::Result<T, Error>;
+
 impl Default for Amount {
     fn default() -> Self {
         Self { micro: 0 }
@@ -41,21 +79,51 @@ impl Amount {
         self.micro as Change
     }
 
-    /// Spend a given amount
-    pub fn spend(&mut self, amount: &Amount) {
-        self.micro -= amount.micro
+    /// Spend a given amount. Fails without modifying the amount when the given
+    /// amount is greater than this amount.
+    pub fn spend(&mut self, amount: &Amount) -> Result<()> {
+        *self = self.checked_sub(amount).ok_or(Error::InsufficientAmount {
+            available: *self,
+            required: *amount,
+        })?;
+        Ok(())
+    }
+
+    /// Receive a given amount. Fails without modifying the amount when the
+    /// result would overflow.
+    pub fn receive(&mut self, amount: &Amount) -> Result<()> {
+        *self = self.checked_add(amount).ok_or(Error::Overflow)?;
+        Ok(())
+    }
+
+    /// Add two amounts, returning `None` on overflow.
+    pub fn checked_add(&self, amount: &Amount) -> Option<Self> {
+        self.micro
+            .checked_add(amount.micro)
+            .map(|micro| Self { micro })
     }
 
-    /// Receive a given amount
-    pub fn receive(&mut self, amount: &Amount) {
-        self.micro += amount.micro
+    /// Subtract an amount, returning `None` when the result would be negative.
+    pub fn checked_sub(&self, amount: &Amount) -> Option<Self> {
+        self.micro
+            .checked_sub(amount.micro)
+            .map(|micro| Self { micro })
     }
 
-    /// Create a new amount from whole number of tokens
+    /// Create a new amount from whole number of tokens.
+    ///
+    /// Panics when the amount in micro units overflows `u64`. Use
+    /// [`Amount::checked_whole`] for amounts that aren't known to be small
+    /// enough.
     pub fn whole(amount: u64) -> Self {
-        Self {
-            micro: amount * 1_000_000,
-        }
+        Self::checked_whole(amount)
+            .expect("The whole amount shouldn't overflow")
+    }
+
+    /// Create a new amount from whole number of tokens, returning `None` when
+    /// the amount in micro units overflows `u64`.
+    pub fn checked_whole(amount: u64) -> Option<Self> {
+        amount.checked_mul(SCALE).map(|micro| Self { micro })
     }
 }
 
@@ -65,14 +133,96 @@ impl From<u64> for Amount {
     }
 }
 
-impl From<f64> for Amount {
-    fn from(decimal: f64) -> Self {
-        Self {
-            micro: (decimal * 1_000_000.0).round() as u64,
+impl From<Amount> for u64 {
+    fn from(amount: Amount) -> Self {
+        amount.micro
+    }
+}
+
+impl Amount {
+    /// Parse a decimal string with at most the given number of decimal
+    /// places, e.g. `"12.345"`, without any loss of precision.
+    pub fn from_str_with_decimals(s: &str, decimals: u8) -> Result<Self> {
+        let scale = decimal_scale(decimals)?;
+        let (whole, fraction) = match s.find('.') {
+            Some(dot) => (&s[..dot], Some(&s[dot + 1..])),
+            None => (s, None),
+        };
+        let is_digits = |digits: &str| {
+            !digits.is_empty() && digits.chars().all(|c| c.is_ascii_digit())
+        };
+        if !is_digits(whole) || !fraction.map(is_digits).unwrap_or(true) {
+            return Err(Error::InvalidDecimal(s.to_owned()));
+        }
+        let whole: u64 = whole.parse().map_err(|_| Error::Overflow)?;
+        let fraction = match fraction {
+            Some(fraction) => {
+                if fraction.len() > decimals as usize {
+                    return Err(Error::TooManyDecimalPlaces {
+                        amount: s.to_owned(),
+                        decimals,
+                    });
+                }
+                // right-pad the decimal places with zeros
+                let padding = decimals as u32 - fraction.len() as u32;
+                // this cannot overflow, because the number of digits is
+                // limited by `MAX_DECIMALS`
+                fraction.parse::<u64>().unwrap() * 10_u64.pow(padding)
+            }
+            None => 0,
+        };
+        whole
+            .checked_mul(scale)
+            .and_then(|micro| micro.checked_add(fraction))
+            .map(|micro| Self { micro })
+            .ok_or(Error::Overflow)
+    }
+
+    /// Format the amount as a decimal string with the given number of decimal
+    /// places, with their trailing zeros removed, e.g. `"12.345"`.
+    pub fn to_string_with_decimals(&self, decimals: u8) -> Result<String> {
+        let scale = decimal_scale(decimals)?;
+        let whole = self.micro / scale;
+        let fraction = self.micro % scale;
+        if fraction == 0 {
+            Ok(whole.to_string())
+        } else {
+            let fraction =
+                format!("{:0width$}", fraction, width = decimals as usize);
+            Ok(format!("{}.{}", whole, fraction.trim_end_matches('0')))
         }
     }
 }
 
+/// Get the scale of an [`Amount`] with the given number of decimal places.
+fn decimal_scale(decimals: u8) -> Result<u64> {
+    if decimals > MAX_DECIMALS {
+        return Err(Error::InvalidDecimals(decimals));
+    }
+    Ok(10_u64.pow(decimals as u32))
+}
+
+impl FromStr for Amount {
+    type Err = Error;
+
+    /// Parse a decimal string with at most [`DEFAULT_DECIMALS`] decimal
+    /// places.
+    fn from_str(s: &str) -> Result<Self> {
+        Self::from_str_with_decimals(s, DEFAULT_DECIMALS)
+    }
+}
+
+impl Display for Amount {
+    /// Format the amount as a decimal string with [`DEFAULT_DECIMALS`]
+    /// decimal places.
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let amount = self
+            .to_string_with_decimals(DEFAULT_DECIMALS)
+            .expect("The default decimals are valid");
+        write!(f, "{}", amount)
+    }
+}
+
 const BALANCE_STORAGE_KEY: &str = "balance";
 
 /// Obtain a storage key for user's balance.
@@ -113,6 +263,124 @@ pub fn is_any_token_balance_key(key: &Key) -> Option<&Address> {
     }
 }
 
+const METADATA_STORAGE_KEY: &str = "metadata";
+const SUPPLY_STORAGE_KEY: &str = "supply";
+const MINT_AUTHORITY_STORAGE_KEY: &str = "mint_authority";
+
+/// Obtain a storage key for the metadata of a token.
+pub fn metadata_key(token_addr: &Address) -> Key {
+    Key::from(token_addr.to_db_key())
+        .push(&METADATA_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is the metadata key of the given token.
+pub fn is_metadata_key(token_addr: &Address, key: &Key) -> bool {
+    matches!(&key.segments[..],
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(key)]
+            if key == METADATA_STORAGE_KEY && addr == token_addr)
+}
+
+/// Obtain a storage key for the total supply of a token.
+pub fn supply_key(token_addr: &Address) -> Key {
+    Key::from(token_addr.to_db_key())
+        .push(&SUPPLY_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is the total supply key of the given token.
+pub fn is_supply_key(token_addr: &Address, key: &Key) -> bool {
+    matches!(&key.segments[..],
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(key)]
+            if key == SUPPLY_STORAGE_KEY && addr == token_addr)
+}
+
+/// Obtain a storage key for the address of a token's mint authority, i.e. the
+/// account that can authorize an increase of the token's supply.
+pub fn mint_authority_key(token_addr: &Address) -> Key {
+    Key::from(token_addr.to_db_key())
+        .push(&MINT_AUTHORITY_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is the mint authority key of the given
+/// token.
+pub fn is_mint_authority_key(token_addr: &Address, key: &Key) -> bool {
+    matches!(&key.segments[..],
+        [DbKeySeg::AddressSeg(addr), DbKeySeg::StringSeg(key)]
+            if key == MINT_AUTHORITY_STORAGE_KEY && addr == token_addr)
+}
+
+/// The internal address of the registry of the tokens. The genesis tokens
+/// and the tokens initialized by transactions are registered with their
+/// metadata.
+pub fn token_registry_address() -> Address {
+    Address::Internal(InternalAddress::TokenRegistry)
+}
+
+const REGISTRY_STORAGE_KEY: &str = "token";
+
+/// Obtain the storage key of a token's entry in the token registry. The entry
+/// holds the token's [`TokenMetadata`].
+pub fn registry_key(token_addr: &Address) -> Key {
+    Key::from(token_registry_address().to_db_key())
+        .push(&REGISTRY_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+        .push(&token_addr.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is an entry of the token registry. If it is,
+/// returns the token.
+pub fn is_registry_key(key: &Key) -> Option<&Address> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(registry), DbKeySeg::StringSeg(key), DbKeySeg::AddressSeg(token)]
+            if registry == &token_registry_address()
+                && key == REGISTRY_STORAGE_KEY =>
+        {
+            Some(token)
+        }
+        _ => None,
+    }
+}
+
+/// The metadata of a token, set when the token is created.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct TokenMetadata {
+    /// The token's ticker symbol, e.g. `"XAN"`
+    pub symbol: String,
+    /// The token's full name
+    pub name: String,
+    /// The number of decimal places used to display the token's amounts
+    pub decimals: u8,
+}
+
+impl TokenMetadata {
+    /// Create new token metadata, checking that the decimal places are within
+    /// the precision of [`Amount`].
+    pub fn new(
+        symbol: impl Into<String>,
+        name: impl Into<String>,
+        decimals: u8,
+    ) -> Result<Self> {
+        decimal_scale(decimals)?;
+        Ok(Self {
+            symbol: symbol.into(),
+            name: name.into(),
+            decimals,
+        })
+    }
+}
+
 /// A simple bilateral token transfer
 #[derive(
     Debug,
@@ -150,3 +418,293 @@ impl Transfer {
         SignedTxData::new(keypair, bytes, tx_code)
     }
 }
+
+/// A list of transfers, possibly of different tokens between different
+/// parties, that are applied atomically. If any of them fails, none of them is
+/// applied. It has to be signed by all the sources, e.g. with
+/// [`crate::types::key::multisig::MultiSignedTxData`].
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct BatchTransfer {
+    /// The transfers to apply in order
+    pub transfers: Vec<Transfer>,
+}
+
+impl BatchTransfer {
+    /// Get the unique sources of the transfers, in the order of their first
+    /// appearance.
+    pub fn sources(&self) -> Vec<&Address> {
+        let mut sources: Vec<&Address> = vec![];
+        for transfer in &self.transfers {
+            if !sources.contains(&&transfer.source) {
+                sources.push(&transfer.source);
+            }
+        }
+        sources
+    }
+}
+
+/// Issue new tokens to the target, increasing the token's supply. It has to
+/// be signed by the token's mint authority.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Mint {
+    /// Token's address
+    pub token: Address,
+    /// Target address will receive the new tokens
+    pub target: Address,
+    /// The amount of tokens
+    pub amount: Amount,
+}
+
+impl Mint {
+    /// Sign a mint with the mint authority's keypair.
+    pub fn sign(
+        self,
+        tx_code: impl AsRef<[u8]>,
+        keypair: &Keypair,
+    ) -> SignedTxData {
+        let bytes = self
+            .try_to_vec()
+            .expect("Encoding unsigned mint shouldn't fail");
+        SignedTxData::new(keypair, bytes, tx_code)
+    }
+}
+
+/// Destroy the source's tokens, decreasing the token's supply. It has to be
+/// signed by the source.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct Burn {
+    /// Token's address
+    pub token: Address,
+    /// Source address will lose the tokens
+    pub source: Address,
+    /// The amount of tokens
+    pub amount: Amount,
+}
+
+impl Burn {
+    /// Sign a burn with the source's keypair.
+    pub fn sign(
+        self,
+        tx_code: impl AsRef<[u8]>,
+        keypair: &Keypair,
+    ) -> SignedTxData {
+        let bytes = self
+            .try_to_vec()
+            .expect("Encoding unsigned burn shouldn't fail");
+        SignedTxData::new(keypair, bytes, tx_code)
+    }
+}
+
+/// A tx data type to initialize a new token account with zero supply, the
+/// given mint authority and metadata
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct InitToken {
+    /// The account that can mint the token
+    pub mint_authority: Address,
+    /// The token's metadata
+    pub metadata: TokenMetadata,
+    /// The token's VP code
+    pub vp_code: Vec<u8>,
+}
+
+#[cfg(test)]
+mod tests {
+    use proptest::prelude::*;
+
+    use super::*;
+    use crate::types::address;
+
+    proptest! {
+        /// The formatted amount can be parsed back without any loss.
+        #[test]
+        fn test_amount_string_roundtrip(micro in 0..u64::MAX) {
+            let amount = Amount::from(micro);
+            let parsed: Amount = amount.to_string().parse().unwrap();
+            assert_eq!(amount, parsed);
+        }
+    }
+
+    #[test]
+    fn test_amount_from_str() {
+        assert_eq!("0".parse(), Ok(Amount::from(0)));
+        assert_eq!("12".parse(), Ok(Amount::whole(12)));
+        assert_eq!("0.000001".parse(), Ok(Amount::from(1)));
+        assert_eq!("1.5".parse(), Ok(Amount::from(1_500_000)));
+        assert_eq!("18446744073709.551615".parse(), Ok(Amount::from(u64::MAX)));
+
+        for invalid in &["", ".", "1.", ".5", "-1", "1,5", " 1", "1e6"] {
+            assert_eq!(
+                invalid.parse::<Amount>(),
+                Err(Error::InvalidDecimal(invalid.to_string()))
+            );
+        }
+        assert_eq!(
+            "0.0000001".parse::<Amount>(),
+            Err(Error::TooManyDecimalPlaces {
+                amount: "0.0000001".to_owned(),
+                decimals: DEFAULT_DECIMALS,
+            })
+        );
+        assert_eq!(
+            "18446744073709.551616".parse::<Amount>(),
+            Err(Error::Overflow)
+        );
+        assert_eq!(
+            "99999999999999999999".parse::<Amount>(),
+            Err(Error::Overflow)
+        );
+    }
+
+    #[test]
+    fn test_amount_display() {
+        assert_eq!(Amount::from(0).to_string(), "0");
+        assert_eq!(Amount::whole(12).to_string(), "12");
+        assert_eq!(Amount::from(1).to_string(), "0.000001");
+        assert_eq!(Amount::from(1_500_000).to_string(), "1.5");
+    }
+
+    #[test]
+    fn test_amount_with_decimals() {
+        assert_eq!(
+            Amount::from_str_with_decimals("1.23", 2),
+            Ok(Amount::from(123))
+        );
+        assert_eq!(
+            Amount::from(123).to_string_with_decimals(2).unwrap(),
+            "1.23"
+        );
+        assert_eq!(
+            Amount::from_str_with_decimals("12", 0),
+            Ok(Amount::from(12))
+        );
+        assert_eq!(Amount::from(12).to_string_with_decimals(0).unwrap(), "12");
+        assert_eq!(
+            Amount::from_str_with_decimals("1.5", 0),
+            Err(Error::TooManyDecimalPlaces {
+                amount: "1.5".to_owned(),
+                decimals: 0,
+            })
+        );
+        assert_eq!(
+            Amount::from_str_with_decimals("0.000000000000000001", 18),
+            Ok(Amount::from(1))
+        );
+        assert_eq!(
+            Amount::from_str_with_decimals("1", 19),
+            Err(Error::InvalidDecimals(19))
+        );
+        assert!(TokenMetadata::new("XAN", "Anoma", 19).is_err());
+    }
+
+    #[test]
+    fn test_checked_arithmetic() {
+        let mut amount = Amount::from(10);
+        assert_eq!(
+            amount.spend(&Amount::from(11)),
+            Err(Error::InsufficientAmount {
+                available: Amount::from(10),
+                required: Amount::from(11),
+            })
+        );
+        // the amount is unchanged on failure
+        assert_eq!(amount, Amount::from(10));
+        assert_eq!(amount.spend(&Amount::from(10)), Ok(()));
+        assert_eq!(amount, Amount::default());
+
+        let mut amount = Amount::from(u64::MAX);
+        assert_eq!(amount.receive(&Amount::from(1)), Err(Error::Overflow));
+        assert_eq!(amount, Amount::from(u64::MAX));
+
+        assert_eq!(Amount::checked_whole(12), Some(Amount::from(12_000_000)));
+        assert_eq!(Amount::checked_whole(u64::MAX / SCALE + 1), None);
+    }
+
+    #[test]
+    fn test_batch_transfer_sources() {
+        let alice = address::testing::established_address_1();
+        let bob = address::testing::established_address_2();
+        let transfer =
+            |source: &Address, target: &Address, token: Address| Transfer {
+                source: source.clone(),
+                target: target.clone(),
+                token,
+                amount: Amount::whole(1),
+            };
+        let batch = BatchTransfer {
+            transfers: vec![
+                transfer(&bob, &alice, address::xan()),
+                transfer(&alice, &bob, address::btc()),
+                transfer(&bob, &alice, address::eth()),
+            ],
+        };
+        assert_eq!(batch.sources(), vec![&bob, &alice]);
+    }
+
+    #[test]
+    fn test_supply_keys() {
+        let token = address::xan();
+        let other_token = address::btc();
+        let owner = address::testing::established_address_1();
+
+        let key = supply_key(&token);
+        assert!(is_supply_key(&token, &key));
+        assert!(!is_supply_key(&other_token, &key));
+        assert!(!is_mint_authority_key(&token, &key));
+        assert_eq!(is_balance_key(&token, &key), None);
+
+        let key = metadata_key(&token);
+        assert!(is_metadata_key(&token, &key));
+        assert!(!is_metadata_key(&other_token, &key));
+        assert!(!is_supply_key(&token, &key));
+
+        let key = mint_authority_key(&token);
+        assert!(is_mint_authority_key(&token, &key));
+        assert!(!is_supply_key(&token, &key));
+
+        let key = balance_key(&token, &owner);
+        assert!(!is_supply_key(&token, &key));
+        assert!(!is_mint_authority_key(&token, &key));
+    }
+
+    #[test]
+    fn test_registry_keys() {
+        let token = address::xan();
+
+        let key = registry_key(&token);
+        assert_eq!(is_registry_key(&key), Some(&token));
+        assert_eq!(is_any_token_balance_key(&key), None);
+        assert_eq!(is_registry_key(&metadata_key(&token)), None);
+    }
+}
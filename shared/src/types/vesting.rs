@@ -0,0 +1,189 @@
+//! Vesting schedules for time-locked token balances.
+
+use borsh::{BorshDeserialize, BorshSerialize};
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+use crate::types::token::Amount;
+use crate::types::{Address, BlockHeight, DbKeySeg, Key, KeySeg};
+
+#[allow(missing_docs)]
+#[derive(Error, Debug, Clone, PartialEq, Eq)]
+pub enum Error {
+    #[error(
+        "The vesting schedule heights must satisfy start <= cliff <= end, \
+         got start {start}, cliff {cliff}, end {end}"
+    )]
+    InvalidHeights { start: u64, cliff: u64, end: u64 },
+}
+
+/// Result for functions that may fail
+pub type Result<T> = std::result::Result<T, Error>;
+
+/// A vesting schedule of a token balance. Until the `cliff` height, the whole
+/// `amount` is locked. After the cliff, the locked amount decreases linearly
+/// from the `start` until the `end` height, at which point it's fully
+/// unlocked.
+///
+/// The schedule is checked against the block height, because the block time
+/// is not available to validity predicates.
+#[derive(
+    Debug,
+    Clone,
+    PartialEq,
+    Eq,
+    BorshSerialize,
+    BorshDeserialize,
+    Serialize,
+    Deserialize,
+)]
+pub struct VestingSchedule {
+    /// The height from which the amount starts vesting
+    pub start: BlockHeight,
+    /// The height before which nothing can be unlocked
+    pub cliff: BlockHeight,
+    /// The height at which the whole amount is unlocked
+    pub end: BlockHeight,
+    /// The total vested amount
+    pub amount: Amount,
+}
+
+impl VestingSchedule {
+    /// Create a new vesting schedule, checking that the heights are ordered.
+    pub fn new(
+        start: BlockHeight,
+        cliff: BlockHeight,
+        end: BlockHeight,
+        amount: Amount,
+    ) -> Result<Self> {
+        if start > cliff || cliff > end {
+            return Err(Error::InvalidHeights {
+                start: start.0,
+                cliff: cliff.0,
+                end: end.0,
+            });
+        }
+        Ok(Self {
+            start,
+            cliff,
+            end,
+            amount,
+        })
+    }
+
+    /// Get the amount that is unlocked at the given height.
+    pub fn unlocked_at(&self, height: BlockHeight) -> Amount {
+        if height < self.cliff || height < self.start {
+            Amount::default()
+        } else if height >= self.end {
+            self.amount
+        } else {
+            let total = self.amount.change() as u128;
+            let elapsed = (height.0 - self.start.0) as u128;
+            let duration = (self.end.0 - self.start.0) as u128;
+            // `elapsed < duration`, so the result is always less than `total`
+            Amount::from((total * elapsed / duration) as u64)
+        }
+    }
+
+    /// Get the amount that is still locked at the given height.
+    pub fn locked_at(&self, height: BlockHeight) -> Amount {
+        // the unlocked amount is never greater than the total amount
+        self.amount
+            .checked_sub(&self.unlocked_at(height))
+            .unwrap_or_default()
+    }
+
+    /// Check if the whole amount is unlocked at the given height.
+    pub fn is_fully_vested(&self, height: BlockHeight) -> bool {
+        height >= self.end
+    }
+}
+
+const VESTING_STORAGE_KEY: &str = "vesting";
+
+/// Obtain a storage key prefix for all the owner's vesting schedules.
+pub fn vesting_prefix(owner: &Address) -> Key {
+    Key::from(owner.to_db_key())
+        .push(&VESTING_STORAGE_KEY.to_owned())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Obtain a storage key for owner's vesting schedule of the given token.
+pub fn vesting_key(owner: &Address, token: &Address) -> Key {
+    vesting_prefix(owner)
+        .push(&token.to_db_key())
+        .expect("Cannot obtain a storage key")
+}
+
+/// Check if the given storage key is a vesting schedule key. If it is, returns
+/// the owner and the token.
+pub fn is_vesting_key(key: &Key) -> Option<(&Address, &Address)> {
+    match &key.segments[..] {
+        [DbKeySeg::AddressSeg(owner), DbKeySeg::StringSeg(key), DbKeySeg::AddressSeg(token)]
+            if key == VESTING_STORAGE_KEY =>
+        {
+            Some((owner, token))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::address;
+
+    #[test]
+    fn test_invalid_schedule() {
+        let result = VestingSchedule::new(
+            BlockHeight(10),
+            BlockHeight(5),
+            BlockHeight(20),
+            Amount::whole(100),
+        );
+        assert!(result.is_err());
+        let result = VestingSchedule::new(
+            BlockHeight(10),
+            BlockHeight(30),
+            BlockHeight(20),
+            Amount::whole(100),
+        );
+        assert!(result.is_err());
+    }
+
+    #[test]
+    fn test_cliff_and_linear_unlock() {
+        let schedule = VestingSchedule::new(
+            BlockHeight(100),
+            BlockHeight(150),
+            BlockHeight(200),
+            Amount::from(1_000),
+        )
+        .expect("the schedule should be valid");
+
+        // everything is locked before the cliff
+        assert_eq!(schedule.locked_at(BlockHeight(0)), Amount::from(1_000));
+        assert_eq!(schedule.locked_at(BlockHeight(149)), Amount::from(1_000));
+        // after the cliff, the amount vested since the start is unlocked
+        assert_eq!(schedule.unlocked_at(BlockHeight(150)), Amount::from(500));
+        assert_eq!(schedule.unlocked_at(BlockHeight(175)), Amount::from(750));
+        assert_eq!(schedule.locked_at(BlockHeight(199)), Amount::from(10));
+        // everything is unlocked at the end
+        assert_eq!(schedule.locked_at(BlockHeight(200)), Amount::default());
+        assert_eq!(schedule.unlocked_at(BlockHeight(999)), Amount::from(1_000));
+        assert!(!schedule.is_fully_vested(BlockHeight(199)));
+        assert!(schedule.is_fully_vested(BlockHeight(200)));
+    }
+
+    #[test]
+    fn test_vesting_key() {
+        let owner = address::testing::established_address_1();
+        let token = address::xan();
+        let key = vesting_key(&owner, &token);
+        assert_eq!(is_vesting_key(&key), Some((&owner, &token)));
+
+        let other_key = crate::types::token::balance_key(&token, &owner);
+        assert_eq!(is_vesting_key(&other_key), None);
+    }
+}
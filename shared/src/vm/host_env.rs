@@ -7,7 +7,8 @@ use std::sync::{Arc, Mutex};
 use borsh::{BorshDeserialize, BorshSerialize};
 
 use crate::gossip::mm::MmHost;
-use crate::ledger::gas::{BlockGasMeter, VpGasMeter};
+use crate::ledger::gas::{BlockGasMeter, VpGasMeter, MIN_STORAGE_GAS};
+use crate::ledger::parameters::{self, Parameters};
 use crate::ledger::storage::write_log::{self, WriteLog};
 use crate::ledger::storage::{self, Storage, StorageHasher};
 use crate::types::internal::HostEnvResult;
@@ -16,7 +17,9 @@ use crate::types::{Address, Key};
 use crate::vm::memory::VmMemory;
 use crate::vm::prefix_iter::{PrefixIteratorId, PrefixIterators};
 use crate::vm::types::KeyVal;
-use crate::vm::{EnvHostSliceWrapper, EnvHostWrapper, MutEnvHostWrapper};
+use crate::vm::{
+    EnvHostSliceWrapper, EnvHostWrapper, MutEnvHostWrapper, WasmKind,
+};
 
 const VERIFY_TX_SIG_GAS_COST: u64 = 1000;
 const WASM_VALIDATION_GAS_PER_BYTE: u64 = 1;
@@ -32,6 +35,8 @@ where
     pub memory: MEM,
     /// Read-only access to the storage
     pub storage: EnvHostWrapper<'a, &'a Storage<DB, H>>,
+    /// Read-only access to the protocol parameters of the current block
+    pub params: EnvHostWrapper<'a, &'a Parameters>,
     /// Read/write access to the write log.
     /// Not thread-safe, assuming single-threaded Tx runner
     pub write_log: MutEnvHostWrapper<'a, &'a WriteLog>,
@@ -56,6 +61,7 @@ where
         Self {
             memory: self.memory.clone(),
             storage: self.storage.clone(),
+            params: self.params.clone(),
             write_log: self.write_log.clone(),
             iterators: self.iterators.clone(),
             gas_meter: self.gas_meter.clone(),
@@ -445,7 +451,9 @@ where
 }
 
 /// Storage write function exposed to the wasm VM Tx environment. The given
-/// key/value will be written to the write log.
+/// key/value will be written to the write log. A validity predicate written
+/// directly to its key is checked the same as in
+/// [`tx_update_validity_predicate`] and [`tx_init_account`].
 pub fn tx_write<MEM, DB, H>(
     env: &TxEnv<MEM, DB, H>,
     key_ptr: u64,
@@ -470,6 +478,10 @@ pub fn tx_write<MEM, DB, H>(
     let write_log = unsafe { env.write_log.get() };
     let storage = unsafe { env.storage.get() };
     for addr in key.find_addresses() {
+        // the internal addresses have native VPs and always exist
+        if let Address::Internal(_) = addr {
+            continue;
+        }
         let vp_key = Key::validity_predicate(&addr)
             .expect("Unable to create a validity predicate key");
         let (vp, gas) = write_log.read(&vp_key);
@@ -491,9 +503,14 @@ pub fn tx_write<MEM, DB, H>(
         }
     }
 
+    if key.is_validity_predicate().is_some() {
+        check_vp_code(env, &value, "write a validity predicate key");
+    }
+
     let (gas, _size_diff) = write_log.write(&key, value);
     tx_add_gas(env, gas);
-    // TODO: charge the size diff
+    // the storage growth is charged when the transaction is applied, see
+    // `ledger::storage_usage::apply_tx`
 }
 
 /// Storage delete function exposed to the wasm VM Tx environment. The given
@@ -517,7 +534,57 @@ pub fn tx_delete<MEM, DB, H>(
     let write_log = unsafe { env.write_log.get() };
     let (gas, _size_diff) = write_log.delete(&key);
     tx_add_gas(env, gas);
-    // TODO: charge the size diff
+    // the storage growth is charged when the transaction is applied, see
+    // `ledger::storage_usage::apply_tx`
+}
+
+/// Open a savepoint of the transaction's modifications function exposed to the
+/// wasm VM Tx environment. The savepoints can be nested.
+pub fn tx_open_savepoint<MEM, DB, H>(env: &TxEnv<MEM, DB, H>)
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    tracing::debug!("tx_open_savepoint");
+
+    let write_log = unsafe { env.write_log.get() };
+    let gas = write_log.open_savepoint();
+    tx_add_gas(env, gas);
+}
+
+/// Roll back the transaction's modifications to the last open savepoint
+/// function exposed to the wasm VM Tx environment. The savepoint is closed.
+///
+/// Returns `-1` when there is no open savepoint.
+pub fn tx_rollback_savepoint<MEM, DB, H>(env: &TxEnv<MEM, DB, H>) -> i64
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    tracing::debug!("tx_rollback_savepoint");
+
+    tx_add_gas(env, MIN_STORAGE_GAS);
+    let write_log = unsafe { env.write_log.get() };
+    HostEnvResult::from(write_log.rollback_savepoint()).to_i64()
+}
+
+/// Close the last open savepoint, keeping the transaction's modifications,
+/// function exposed to the wasm VM Tx environment.
+///
+/// Returns `-1` when there is no open savepoint.
+pub fn tx_release_savepoint<MEM, DB, H>(env: &TxEnv<MEM, DB, H>) -> i64
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    tracing::debug!("tx_release_savepoint");
+
+    tx_add_gas(env, MIN_STORAGE_GAS);
+    let write_log = unsafe { env.write_log.get() };
+    HostEnvResult::from(write_log.release_savepoint()).to_i64()
 }
 
 /// Storage read prior state (before tx execution) function exposed to the wasm
@@ -886,21 +953,13 @@ pub fn tx_update_validity_predicate<MEM, DB, H>(
         Key::validity_predicate(&addr).expect("Cannot make the key for the VP");
     let (code, gas) = env.memory.read_bytes(code_ptr, code_len as _);
     tx_add_gas(env, gas);
-
-    tx_add_gas(env, code.len() as u64 * WASM_VALIDATION_GAS_PER_BYTE);
-    if let Err(err) = super::validate_untrusted_wasm(&code) {
-        tracing::info!(
-            "Trying to update an account with an invalid validity predicate \
-             code, error: {:#?}",
-            err
-        );
-        unreachable!()
-    }
+    check_vp_code(env, &code, "update an account");
 
     let write_log = unsafe { env.write_log.get() };
     let (gas, _size_diff) = write_log.write(&key, code);
     tx_add_gas(env, gas);
-    // TODO: charge the size diff
+    // the storage growth is charged when the transaction is applied, see
+    // `ledger::storage_usage::apply_tx`
 }
 
 /// Initialize a new account established address.
@@ -917,16 +976,7 @@ where
 {
     let (code, gas) = env.memory.read_bytes(code_ptr, code_len as _);
     tx_add_gas(env, gas);
-
-    tx_add_gas(env, code.len() as u64 * WASM_VALIDATION_GAS_PER_BYTE);
-    if let Err(err) = super::validate_untrusted_wasm(&code) {
-        tracing::info!(
-            "Trying to initialize an account with an invalid validity \
-             predicate code, error: {:#?}",
-            err
-        );
-        unreachable!()
-    }
+    check_vp_code(env, &code, "initialize an account");
 
     tracing::debug!("tx_init_account");
 
@@ -942,6 +992,37 @@ where
     result_len
 }
 
+/// Check that a validity predicate code written by a transaction is a valid
+/// untrusted wasm module and that it's allowed by the whitelist, otherwise the
+/// transaction is aborted. The `action` describes the write for the logs.
+fn check_vp_code<MEM, DB, H>(env: &TxEnv<MEM, DB, H>, code: &[u8], action: &str)
+where
+    MEM: VmMemory,
+    DB: storage::DB + for<'iter> storage::DBIter<'iter>,
+    H: StorageHasher,
+{
+    tx_add_gas(env, code.len() as u64 * WASM_VALIDATION_GAS_PER_BYTE);
+    if let Err(err) = super::validate_untrusted_wasm(code, WasmKind::Vp) {
+        tracing::info!(
+            "Trying to {} with an invalid validity predicate code, error: \
+             {:#?}",
+            action,
+            err
+        );
+        unreachable!()
+    }
+    let params = unsafe { env.params.get() };
+    if !params.is_vp_allowed(code) {
+        tracing::info!(
+            "Trying to {} with a validity predicate code that is not \
+             whitelisted, hash: {}",
+            action,
+            parameters::code_hash(code)
+        );
+        unreachable!()
+    }
+}
+
 /// Getting the chain ID function exposed to the wasm VM Tx environment.
 pub fn tx_get_chain_id<MEM, DB, H>(env: &TxEnv<MEM, DB, H>, result_ptr: u64)
 where
@@ -1227,6 +1308,7 @@ pub mod testing {
     /// Setup a transaction environment
     pub fn tx_env<DB, H>(
         storage: &Storage<DB, H>,
+        params: &Parameters,
         write_log: &mut WriteLog,
         iterators: &mut PrefixIterators<'static, DB>,
         verifiers: &mut HashSet<Address>,
@@ -1237,6 +1319,7 @@ pub mod testing {
         H: StorageHasher,
     {
         let storage = unsafe { EnvHostWrapper::new(storage) };
+        let params = unsafe { EnvHostWrapper::new(params) };
         let write_log = unsafe { MutEnvHostWrapper::new(write_log) };
         let iterators = unsafe { MutEnvHostWrapper::new(iterators) };
         let verifiers = unsafe { MutEnvHostWrapper::new(verifiers) };
@@ -1244,6 +1327,7 @@ pub mod testing {
         TxEnv {
             memory: NativeMemory,
             storage,
+            params,
             write_log,
             iterators,
             verifiers,
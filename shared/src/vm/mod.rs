@@ -5,7 +5,11 @@ use std::ffi::c_void;
 use std::marker::PhantomData;
 use std::slice;
 
-use wasmparser::{Validator, WasmFeatures};
+use thiserror::Error;
+use wasmparser::{
+    ExternalKind, FuncType, ImportSectionEntryType, Parser, Payload, Type,
+    TypeDef, Validator, WasmFeatures,
+};
 
 pub mod host_env;
 pub mod memory;
@@ -172,11 +176,157 @@ impl<'a, T: 'a> MutEnvHostSliceWrapper<'a, &[T]> {
     }
 }
 
+/// The entrypoint of a transaction wasm module
+pub const TX_ENTRYPOINT: &str = "_apply_tx";
+/// The entrypoint of a validity predicate wasm module
+pub const VP_ENTRYPOINT: &str = "_validate_tx";
+/// The entrypoint of a matchmaker wasm module
+pub const MATCHMAKER_ENTRYPOINT: &str = "_match_intent";
+/// The entrypoint of a matchmaker's filter wasm module
+pub const FILTER_ENTRYPOINT: &str = "_validate_intent";
+/// The maximum number of tables an untrusted wasm module may define
+pub const WASM_MAX_TABLES: u32 = 1;
+/// The maximum number of globals an untrusted wasm module may define
+pub const WASM_MAX_GLOBALS: u32 = 128;
+
+/// The host functions that can be imported by transactions. This must match
+/// the imports prepared in `wasm::host_env::prepare_tx_imports`, which is
+/// checked by a test in `wasm::runner`.
+const TX_HOST_FUNCTIONS: &[&str] = &[
+    "anoma_tx_read",
+    "anoma_tx_has_key",
+    "anoma_tx_write",
+    "anoma_tx_delete",
+    "anoma_tx_open_savepoint",
+    "anoma_tx_rollback_savepoint",
+    "anoma_tx_release_savepoint",
+    "anoma_tx_iter_prefix",
+    "anoma_tx_iter_next",
+    "anoma_tx_insert_verifier",
+    "anoma_tx_update_validity_predicate",
+    "anoma_tx_init_account",
+    "anoma_tx_get_chain_id",
+    "anoma_tx_get_block_height",
+    "anoma_tx_get_block_hash",
+    "anoma_tx_log_string",
+];
+
+/// The host functions that can be imported by validity predicates. This must
+/// match the imports prepared in `wasm::host_env::prepare_vp_imports`, which
+/// is checked by a test in `wasm::runner`.
+const VP_HOST_FUNCTIONS: &[&str] = &[
+    "anoma_vp_read_pre",
+    "anoma_vp_read_post",
+    "anoma_vp_has_key_pre",
+    "anoma_vp_has_key_post",
+    "anoma_vp_iter_prefix",
+    "anoma_vp_iter_pre_next",
+    "anoma_vp_iter_post_next",
+    "anoma_vp_get_chain_id",
+    "anoma_vp_get_block_height",
+    "anoma_vp_get_block_hash",
+    "anoma_vp_verify_tx_signature",
+    "anoma_vp_eval",
+    "anoma_vp_log_string",
+];
+
+/// The host functions that can be imported by matchmakers. This must match
+/// the imports prepared in `wasm::host_env::prepare_mm_imports`.
+const MATCHMAKER_HOST_FUNCTIONS: &[&str] = &[
+    "anoma_mm_send_match",
+    "anoma_mm_update_data",
+    "anoma_mm_remove_intents",
+    "anoma_mm_log_string",
+];
+
+/// The host functions that can be imported by matchmaker's filters. This must
+/// match the imports prepared in `wasm::host_env::prepare_mm_filter_imports`.
+const FILTER_HOST_FUNCTIONS: &[&str] = &["anoma_filter_log_string"];
+
+/// The namespace of the imports exposed to the wasm modules
+const IMPORTS_NAMESPACE: &str = "env";
+/// The name of the memory exported from the wasm modules
+const MEMORY_EXPORT: &str = "memory";
+
+#[allow(missing_docs)]
+#[derive(Error, Debug)]
+pub enum WasmValidationError {
+    #[error("Wasm validation error: {0}")]
+    InvalidWasm(wasmparser::BinaryReaderError),
+    #[error("Wasm module imports {module}.{field}, which is not available")]
+    ForbiddenImport { module: String, field: String },
+    #[error("Missing wasm memory export")]
+    MissingMemoryExport,
+    #[error("Missing wasm entrypoint {0}")]
+    MissingEntrypoint(&'static str),
+    #[error("Unexpected wasm entrypoint signature {0}")]
+    UnexpectedEntrypointSignature(&'static str),
+    #[error("Wasm module defines {0} tables, the limit is {1}")]
+    TooManyTables(u32, u32),
+    #[error("Wasm module defines {0} globals, the limit is {1}")]
+    TooManyGlobals(u32, u32),
+    #[error("Wasm module must not have a start function")]
+    StartFunction,
+}
+
+/// The kind of a wasm module, which determines the interface that the module
+/// must conform to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WasmKind {
+    /// Transaction code
+    Tx,
+    /// Validity predicate code
+    Vp,
+    /// Matchmaker code
+    Matchmaker,
+    /// Matchmaker's filter code
+    Filter,
+}
+
+impl WasmKind {
+    /// The host functions that can be imported by this kind of wasm module
+    fn host_functions(&self) -> &'static [&'static str] {
+        match self {
+            WasmKind::Tx => TX_HOST_FUNCTIONS,
+            WasmKind::Vp => VP_HOST_FUNCTIONS,
+            WasmKind::Matchmaker => MATCHMAKER_HOST_FUNCTIONS,
+            WasmKind::Filter => FILTER_HOST_FUNCTIONS,
+        }
+    }
+
+    /// The name of the entrypoint that this kind of wasm module must export
+    pub fn entrypoint(&self) -> &'static str {
+        match self {
+            WasmKind::Tx => TX_ENTRYPOINT,
+            WasmKind::Vp => VP_ENTRYPOINT,
+            WasmKind::Matchmaker => MATCHMAKER_ENTRYPOINT,
+            WasmKind::Filter => FILTER_ENTRYPOINT,
+        }
+    }
+
+    /// The parameters and the results of the entrypoint. All the parameters
+    /// are pointers and lengths of the inputs written into the memory.
+    fn entrypoint_signature(&self) -> (&'static [Type], &'static [Type]) {
+        match self {
+            WasmKind::Tx => (&[Type::I64; 2], &[]),
+            WasmKind::Vp => (&[Type::I64; 8], &[Type::I64]),
+            WasmKind::Matchmaker => (&[Type::I64; 6], &[Type::I64]),
+            WasmKind::Filter => (&[Type::I64; 2], &[Type::I64]),
+        }
+    }
+}
+
 /// Validate an untrusted wasm code with restrictions that we place such code
-/// (e.g. transaction and validity predicates)
+/// (e.g. transaction and validity predicates). Besides the wasm features, the
+/// module may only import the host functions available to its kind, it must
+/// export its memory and the entrypoint with the expected signature, it must
+/// not have a start function and the number of its tables and globals is
+/// bounded.
 pub fn validate_untrusted_wasm(
     wasm_code: impl AsRef<[u8]>,
-) -> Result<(), wasmparser::BinaryReaderError> {
+    kind: WasmKind,
+) -> Result<(), WasmValidationError> {
+    let wasm_code = wasm_code.as_ref();
     let mut validator = Validator::new();
 
     let features = WasmFeatures {
@@ -194,5 +344,122 @@ pub fn validate_untrusted_wasm(
     };
     validator.wasm_features(features);
 
-    validator.validate_all(wasm_code.as_ref())
+    validator
+        .validate_all(wasm_code)
+        .map_err(WasmValidationError::InvalidWasm)?;
+    validate_wasm_interface(wasm_code, kind)
+}
+
+/// Validate the imports, the exports and the limits of a wasm module that
+/// has already been validated by the [`Validator`].
+fn validate_wasm_interface(
+    wasm_code: &[u8],
+    kind: WasmKind,
+) -> Result<(), WasmValidationError> {
+    use WasmValidationError as Error;
+
+    // The function types declared in the module
+    let mut types: Vec<Option<FuncType>> = vec![];
+    // The indices into `types` of the imported and then the defined functions
+    let mut functions: Vec<u32> = vec![];
+    let mut has_memory_export = false;
+    let mut entrypoint_index: Option<u32> = None;
+    let entrypoint = kind.entrypoint();
+
+    for payload in Parser::new(0).parse_all(wasm_code) {
+        match payload.map_err(Error::InvalidWasm)? {
+            Payload::TypeSection(reader) => {
+                for ty in reader {
+                    match ty.map_err(Error::InvalidWasm)? {
+                        TypeDef::Func(func_type) => types.push(Some(func_type)),
+                        _ => types.push(None),
+                    }
+                }
+            }
+            Payload::ImportSection(reader) => {
+                for import in reader {
+                    let import = import.map_err(Error::InvalidWasm)?;
+                    let field = import.field.unwrap_or_default();
+                    let is_allowed = import.module == IMPORTS_NAMESPACE
+                        && match import.ty {
+                            ImportSectionEntryType::Function(ty) => {
+                                functions.push(ty);
+                                kind.host_functions()
+                                    .iter()
+                                    .any(|f| *f == field)
+                            }
+                            ImportSectionEntryType::Memory(_) => {
+                                field == MEMORY_EXPORT
+                            }
+                            _ => false,
+                        };
+                    if !is_allowed {
+                        return Err(Error::ForbiddenImport {
+                            module: import.module.to_owned(),
+                            field: field.to_owned(),
+                        });
+                    }
+                }
+            }
+            Payload::FunctionSection(reader) => {
+                for ty in reader {
+                    functions.push(ty.map_err(Error::InvalidWasm)?);
+                }
+            }
+            Payload::TableSection(reader) => {
+                if reader.get_count() > WASM_MAX_TABLES {
+                    return Err(Error::TooManyTables(
+                        reader.get_count(),
+                        WASM_MAX_TABLES,
+                    ));
+                }
+            }
+            Payload::GlobalSection(reader) => {
+                if reader.get_count() > WASM_MAX_GLOBALS {
+                    return Err(Error::TooManyGlobals(
+                        reader.get_count(),
+                        WASM_MAX_GLOBALS,
+                    ));
+                }
+            }
+            Payload::ExportSection(reader) => {
+                for export in reader {
+                    let export = export.map_err(Error::InvalidWasm)?;
+                    match export.kind {
+                        ExternalKind::Memory
+                            if export.field == MEMORY_EXPORT =>
+                        {
+                            has_memory_export = true
+                        }
+                        ExternalKind::Function
+                            if export.field == entrypoint =>
+                        {
+                            entrypoint_index = Some(export.index)
+                        }
+                        _ => {}
+                    }
+                }
+            }
+            Payload::StartSection { .. } => return Err(Error::StartFunction),
+            _ => {}
+        }
+    }
+
+    if !has_memory_export {
+        return Err(Error::MissingMemoryExport);
+    }
+    let entrypoint_index =
+        entrypoint_index.ok_or(Error::MissingEntrypoint(entrypoint))?;
+    let entrypoint_type = functions
+        .get(entrypoint_index as usize)
+        .and_then(|ty| types.get(*ty as usize))
+        .and_then(Option::as_ref)
+        .ok_or(Error::UnexpectedEntrypointSignature(entrypoint))?;
+    let (params, returns) = kind.entrypoint_signature();
+    if &*entrypoint_type.params != params
+        || &*entrypoint_type.returns != returns
+    {
+        return Err(Error::UnexpectedEntrypointSignature(entrypoint));
+    }
+    Ok(())
 }
@@ -13,6 +13,7 @@ use wasmer::{
 
 use crate::gossip::mm::MmHost;
 use crate::ledger::gas::{BlockGasMeter, VpGasMeter};
+use crate::ledger::parameters::Parameters;
 use crate::ledger::storage::write_log::WriteLog;
 use crate::ledger::storage::{self, Storage, StorageHasher};
 use crate::types::Address;
@@ -75,9 +76,11 @@ impl WasmerEnv for FilterEnv<WasmMemory> {
 
 /// Prepare imports (memory and host functions) exposed to the vm guest running
 /// transaction code
+#[allow(clippy::too_many_arguments)]
 pub fn prepare_tx_imports<DB, H>(
     wasm_store: &Store,
     storage: EnvHostWrapper<'static, &'static Storage<DB, H>>,
+    params: EnvHostWrapper<'static, &'static Parameters>,
     write_log: MutEnvHostWrapper<'static, &WriteLog>,
     iterators: MutEnvHostWrapper<'static, &PrefixIterators<'static, DB>>,
     verifiers: MutEnvHostWrapper<'static, &HashSet<Address>>,
@@ -91,6 +94,7 @@ where
     let env = TxEnv {
         memory: WasmMemory::default(),
         storage,
+        params,
         write_log,
         iterators,
         verifiers,
@@ -105,6 +109,9 @@ where
             "anoma_tx_has_key" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_has_key),
             "anoma_tx_write" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_write),
             "anoma_tx_delete" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_delete),
+            "anoma_tx_open_savepoint" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_open_savepoint),
+            "anoma_tx_rollback_savepoint" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_rollback_savepoint),
+            "anoma_tx_release_savepoint" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_release_savepoint),
             "anoma_tx_iter_prefix" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_iter_prefix),
             "anoma_tx_iter_next" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_iter_next),
             "anoma_tx_insert_verifier" => Function::new_native_with_env(wasm_store, env.clone(), host_env::tx_insert_verifier),
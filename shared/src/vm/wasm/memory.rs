@@ -22,30 +22,38 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 // The bounds are set in number of pages, the actual size is multiplied by
 // `wasmer::WASM_PAGE_SIZE = 64kiB`. The wasm code also occupies the memory
-// space.
+// space. The maximum bounds of the transaction and VP memory are set by the
+// protocol parameters.
 // TODO set bounds to accommodate for wasm env size
 const TX_MEMORY_INIT_PAGES: u32 = 100; // 6.4 MiB
-const TX_MEMORY_MAX_PAGES: u32 = 200; // 12.8 MiB
 const VP_MEMORY_INIT_PAGES: u32 = 100; // 6.4 MiB
-const VP_MEMORY_MAX_PAGES: u32 = 200; // 12.8 MiB
 const MATCHMAKER_MEMORY_INIT_PAGES: u32 = 400; // 12.8 MiB
 const FILTER_MEMORY_INIT_PAGES: u32 = 100; // 6.4 MiB
 
-/// Prepare memory for instantiating a transaction module
-pub fn prepare_tx_memory(store: &wasmer::Store) -> Result<wasmer::Memory> {
+/// Prepare memory for instantiating a transaction module with the given
+/// maximum number of pages. The initial memory cannot exceed the maximum.
+pub fn prepare_tx_memory(
+    store: &wasmer::Store,
+    max_pages: u32,
+) -> Result<wasmer::Memory> {
     let mem_type = wasmer::MemoryType::new(
-        TX_MEMORY_INIT_PAGES,
-        Some(TX_MEMORY_MAX_PAGES),
+        TX_MEMORY_INIT_PAGES.min(max_pages),
+        Some(max_pages),
         false,
     );
     Memory::new(store, mem_type).map_err(Error::InitMemoryError)
 }
 
-/// Prepare memory for instantiating a validity predicate module
-pub fn prepare_vp_memory(store: &wasmer::Store) -> Result<wasmer::Memory> {
+/// Prepare memory for instantiating a validity predicate module with the
+/// given maximum number of pages. The initial memory cannot exceed the
+/// maximum.
+pub fn prepare_vp_memory(
+    store: &wasmer::Store,
+    max_pages: u32,
+) -> Result<wasmer::Memory> {
     let mem_type = wasmer::MemoryType::new(
-        VP_MEMORY_INIT_PAGES,
-        Some(VP_MEMORY_MAX_PAGES),
+        VP_MEMORY_INIT_PAGES.min(max_pages),
+        Some(max_pages),
         false,
     );
     let memory =
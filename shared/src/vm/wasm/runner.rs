@@ -14,6 +14,7 @@ use super::host_env::{
 };
 use crate::gossip::mm::MmHost;
 use crate::ledger::gas::{BlockGasMeter, VpGasMeter};
+use crate::ledger::parameters::Parameters;
 use crate::ledger::storage::write_log::WriteLog;
 use crate::ledger::storage::{self, Storage, StorageHasher};
 use crate::types::internal::HostEnvResult;
@@ -24,13 +25,10 @@ use crate::vm::types::{TxInput, VpInput};
 use crate::vm::wasm::memory;
 use crate::vm::{
     validate_untrusted_wasm, EnvHostSliceWrapper, EnvHostWrapper,
-    MutEnvHostWrapper,
+    MutEnvHostWrapper, WasmKind, WasmValidationError, FILTER_ENTRYPOINT,
+    MATCHMAKER_ENTRYPOINT, TX_ENTRYPOINT, VP_ENTRYPOINT,
 };
 
-const TX_ENTRYPOINT: &str = "_apply_tx";
-const VP_ENTRYPOINT: &str = "_validate_tx";
-const MATCHMAKER_ENTRYPOINT: &str = "_match_intent";
-const FILTER_ENTRYPOINT: &str = "_validate_intent";
 const WASM_STACK_LIMIT: u32 = u16::MAX as u32;
 
 #[allow(missing_docs)]
@@ -66,7 +64,7 @@ pub enum Error {
         error: wasmer::RuntimeError,
     },
     #[error("Wasm validation error: {0}")]
-    ValidationError(wasmparser::BinaryReaderError),
+    ValidationError(WasmValidationError),
 }
 
 /// Result for functions that may fail
@@ -92,11 +90,12 @@ impl TxRunner {
         Self { wasm_store }
     }
 
-    /// Execute a transaction code. Returns verifiers requested by the
-    /// transaction.
+    /// Execute a transaction code with the protocol parameters of the current
+    /// block. Returns verifiers requested by the transaction.
     pub fn run<DB, H>(
         &self,
         storage: &Storage<DB, H>,
+        params: &Parameters,
         write_log: &mut WriteLog,
         gas_meter: &mut BlockGasMeter,
         tx_code: Vec<u8>,
@@ -106,12 +105,16 @@ impl TxRunner {
         DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
         H: 'static + StorageHasher,
     {
-        validate_untrusted_wasm(&tx_code).map_err(Error::ValidationError)?;
+        validate_untrusted_wasm(&tx_code, WasmKind::Tx)
+            .map_err(Error::ValidationError)?;
 
         // This is not thread-safe, we're assuming single-threaded Tx runner.
         let storage = unsafe { EnvHostWrapper::new(storage) };
         // This is also not thread-safe, we're assuming single-threaded Tx
         // runner.
+        let env_params = unsafe { EnvHostWrapper::new(params) };
+        // This is also not thread-safe, we're assuming single-threaded Tx
+        // runner.
         let write_log = unsafe { MutEnvHostWrapper::new(write_log) };
         // This is also not thread-safe, we're assuming single-threaded Tx
         // runner.
@@ -129,11 +132,15 @@ impl TxRunner {
 
         let tx_module = wasmer::Module::new(&self.wasm_store, &tx_code)
             .map_err(Error::CompileError)?;
-        let initial_memory = memory::prepare_tx_memory(&self.wasm_store)
-            .map_err(Error::MemoryError)?;
+        let initial_memory = memory::prepare_tx_memory(
+            &self.wasm_store,
+            params.tx_memory_max_pages,
+        )
+        .map_err(Error::MemoryError)?;
         let tx_imports = prepare_tx_imports(
             &self.wasm_store,
             storage,
+            env_params,
             write_log,
             iterators,
             env_verifiers,
@@ -195,9 +202,10 @@ impl VpRunner {
         Self { wasm_store }
     }
 
-    /// Execute a validity predicate code. Returns whether the validity
-    /// predicate accepted storage modifications performed by the transaction
-    /// that triggered the execution.
+    /// Execute a validity predicate code with the protocol parameters of the
+    /// current block. Returns whether the validity predicate accepted storage
+    /// modifications performed by the transaction that triggered the
+    /// execution.
     // TODO consider using a wrapper object for all the host env references
     #[allow(clippy::too_many_arguments)]
     pub fn run<DB, H>(
@@ -207,6 +215,7 @@ impl VpRunner {
         tx_code: impl AsRef<[u8]>,
         address: &Address,
         storage: &Storage<DB, H>,
+        params: &Parameters,
         write_log: &WriteLog,
         vp_gas_meter: &mut VpGasMeter,
         keys_changed: &[Key],
@@ -216,7 +225,7 @@ impl VpRunner {
         DB: 'static + storage::DB + for<'iter> storage::DBIter<'iter>,
         H: 'static + StorageHasher,
     {
-        validate_untrusted_wasm(vp_code.as_ref())
+        validate_untrusted_wasm(vp_code.as_ref(), WasmKind::Vp)
             .map_err(Error::ValidationError)?;
 
         // Read-only access from parallel Vp runners
@@ -247,6 +256,7 @@ impl VpRunner {
             tx_code: tx_code.clone(),
             keys_changed: env_keys_changed.clone(),
             verifiers: env_verifiers.clone(),
+            vp_memory_max_pages: params.vp_memory_max_pages,
         };
         // Assuming single-threaded VP wasm runner
         let eval_runner = unsafe { EnvHostWrapper::new(&eval_runner) };
@@ -255,8 +265,11 @@ impl VpRunner {
 
         let vp_module = wasmer::Module::new(&self.wasm_store, &vp_code)
             .map_err(Error::CompileError)?;
-        let initial_memory = memory::prepare_vp_memory(&self.wasm_store)
-            .map_err(Error::MemoryError)?;
+        let initial_memory = memory::prepare_vp_memory(
+            &self.wasm_store,
+            params.vp_memory_max_pages,
+        )
+        .map_err(Error::MemoryError)?;
         let input: VpInput = VpInput {
             addr: &address,
             data: tx_data.as_ref(),
@@ -349,6 +362,8 @@ where
     pub keys_changed: EnvHostSliceWrapper<'a, &'a [Key]>,
     /// The verifiers whose validity predicates should be triggered.
     pub verifiers: EnvHostWrapper<'a, &'a HashSet<Address>>,
+    /// The maximum memory of the validity predicate in pages.
+    pub vp_memory_max_pages: u32,
 }
 
 impl<DB, H> VpEvalRunner for VpEval<'static, DB, H>
@@ -358,7 +373,7 @@ where
 {
     // TODO more code re-use with VpRunner
     fn eval(&self, vp_code: Vec<u8>, input_data: Vec<u8>) -> HostEnvResult {
-        if validate_untrusted_wasm(&vp_code).is_err() {
+        if validate_untrusted_wasm(&vp_code, WasmKind::Vp).is_err() {
             return HostEnvResult::Fail;
         }
 
@@ -376,6 +391,7 @@ where
             tx_code: self.tx_code.clone(),
             keys_changed: self.keys_changed.clone(),
             verifiers: self.verifiers.clone(),
+            vp_memory_max_pages: self.vp_memory_max_pages,
         };
         // Assuming single-threaded VP wasm runner
         let eval_runner = unsafe { EnvHostWrapper::new(&eval_runner) };
@@ -391,8 +407,11 @@ where
             Ok(ok) => ok,
             Err(_) => return HostEnvResult::Fail,
         };
-        let initial_memory = match memory::prepare_vp_memory(&wasm_store)
-            .map_err(Error::MemoryError)
+        let initial_memory = match memory::prepare_vp_memory(
+            &wasm_store,
+            self.vp_memory_max_pages,
+        )
+        .map_err(Error::MemoryError)
         {
             Ok(ok) => ok,
             Err(_) => return HostEnvResult::Fail,
@@ -462,6 +481,8 @@ impl MmRunner {
     where
         MM: 'static + MmHost,
     {
+        validate_untrusted_wasm(matchmaker_code.as_ref(), WasmKind::Matchmaker)
+            .map_err(Error::ValidationError)?;
         let matchmaker_module: wasmer::Module =
             wasmer::Module::new(&self.wasm_store, &matchmaker_code)
                 .map_err(Error::CompileError)?;
@@ -552,7 +573,7 @@ impl MmFilterRunner {
         code: impl AsRef<[u8]>,
         intent_data: impl AsRef<[u8]>,
     ) -> Result<bool> {
-        validate_untrusted_wasm(code.as_ref())
+        validate_untrusted_wasm(code.as_ref(), WasmKind::Filter)
             .map_err(Error::ValidationError)?;
         let code = prepare_wasm_code(code)?;
         let filter_module: wasmer::Module =
@@ -620,8 +641,98 @@ fn get_gas_rules() -> rules::Set {
 
 #[cfg(test)]
 mod tests {
+    use std::collections::BTreeSet;
+
     use super::*;
+    use crate::ledger::storage::mockdb::MockDB;
     use crate::ledger::storage::testing::TestStorage;
+    use crate::types::address;
+    use crate::vm::{IMPORTS_NAMESPACE, MEMORY_EXPORT};
+
+    /// A VP eval runner for the tests that don't evaluate any VPs
+    struct NoEval;
+
+    impl VpEvalRunner for NoEval {
+        fn eval(
+            &self,
+            _vp_code: Vec<u8>,
+            _input_data: Vec<u8>,
+        ) -> HostEnvResult {
+            HostEnvResult::Fail
+        }
+    }
+
+    /// Get the names of the host functions in the given imports, without the
+    /// memory and the gas function that is injected into every module.
+    fn host_function_imports(
+        imports: &wasmer::ImportObject,
+    ) -> BTreeSet<String> {
+        imports
+            .externs_vec()
+            .into_iter()
+            .filter_map(|(namespace, name, _export)| {
+                assert_eq!(namespace, IMPORTS_NAMESPACE);
+                if name == MEMORY_EXPORT || name == "gas" {
+                    None
+                } else {
+                    Some(name)
+                }
+            })
+            .collect()
+    }
+
+    /// Test that the host functions that untrusted transactions and validity
+    /// predicates may import are the ones prepared by the runners.
+    #[test]
+    fn test_host_functions_match_imports() {
+        let store = TxRunner::new().wasm_store;
+        let storage = TestStorage::default();
+        let params = Parameters::default();
+        let mut write_log = WriteLog::default();
+        let mut iterators: PrefixIterators<'static, MockDB> =
+            PrefixIterators::default();
+        let mut verifiers = HashSet::new();
+        let mut gas_meter = BlockGasMeter::default();
+        let mut vp_gas_meter = VpGasMeter::new(0, &params);
+        let tx_code: Vec<u8> = vec![];
+
+        let tx_imports = prepare_tx_imports(
+            &store,
+            unsafe { EnvHostWrapper::new(&storage) },
+            unsafe { EnvHostWrapper::new(&params) },
+            unsafe { MutEnvHostWrapper::new(&mut write_log) },
+            unsafe { MutEnvHostWrapper::new(&mut iterators) },
+            unsafe { MutEnvHostWrapper::new(&mut verifiers) },
+            unsafe { MutEnvHostWrapper::new(&mut gas_meter) },
+            memory::prepare_tx_memory(&store, params.tx_memory_max_pages)
+                .expect("Unable to prepare the tx memory"),
+        );
+        let tx_host_functions: BTreeSet<String> = WasmKind::Tx
+            .host_functions()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        assert_eq!(host_function_imports(&tx_imports), tx_host_functions);
+
+        let vp_imports = prepare_vp_env(
+            &store,
+            address::testing::established_address_1(),
+            unsafe { EnvHostWrapper::new(&storage) },
+            unsafe { EnvHostWrapper::new(&write_log) },
+            unsafe { MutEnvHostWrapper::new(&mut iterators) },
+            unsafe { MutEnvHostWrapper::new(&mut vp_gas_meter) },
+            unsafe { EnvHostSliceWrapper::new(&tx_code[..]) },
+            memory::prepare_vp_memory(&store, params.vp_memory_max_pages)
+                .expect("Unable to prepare the VP memory"),
+            unsafe { EnvHostWrapper::new(&NoEval) },
+        );
+        let vp_host_functions: BTreeSet<String> = WasmKind::Vp
+            .host_functions()
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        assert_eq!(host_function_imports(&vp_imports), vp_host_functions);
+    }
 
     /// Test that when a transaction wasm goes over the stack-height limit, the
     /// execution is aborted.
@@ -638,7 +749,7 @@ mod tests {
             format!(
                 r#"
             (module
-                (type (;0;) (func (param i64 i64) (result i64)))
+                (type (;0;) (func (param i64 i64)))
 
                 ;; recursive loop, the param is the number of loops
                 (func $loop (param i64) (result i64)
@@ -648,14 +759,14 @@ mod tests {
                 (then (get_local 0))
                 (else (call $loop (i64.sub (get_local 0) (i64.const 1))))))
 
-                (func $apply_tx (type 0) (param i64 i64) (result i64)
-                (call $loop (i64.const {})))
+                (func $apply_tx (type 0) (param i64 i64)
+                (drop (call $loop (i64.const {}))))
 
                 (table (;0;) 1 1 funcref)
                 (memory (;0;) 16)
                 (global (;0;) (mut i32) (i32.const 1048576))
                 (export "memory" (memory 0))
-                (export "apply_tx" (func $apply_tx)))
+                (export "_apply_tx" (func $apply_tx)))
             "#,
                 loops
             )
@@ -670,7 +781,14 @@ mod tests {
         let mut write_log = WriteLog::default();
         let mut gas_meter = BlockGasMeter::default();
         let error = runner
-            .run(&storage, &mut write_log, &mut gas_meter, tx_code, tx_data)
+            .run(
+                &storage,
+                &Parameters::default(),
+                &mut write_log,
+                &mut gas_meter,
+                tx_code,
+                tx_data,
+            )
             .expect_err(
                 "Expecting runtime error \"unreachable\" caused by \
                  stack-height overflow",
@@ -700,7 +818,7 @@ mod tests {
         let vp_code = wasmer::wat2wasm(format!(
             r#"
             (module
-                (type (;0;) (func (param i64 i64 i64 i64 i64 i64) (result i64)))
+                (type (;0;) (func (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)))
 
                 ;; recursive loop, the param is the number of loops
                 (func $loop (param i64) (result i64)
@@ -710,14 +828,14 @@ mod tests {
                 (then (get_local 0))
                 (else (call $loop (i64.sub (get_local 0) (i64.const 1))))))
 
-                (func $validate_tx (type 0) (param i64 i64 i64 i64 i64 i64) (result i64)
+                (func $validate_tx (type 0) (param i64 i64 i64 i64 i64 i64 i64 i64) (result i64)
                 (call $loop (i64.const {})))
 
                 (table (;0;) 1 1 funcref)
                 (memory (;0;) 16)
                 (global (;0;) (mut i32) (i32.const 1048576))
                 (export "memory" (memory 0))
-                (export "validate_tx" (func $validate_tx)))
+                (export "_validate_tx" (func $validate_tx)))
             "#, loops).as_bytes(),
         )
         .expect("unexpected error converting wat2wasm").into_owned();
@@ -728,7 +846,7 @@ mod tests {
         let mut storage = TestStorage::default();
         let addr = storage.address_gen.generate_address("rng seed");
         let write_log = WriteLog::default();
-        let mut gas_meter = VpGasMeter::new(0);
+        let mut gas_meter = VpGasMeter::new(0, &Parameters::default());
         let keys_changed = vec![];
         let verifiers = HashSet::new();
         let error = runner
@@ -738,6 +856,7 @@ mod tests {
                 &tx_code,
                 &addr,
                 &storage,
+                &Parameters::default(),
                 &write_log,
                 &mut gas_meter,
                 &keys_changed[..],
@@ -757,4 +876,120 @@ mod tests {
         }
         println!("Failed with unexpected error: {}", error);
     }
+
+    /// Test that the interface of untrusted wasm modules is validated before
+    /// they are executed.
+    #[test]
+    fn test_untrusted_wasm_interface() {
+        let tx_wasm = |imports: &str, exports: &str| {
+            wasmer::wat2wasm(
+                format!(
+                    r#"
+            (module
+                {}
+                (func $apply_tx (param i64 i64))
+                (memory (;0;) 16)
+                {})
+            "#,
+                    imports, exports
+                )
+                .as_bytes(),
+            )
+            .expect("unexpected error converting wat2wasm")
+            .into_owned()
+        };
+        let valid_exports = r#"
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $apply_tx))"#;
+
+        // A transaction importing its host function is valid
+        let code = tx_wasm(
+            r#"(import "env" "anoma_tx_log_string" (func (param i64 i64)))"#,
+            valid_exports,
+        );
+        assert!(validate_untrusted_wasm(&code, WasmKind::Tx).is_ok());
+
+        // ... but the same module is not a valid VP
+        assert!(matches!(
+            validate_untrusted_wasm(&code, WasmKind::Vp),
+            Err(WasmValidationError::ForbiddenImport { .. })
+        ));
+
+        // A transaction must not import a VP host function
+        let code = tx_wasm(
+            r#"(import "env" "anoma_vp_read_pre" (func (param i64 i64)))"#,
+            valid_exports,
+        );
+        assert!(matches!(
+            validate_untrusted_wasm(&code, WasmKind::Tx),
+            Err(WasmValidationError::ForbiddenImport { .. })
+        ));
+
+        // A transaction must not import the injected gas function
+        let code = tx_wasm(
+            r#"(import "env" "gas" (func (param i32)))"#,
+            valid_exports,
+        );
+        assert!(matches!(
+            validate_untrusted_wasm(&code, WasmKind::Tx),
+            Err(WasmValidationError::ForbiddenImport { .. })
+        ));
+
+        // The memory must be exported
+        let code = tx_wasm("", r#"(export "_apply_tx" (func $apply_tx))"#);
+        assert!(matches!(
+            validate_untrusted_wasm(&code, WasmKind::Tx),
+            Err(WasmValidationError::MissingMemoryExport)
+        ));
+
+        // The entrypoint must be exported
+        let code = tx_wasm(
+            "",
+            r#"
+                (export "memory" (memory 0))
+                (export "apply_tx" (func $apply_tx))"#,
+        );
+        assert!(matches!(
+            validate_untrusted_wasm(&code, WasmKind::Tx),
+            Err(WasmValidationError::MissingEntrypoint(TX_ENTRYPOINT))
+        ));
+
+        // The entrypoint must have the expected signature
+        let code = wasmer::wat2wasm(
+            br#"
+            (module
+                (func $apply_tx (param i64 i64) (result i64) (i64.const 0))
+                (memory (;0;) 16)
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $apply_tx)))
+            "#,
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+        assert!(matches!(
+            validate_untrusted_wasm(&code, WasmKind::Tx),
+            Err(WasmValidationError::UnexpectedEntrypointSignature(
+                TX_ENTRYPOINT
+            ))
+        ));
+
+        // The module must not have a start function
+        let code = wasmer::wat2wasm(
+            br#"
+            (module
+                (func $apply_tx (param i64 i64))
+                (func $start)
+                (memory (;0;) 16)
+                (start $start)
+                (export "memory" (memory 0))
+                (export "_apply_tx" (func $apply_tx)))
+            "#,
+        )
+        .expect("unexpected error converting wat2wasm")
+        .into_owned();
+        assert!(matches!(
+            validate_untrusted_wasm(&code, WasmKind::Tx),
+            Err(WasmValidationError::StartFunction)
+        ));
+    }
 }
@@ -3,7 +3,11 @@ pub mod vp;
 
 #[cfg(test)]
 mod tests {
-    use anoma_shared::types::Key;
+    use std::collections::HashSet;
+
+    use anoma_shared::ledger::parameters;
+    use anoma_shared::types::address;
+    use anoma_shared::types::{token, Key, KeySeg};
     use anoma_vm_env::tx_prelude::BorshSerialize;
 
     use super::tx::*;
@@ -24,6 +28,86 @@ mod tests {
         assert_eq!(Some(value), read_value);
     }
 
+    #[test]
+    fn test_tx_savepoints() {
+        let mut env = TestTxEnv::default();
+        init_tx_env(&mut env);
+
+        let key = "key";
+        tx_host_env::write(key, 1_u64);
+
+        // a failed sub-operation is rolled back
+        let result: Result<(), ()> = tx_host_env::with_savepoint(|| {
+            tx_host_env::write(key, 2_u64);
+            tx_host_env::write("other_key", 2_u64);
+            Err(())
+        });
+        assert!(result.is_err());
+        assert_eq!(tx_host_env::read(key), Some(1_u64));
+        assert!(!tx_host_env::has_key("other_key"));
+
+        // a successful sub-operation is kept
+        let result: Result<(), ()> = tx_host_env::with_savepoint(|| {
+            tx_host_env::write(key, 3_u64);
+            Ok(())
+        });
+        assert!(result.is_ok());
+        assert_eq!(tx_host_env::read(key), Some(3_u64));
+
+        // the savepoints are nested
+        tx_host_env::open_savepoint();
+        tx_host_env::write(key, 4_u64);
+        tx_host_env::open_savepoint();
+        tx_host_env::write(key, 5_u64);
+        assert!(tx_host_env::release_savepoint());
+        assert_eq!(tx_host_env::read(key), Some(5_u64));
+        assert!(tx_host_env::rollback_savepoint());
+        assert_eq!(tx_host_env::read(key), Some(3_u64));
+        assert!(!tx_host_env::rollback_savepoint());
+    }
+
+    #[test]
+    fn test_tx_init_and_configure_account() {
+        let mut env = TestTxEnv::default();
+        init_tx_env(&mut env);
+
+        let vp_template = std::fs::read("res/wasm/vp_template.wasm")
+            .expect("cannot load the VP template");
+        let addr = tx_host_env::init_account(&vp_template);
+
+        // the new account's VP can be updated and its sub-space written in the
+        // same transaction
+        tx_host_env::update_validity_predicate(addr.clone(), &vp_template);
+        let key = Key::from(addr.to_db_key())
+            .push(&"key".to_owned())
+            .unwrap()
+            .to_string();
+        tx_host_env::write(&key, 1_u64);
+        assert_eq!(tx_host_env::read(&key), Some(1_u64));
+
+        let (_changed_keys, initialized_accounts) =
+            env.write_log.get_partitioned_keys();
+        assert_eq!(initialized_accounts.len(), 1);
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_tx_write_vp_not_whitelisted() {
+        let mut env = TestTxEnv::default();
+        let vp_template = std::fs::read("res/wasm/vp_template.wasm")
+            .expect("cannot load the VP template");
+        env.params.vp_whitelist = vec![parameters::code_hash(&vp_template)];
+        init_tx_env(&mut env);
+
+        let addr = tx_host_env::init_account(&vp_template);
+
+        // a validity predicate that is not whitelisted cannot be written to
+        // its key directly, even for an account initialized in the same
+        // transaction
+        let key = Key::validity_predicate(&addr).unwrap().to_string();
+        tx_host_env::write(&key, vec![0_u8; 8]);
+    }
+
     /// An example how to write a VP host environment integration test
     #[test]
     fn test_vp_host_env() {
@@ -63,4 +147,52 @@ mod tests {
         let result = vp_host_env::eval(vp_template, input_data);
         assert!(result);
     }
+
+    /// Set up a token VP environment in which the transaction replaces the
+    /// token's VP and mint authority and mints new supply for the attacker.
+    /// Returns whether the token VP accepts it.
+    fn token_takeover(token_exists: bool) -> bool {
+        let mut env = TestVpEnv::default();
+        init_vp_env(&mut env);
+        let token = env.addr.clone();
+        let attacker = address::testing::established_address_2();
+        let vp_key = Key::validity_predicate(&token).unwrap();
+        if token_exists {
+            env.storage.write(&vp_key, vec![1_u8; 8]).unwrap();
+        }
+
+        let amount = token::Amount::from(1_000);
+        let balance_key = token::balance_key(&token, &attacker);
+        let changes = vec![
+            (vp_key, vec![2_u8; 8]),
+            (
+                token::mint_authority_key(&token),
+                attacker.try_to_vec().unwrap(),
+            ),
+            (token::supply_key(&token), amount.try_to_vec().unwrap()),
+            (balance_key, amount.try_to_vec().unwrap()),
+        ];
+        let mut keys_changed = vec![];
+        for (key, value) in changes {
+            env.write_log.write(&key, value);
+            keys_changed.push(key);
+        }
+        let verifiers: HashSet<_> = vec![attacker].into_iter().collect();
+
+        anoma_vm_env::vp_prelude::token::vp(
+            &token,
+            &[],
+            &keys_changed,
+            &verifiers,
+        )
+    }
+
+    #[test]
+    fn test_token_vp_rejects_takeover() {
+        // a token initialized by the transaction can be set up freely
+        assert!(token_takeover(false));
+        // an existing token's VP and mint authority cannot be replaced to
+        // mint new supply
+        assert!(!token_takeover(true));
+    }
 }
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 
 use anoma_shared::ledger::gas::BlockGasMeter;
+use anoma_shared::ledger::parameters::Parameters;
 use anoma_shared::ledger::storage::mockdb::MockDB;
 use anoma_shared::ledger::storage::testing::TestStorage;
 use anoma_shared::ledger::storage::write_log::WriteLog;
@@ -21,6 +22,7 @@ pub mod tx_host_env {
 /// Host environment structures required for transactions.
 pub struct TestTxEnv {
     pub storage: TestStorage,
+    pub params: Parameters,
     pub write_log: WriteLog,
     pub iterators: PrefixIterators<'static, MockDB>,
     pub verifiers: HashSet<Address>,
@@ -31,6 +33,7 @@ impl Default for TestTxEnv {
     fn default() -> Self {
         Self {
             storage: TestStorage::default(),
+            params: Parameters::default(),
             write_log: WriteLog::default(),
             iterators: PrefixIterators::default(),
             verifiers: HashSet::default(),
@@ -50,6 +53,7 @@ impl TestTxEnv {
 pub fn init_tx_env(
     TestTxEnv {
         storage,
+        params,
         write_log,
         iterators,
         verifiers,
@@ -59,7 +63,7 @@ pub fn init_tx_env(
     tx_host_env::ENV.with(|env| {
         *env.borrow_mut() = Some({
             vm::host_env::testing::tx_env(
-                storage, write_log, iterators, verifiers, gas_meter,
+                storage, params, write_log, iterators, verifiers, gas_meter,
             )
         })
     });
@@ -131,6 +135,9 @@ mod native_tx_host_env {
         val_len: u64
     ));
     native_host_fn!(tx_delete(key_ptr: u64, key_len: u64));
+    native_host_fn!(tx_open_savepoint());
+    native_host_fn!(tx_rollback_savepoint() -> i64);
+    native_host_fn!(tx_release_savepoint() -> i64);
     native_host_fn!(tx_iter_prefix(prefix_ptr: u64, prefix_len: u64) -> u64);
     native_host_fn!(tx_iter_next(iter_id: u64, result_ptr: u64) -> i64);
     native_host_fn!(tx_insert_verifier(addr_ptr: u64, addr_len: u64));
@@ -1,6 +1,7 @@
 use std::collections::HashSet;
 
 use anoma_shared::ledger::gas::VpGasMeter;
+use anoma_shared::ledger::parameters::Parameters;
 use anoma_shared::ledger::storage::mockdb::MockDB;
 use anoma_shared::ledger::storage::testing::TestStorage;
 use anoma_shared::ledger::storage::write_log::WriteLog;
@@ -41,7 +42,7 @@ impl Default for TestVpEnv {
         let storage = TestStorage::default();
         let write_log = WriteLog::default();
         let iterators = PrefixIterators::default();
-        let gas_meter = VpGasMeter::new(0);
+        let gas_meter = VpGasMeter::new(0, &Parameters::default());
         let tx_code = vec![];
         let keys_changed = vec![];
         let verifiers = HashSet::default();
@@ -84,6 +85,7 @@ impl Default for TestVpEnv {
                 tx_code: env_tx_code,
                 keys_changed: env_keys_changed,
                 verifiers: env_verifiers,
+                vp_memory_max_pages: Parameters::default().vp_memory_max_pages,
             }
         };
         #[cfg(not(feature = "wasm-runtime"))]
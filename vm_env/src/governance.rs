@@ -0,0 +1,67 @@
+use anoma_shared::types::governance::{
+    self, GovParams, Proposal, ProposalContent, ProposalId, Vote,
+};
+use anoma_shared::types::pos::{self, Bonds};
+use anoma_shared::types::token::Amount;
+use anoma_shared::types::Address;
+
+/// Submit a proposal with the author's deposit. Returns the proposal's ID.
+pub fn submit_proposal(
+    author: &Address,
+    title: String,
+    content: ProposalContent,
+) -> Result<ProposalId, governance::Error> {
+    use crate::imports::tx;
+
+    let params: GovParams =
+        tx::read(&governance::params_key().to_string()).unwrap_or_default();
+    params.check_content(&content)?;
+    let (_, epoch) = crate::pos::current_epoch();
+    let deposit = Amount::from(params.min_deposit);
+    crate::token::transfer(
+        author,
+        &governance::governance_address(),
+        &pos::staking_token(),
+        deposit,
+    )
+    .map_err(governance::Error::Token)?;
+
+    let id_key = governance::next_proposal_id_key().to_string();
+    let id: ProposalId = tx::read(&id_key).unwrap_or_default();
+    let proposal = Proposal {
+        author: author.clone(),
+        title,
+        content,
+        deposit,
+        start_epoch: epoch,
+        end_epoch: epoch + params.voting_period,
+    };
+    tx::write(&governance::proposal_key(id).to_string(), proposal);
+    tx::write(&id_key, id + 1);
+    Ok(id)
+}
+
+/// Vote on a proposal in its voting period. The voter must have some bonded
+/// stake.
+pub fn vote(
+    voter: &Address,
+    proposal_id: ProposalId,
+    vote: Vote,
+) -> Result<(), governance::Error> {
+    use crate::imports::tx;
+
+    let proposal: Proposal =
+        tx::read(&governance::proposal_key(proposal_id).to_string())
+            .ok_or(governance::Error::UnknownProposal(proposal_id))?;
+    let (_, epoch) = crate::pos::current_epoch();
+    if !proposal.is_voting(epoch) {
+        return Err(governance::Error::VotingPeriodOver(proposal_id));
+    }
+    let bonds: Bonds =
+        tx::read(&pos::bond_key(voter).to_string()).unwrap_or_default();
+    if bonds.is_empty() {
+        return Err(governance::Error::NoStake(voter.clone()));
+    }
+    tx::write(&governance::vote_key(proposal_id, voter).to_string(), vote);
+    Ok(())
+}
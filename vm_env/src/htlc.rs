@@ -0,0 +1,84 @@
+use anoma_shared::types::htlc::{self, Htlc};
+use anoma_shared::types::token::{self, Amount};
+use anoma_shared::types::Address;
+
+/// Initialize a new escrow account with the given terms and lock the sender's
+/// tokens in it.
+pub fn lock(htlc: &Htlc, vp_code: &[u8]) -> Result<Address, token::Error> {
+    use crate::imports::tx;
+
+    let escrow = tx::init_account(vp_code);
+    tx::write(&htlc::htlc_key(&escrow).to_string(), htlc);
+    crate::token::transfer(&htlc.sender, &escrow, &htlc.token, htlc.amount)?;
+    Ok(escrow)
+}
+
+/// Release the whole escrowed balance to the beneficiary and reveal the
+/// preimage. The escrow's validity predicate checks the preimage and the
+/// deadline.
+pub fn claim(escrow: &Address, preimage: &[u8]) -> Result<(), token::Error> {
+    use crate::imports::tx;
+
+    let htlc = get_terms(escrow);
+    tx::write(&htlc::preimage_key(escrow).to_string(), preimage.to_vec());
+    release(escrow, &htlc.beneficiary, &htlc.token)
+}
+
+/// Refund the whole escrowed balance to the sender. The escrow's validity
+/// predicate checks the deadline.
+pub fn refund(escrow: &Address) -> Result<(), token::Error> {
+    let htlc = get_terms(escrow);
+    release(escrow, &htlc.sender, &htlc.token)
+}
+
+/// Get the terms of the escrow in a transaction. Panics if the account is not
+/// an escrow.
+fn get_terms(escrow: &Address) -> Htlc {
+    use crate::imports::tx;
+
+    match tx::read(&htlc::htlc_key(escrow).to_string()) {
+        Some(htlc) => htlc,
+        None => {
+            tx::log_string(format!("{} is not an escrow account", escrow));
+            panic!()
+        }
+    }
+}
+
+/// Transfer the whole escrowed balance of the token to the recipient.
+fn release(
+    escrow: &Address,
+    recipient: &Address,
+    token: &Address,
+) -> Result<(), token::Error> {
+    use crate::imports::tx;
+
+    let balance_key = token::balance_key(token, escrow).to_string();
+    let balance: Amount = tx::read(&balance_key).unwrap_or_default();
+    crate::token::transfer(escrow, recipient, token, balance)
+}
+
+/// Get the terms of the escrow, as they were before the transaction.
+pub fn get(escrow: &Address) -> Option<Htlc> {
+    use crate::imports::vp;
+
+    vp::read_pre(&htlc::htlc_key(escrow).to_string())
+}
+
+/// Check that a debit of the escrow's balance of the given token is fully
+/// credited to the recipient.
+pub fn is_released_to(
+    escrow: &Address,
+    recipient: &Address,
+    token: &Address,
+) -> bool {
+    use crate::imports::vp;
+
+    let change = |owner: &Address| {
+        let key = token::balance_key(token, owner).to_string();
+        let pre: Amount = vp::read_pre(&key).unwrap_or_default();
+        let post: Amount = vp::read_post(&key).unwrap_or_default();
+        post.change() - pre.change()
+    };
+    change(recipient) >= -change(escrow)
+}
@@ -88,6 +88,41 @@ pub mod tx {
         unsafe { anoma_tx_delete(key.as_ptr() as _, key.len() as _) };
     }
 
+    /// Open a savepoint of the transaction's storage modifications. The
+    /// savepoints can be nested.
+    pub fn open_savepoint() {
+        unsafe { anoma_tx_open_savepoint() }
+    }
+
+    /// Roll back the transaction's storage modifications to the last open
+    /// savepoint and close it. Returns `false` if there is no open savepoint.
+    pub fn rollback_savepoint() -> bool {
+        let result = unsafe { anoma_tx_rollback_savepoint() };
+        HostEnvResult::is_success(result)
+    }
+
+    /// Close the last open savepoint, keeping the transaction's storage
+    /// modifications. Returns `false` if there is no open savepoint.
+    pub fn release_savepoint() -> bool {
+        let result = unsafe { anoma_tx_release_savepoint() };
+        HostEnvResult::is_success(result)
+    }
+
+    /// Run the given function in a savepoint. The storage modifications of the
+    /// function are rolled back when it returns an error.
+    pub fn with_savepoint<T, E>(
+        f: impl FnOnce() -> Result<T, E>,
+    ) -> Result<T, E> {
+        open_savepoint();
+        let result = f();
+        if result.is_ok() {
+            release_savepoint();
+        } else {
+            rollback_savepoint();
+        }
+        result
+    }
+
     /// Get an iterator with the given prefix
     pub fn iter_prefix<K: AsRef<str>, T: BorshDeserialize>(
         prefix: K,
@@ -173,6 +208,13 @@ pub mod tx {
         }
     }
 
+    /// Log the reason of a failure and abort the transaction. None of its
+    /// storage modifications will be applied.
+    pub fn abort<T: AsRef<str>>(msg: T) -> ! {
+        log_string(msg);
+        panic!()
+    }
+
     /// These host functions are implemented in the Anoma's [`host_env`]
     /// module. The environment provides calls to them via this C interface.
     extern "C" {
@@ -195,6 +237,17 @@ pub mod tx {
         // Delete the given key and its value
         fn anoma_tx_delete(key_ptr: u64, key_len: u64);
 
+        // Open a savepoint of the storage modifications
+        fn anoma_tx_open_savepoint();
+
+        // Roll back the storage modifications to the last open savepoint,
+        // returns 1 on success, -1 if there is no open savepoint.
+        fn anoma_tx_rollback_savepoint() -> i64;
+
+        // Close the last open savepoint, returns 1 on success, -1 if there is
+        // no open savepoint.
+        fn anoma_tx_release_savepoint() -> i64;
+
         // Get an ID of a data iterator with key prefix
         fn anoma_tx_iter_prefix(prefix_ptr: u64, prefix_len: u64) -> u64;
 
@@ -242,10 +295,11 @@ pub mod vp {
     use std::marker::PhantomData;
     pub use std::mem::size_of;
 
+    use anoma_shared::types::address::Address;
     use anoma_shared::types::internal::HostEnvResult;
     use anoma_shared::types::key::ed25519::{PublicKey, Signature};
     use anoma_shared::types::{
-        BlockHash, BlockHeight, BLOCK_HASH_LENGTH, CHAIN_ID_LENGTH,
+        BlockHash, BlockHeight, Key, BLOCK_HASH_LENGTH, CHAIN_ID_LENGTH,
     };
     use anoma_shared::vm::types::KeyVal;
     pub use borsh::{BorshDeserialize, BorshSerialize};
@@ -315,6 +369,15 @@ pub mod vp {
         HostEnvResult::is_success(found)
     }
 
+    /// Check if the account with the given address is being initialized by
+    /// the current transaction, i.e. its validity predicate wasn't in storage
+    /// before the transaction execution.
+    pub fn is_new_account(addr: &Address) -> bool {
+        let key =
+            Key::validity_predicate(addr).expect("Cannot obtain a storage key");
+        !has_key_pre(&key.to_string())
+    }
+
     /// Get an iterator with the given prefix before transaction execution
     pub fn iter_prefix_pre<K: AsRef<str>, T: BorshDeserialize>(
         prefix: K,
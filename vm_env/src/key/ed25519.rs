@@ -8,3 +8,9 @@ pub fn get(owner: &Address) -> Option<PublicKey> {
     let key = ed25519::pk_key(owner).to_string();
     vp::read_pre(&key)
 }
+
+/// Get the recovery public key associated with the given address, if any.
+pub fn get_recovery(owner: &Address) -> Option<PublicKey> {
+    let key = ed25519::recovery_pk_key(owner).to_string();
+    vp::read_pre(&key)
+}
@@ -0,0 +1,38 @@
+use anoma_shared::types::key::ed25519::PublicKey;
+use anoma_shared::types::key::multisig::{self, MultiSignedTxData, Multisig};
+use anoma_shared::types::Address;
+use borsh::BorshDeserialize;
+
+use crate::imports::vp;
+
+/// Get the multi-signature keys set associated with the given address.
+pub fn get(owner: &Address) -> Option<Multisig> {
+    let key = multisig::multisig_key(owner).to_string();
+    vp::read_pre(&key)
+}
+
+/// Check that the transaction data signed by multiple keys contains a valid
+/// signature of the given key. This allows a single key account to be one of
+/// the signers of a transaction that needs the approval of multiple accounts.
+pub fn is_signed_by(pk: &PublicKey, tx_data: &[u8]) -> bool {
+    match MultiSignedTxData::try_from_slice(tx_data) {
+        Ok(tx) => tx.sigs.iter().any(|partial| {
+            &partial.pk == pk
+                && vp::verify_tx_signature(pk, &tx.data, &partial.sig)
+        }),
+        Err(_) => false,
+    }
+}
+
+/// Check that the transaction data has been signed by at least the threshold
+/// of the owner's multi-signature keys.
+pub fn verify_tx(owner: &Address, tx_data: &[u8]) -> bool {
+    let multisig = match get(owner) {
+        Some(multisig) => multisig,
+        None => return false,
+    };
+    match MultiSignedTxData::try_from_slice(tx_data) {
+        Ok(tx) => tx.verify_with(&multisig, vp::verify_tx_signature).is_ok(),
+        Err(_) => false,
+    }
+}
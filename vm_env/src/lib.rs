@@ -1,10 +1,15 @@
 //! This crate contains library code for wasm. Some of the code is re-exported
 //! from the `shared` crate.
 
+mod governance;
+mod htlc;
 pub mod imports;
 mod intent;
 pub mod key;
+mod pos;
+mod storage_usage;
 mod token;
+mod vesting;
 
 pub mod tx_prelude {
     pub use anoma_shared::types::*;
@@ -15,7 +20,7 @@ pub mod tx_prelude {
     pub mod token {
         pub use anoma_shared::types::token::*;
 
-        pub use crate::token::transfer;
+        pub use crate::token::{burn, init_token, mint, transfer};
     }
 
     pub mod intent {
@@ -23,6 +28,41 @@ pub mod tx_prelude {
 
         pub use crate::intent::invalidate_intent;
     }
+
+    pub mod vesting {
+        pub use anoma_shared::types::vesting::*;
+
+        pub use crate::vesting::init_schedule;
+    }
+
+    pub mod htlc {
+        pub use anoma_shared::types::htlc::*;
+
+        pub use crate::htlc::{claim, lock, refund};
+    }
+
+    pub mod pos {
+        pub use anoma_shared::types::pos::*;
+
+        pub use crate::pos::{
+            bond, change_commission, claim_rewards, register_validator, unbond,
+            unjail, withdraw,
+        };
+    }
+
+    pub mod governance {
+        pub use anoma_shared::types::governance::*;
+
+        pub use crate::governance::{submit_proposal, vote};
+    }
+
+    pub mod storage_usage {
+        pub use anoma_shared::ledger::storage_usage::{
+            deposit_payer_key, is_deposit_payer_key,
+        };
+
+        pub use crate::storage_usage::pay_deposit;
+    }
 }
 
 pub mod vp_prelude {
@@ -37,6 +77,12 @@ pub mod vp_prelude {
 
             pub use crate::key::ed25519::*;
         }
+
+        pub mod multisig {
+            pub use anoma_shared::types::key::multisig::*;
+
+            pub use crate::key::multisig::*;
+        }
     }
 
     pub mod token {
@@ -50,6 +96,20 @@ pub mod vp_prelude {
 
         pub use crate::intent::vp;
     }
+
+    pub mod vesting {
+        pub use anoma_shared::types::vesting::*;
+
+        pub use crate::vesting::{
+            get_schedule, is_fully_vested, locked_amount, vp,
+        };
+    }
+
+    pub mod htlc {
+        pub use anoma_shared::types::htlc::*;
+
+        pub use crate::htlc::{get, is_released_to};
+    }
 }
 
 pub mod matchmaker_prelude {
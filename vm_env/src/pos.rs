@@ -0,0 +1,224 @@
+use anoma_shared::types::key::ed25519::PublicKey;
+use anoma_shared::types::pos::{
+    self, Bonds, CommissionRate, Epoch, EpochState, PosParams, TotalBonds,
+    Unbonds,
+};
+use anoma_shared::types::token::{self, Amount};
+use anoma_shared::types::Address;
+
+/// Register the validator with its consensus key and commission rate.
+pub fn register_validator(
+    validator: &Address,
+    consensus_key: &PublicKey,
+    commission_rate: CommissionRate,
+) -> Result<(), pos::Error> {
+    use crate::imports::tx;
+
+    commission_rate.validate()?;
+    let key = pos::consensus_key_key(validator).to_string();
+    if tx::has_key(&key) {
+        return Err(pos::Error::AlreadyRegistered(validator.clone()));
+    }
+    tx::write(&key, consensus_key);
+    tx::write(
+        &pos::commission_rate_key(validator).to_string(),
+        commission_rate,
+    );
+    Ok(())
+}
+
+/// Change a registered validator's commission rate. The new rate applies to
+/// the rewards of the epochs that haven't been distributed yet.
+pub fn change_commission(
+    validator: &Address,
+    commission_rate: CommissionRate,
+) -> Result<(), pos::Error> {
+    use crate::imports::tx;
+
+    commission_rate.validate()?;
+    if !tx::has_key(&pos::consensus_key_key(validator).to_string()) {
+        return Err(pos::Error::NotRegistered(validator.clone()));
+    }
+    tx::write(
+        &pos::commission_rate_key(validator).to_string(),
+        commission_rate,
+    );
+    Ok(())
+}
+
+/// Unjail a jailed validator after its jail period is over. The validator
+/// rejoins the validator set at the end of the current epoch.
+pub fn unjail(validator: &Address) -> Result<(), pos::Error> {
+    use crate::imports::tx;
+
+    let key = pos::jailed_until_key(validator).to_string();
+    let until: Epoch = tx::read(&key)
+        .ok_or_else(|| pos::Error::NotJailed(validator.clone()))?;
+    let (_, epoch) = current_epoch();
+    if epoch < until {
+        return Err(pos::Error::StillJailed(until));
+    }
+    tx::delete::<_, Epoch>(&key);
+    Ok(())
+}
+
+/// Transfer all the owner's rewards from the PoS account to the owner.
+pub fn claim_rewards(owner: &Address) -> Result<Amount, pos::Error> {
+    use crate::imports::tx;
+
+    let rewards_key = pos::rewards_key(owner).to_string();
+    let rewards: Amount = tx::read(&rewards_key).unwrap_or_default();
+    if rewards == Amount::default() {
+        return Err(pos::Error::NothingToClaim);
+    }
+    crate::token::transfer(
+        &pos::pos_address(),
+        owner,
+        &pos::staking_token(),
+        rewards,
+    )
+    .map_err(pos::Error::Token)?;
+    tx::write(&rewards_key, Amount::default());
+    Ok(rewards)
+}
+
+/// Bond the source's staking tokens to a registered validator. The tokens are
+/// transferred to the PoS account.
+pub fn bond(
+    source: &Address,
+    validator: &Address,
+    amount: Amount,
+) -> Result<(), pos::Error> {
+    use crate::imports::tx;
+
+    if !tx::has_key(&pos::consensus_key_key(validator).to_string()) {
+        return Err(pos::Error::NotRegistered(validator.clone()));
+    }
+    crate::token::transfer(
+        source,
+        &pos::pos_address(),
+        &pos::staking_token(),
+        amount,
+    )
+    .map_err(pos::Error::Token)?;
+
+    let bond_key = pos::bond_key(source).to_string();
+    let mut bonds: Bonds = tx::read(&bond_key).unwrap_or_default();
+    bonds
+        .entry(validator.clone())
+        .or_default()
+        .receive(&amount)
+        .map_err(pos::Error::Token)?;
+    let mut total_bonds = read_total_bonds();
+    total_bonds
+        .entry(validator.clone())
+        .or_default()
+        .receive(&amount)
+        .map_err(pos::Error::Token)?;
+    tx::write(&bond_key, bonds);
+    tx::write(&pos::total_bonds_key().to_string(), total_bonds);
+    Ok(())
+}
+
+/// Unbond the source's tokens bonded to a validator. The tokens stay in the
+/// PoS account until they can be withdrawn after the unbonding period.
+pub fn unbond(
+    source: &Address,
+    validator: &Address,
+    amount: Amount,
+) -> Result<(), pos::Error> {
+    use crate::imports::tx;
+
+    let bond_key = pos::bond_key(source).to_string();
+    let mut bonds: Bonds = tx::read(&bond_key).unwrap_or_default();
+    let bonded = bonds.get(validator).copied().unwrap_or_default();
+    let remaining =
+        bonded
+            .checked_sub(&amount)
+            .ok_or(pos::Error::InsufficientBond {
+                bonded,
+                requested: amount,
+            })?;
+    if remaining == Amount::default() {
+        bonds.remove(validator);
+    } else {
+        bonds.insert(validator.clone(), remaining);
+    }
+    let mut total_bonds = read_total_bonds();
+    let total = total_bonds.entry(validator.clone()).or_default();
+    // the total bonds include the source's bond
+    total.spend(&amount).map_err(pos::Error::Token)?;
+    if *total == Amount::default() {
+        total_bonds.remove(validator);
+    }
+
+    let (params, epoch) = current_epoch();
+    let unbond_key = pos::unbond_key(source).to_string();
+    let mut unbonds: Unbonds = tx::read(&unbond_key).unwrap_or_default();
+    unbonds
+        .entry(validator.clone())
+        .or_default()
+        .entry(params.withdrawable_epoch(epoch))
+        .or_default()
+        .receive(&amount)
+        .map_err(pos::Error::Token)?;
+
+    tx::write(&bond_key, bonds);
+    tx::write(&pos::total_bonds_key().to_string(), total_bonds);
+    tx::write(&unbond_key, unbonds);
+    Ok(())
+}
+
+/// Withdraw all the source's unbonded tokens from the validator whose
+/// unbonding period is over back to the source.
+pub fn withdraw(
+    source: &Address,
+    validator: &Address,
+) -> Result<Amount, pos::Error> {
+    use crate::imports::tx;
+
+    let (_, epoch) = current_epoch();
+    let unbond_key = pos::unbond_key(source).to_string();
+    let mut unbonds: Unbonds = tx::read(&unbond_key).unwrap_or_default();
+    let mut withdrawn = Amount::default();
+    if let Some(validator_unbonds) = unbonds.get_mut(validator) {
+        let pending = validator_unbonds.split_off(&(epoch + 1));
+        for amount in validator_unbonds.values() {
+            withdrawn.receive(amount).map_err(pos::Error::Token)?;
+        }
+        *validator_unbonds = pending;
+        if validator_unbonds.is_empty() {
+            unbonds.remove(validator);
+        }
+    }
+    if withdrawn == Amount::default() {
+        return Err(pos::Error::NothingToWithdraw(validator.clone()));
+    }
+    crate::token::transfer(
+        &pos::pos_address(),
+        source,
+        &pos::staking_token(),
+        withdrawn,
+    )
+    .map_err(pos::Error::Token)?;
+    tx::write(&unbond_key, unbonds);
+    Ok(withdrawn)
+}
+
+/// Get the PoS parameters and the current epoch in a transaction.
+pub(crate) fn current_epoch() -> (PosParams, Epoch) {
+    use crate::imports::tx;
+
+    let params: PosParams =
+        tx::read(&pos::params_key().to_string()).unwrap_or_default();
+    let epoch = tx::read(&pos::epoch_key().to_string())
+        .map(|state: EpochState| state.epoch)
+        .unwrap_or_default();
+    (params, epoch)
+}
+
+fn read_total_bonds() -> TotalBonds {
+    use crate::imports::tx;
+
+    tx::read(&pos::total_bonds_key().to_string()).unwrap_or_default()
+}
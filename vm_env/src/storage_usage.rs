@@ -0,0 +1,12 @@
+use anoma_shared::ledger::storage_usage;
+use anoma_shared::types::Address;
+
+/// Declare the account that pays the storage deposits of the transaction. The
+/// payer's validity predicate has to accept the transaction. A transaction
+/// can declare at most one payer.
+pub fn pay_deposit(payer: &Address) {
+    use crate::imports::tx;
+
+    let key = storage_usage::deposit_payer_key(payer);
+    tx::write(&key.to_string(), true)
+}
@@ -1,18 +1,49 @@
 use std::collections::HashSet;
 
-use anoma_shared::types::token::{self, Amount, Change};
+use anoma_shared::types::key::ed25519::SignedTxData;
+use anoma_shared::types::token::{self, Amount, Change, TokenMetadata};
 use anoma_shared::types::{Address, Key};
+use borsh::BorshDeserialize;
 
-/// A token validity predicate.
+/// A token validity predicate. The sum of the balance changes must match the
+/// change of the token's supply. Debits have to be approved by their owners
+/// and any increase of the supply has to be signed by the mint authority.
+///
+/// A token that is being initialized by the transaction, i.e. whose validity
+/// predicate wasn't in storage before, is being set up, so its supply, mint
+/// authority, metadata and validity predicate can be written freely. None of
+/// these except for the supply and the mint authority can be changed
+/// afterwards.
 pub fn vp(
     token: &Address,
+    tx_data: &[u8],
     keys_changed: &[Key],
     verifiers: &HashSet<Address>,
 ) -> bool {
     use crate::imports::vp;
 
+    let supply_key = token::supply_key(token).to_string();
+    let vp_key = Key::validity_predicate(token).expect("expected VP key");
+    let is_new_token = vp::is_new_account(token);
+    // TODO memoize?
+    let authorized =
+        is_new_token || is_signed_by_mint_authority(token, tx_data);
+
     let mut change: Change = 0;
     let all_checked = keys_changed.iter().all(|key| {
+        if token::is_supply_key(token, key) {
+            // checked against the balance changes below
+            return true;
+        }
+        if token::is_mint_authority_key(token, key) {
+            return authorized;
+        }
+        if token::is_metadata_key(token, key)
+            || token::is_registry_key(key) == Some(token)
+            || key == &vp_key
+        {
+            return is_new_token;
+        }
         match token::is_balance_key(token, key) {
             None => {
                 // deny any other keys
@@ -33,33 +64,120 @@ pub fn vp(
             }
         }
     });
-    all_checked && change == 0
+    let pre_supply: Amount = vp::read_pre(&supply_key).unwrap_or_default();
+    let post_supply: Amount = vp::read_post(&supply_key).unwrap_or_default();
+    let supply_change = post_supply.change() - pre_supply.change();
+    // only the mint authority can increase the supply, while anyone can burn
+    // their own tokens
+    all_checked && change == supply_change && (supply_change <= 0 || authorized)
 }
 
-/// A token transfer that can be used in a transaction.
+/// Check that the transaction is signed by the token's mint authority, as it
+/// was before the transaction. The authority can be either a single or a
+/// multi-signature account.
+fn is_signed_by_mint_authority(token: &Address, tx_data: &[u8]) -> bool {
+    use crate::imports::vp;
+
+    let key = token::mint_authority_key(token).to_string();
+    let authority: Address = match vp::read_pre(&key) {
+        Some(authority) => authority,
+        None => return false,
+    };
+    let valid_sig = match (
+        SignedTxData::try_from_slice(tx_data),
+        crate::key::ed25519::get(&authority),
+    ) {
+        (Ok(tx), Some(pk)) => vp::verify_tx_signature(&pk, &tx.data, &tx.sig),
+        _ => false,
+    };
+    valid_sig || crate::key::multisig::verify_tx(&authority, tx_data)
+}
+
+/// A token transfer that can be used in a transaction. Nothing is written
+/// when the source doesn't have enough balance or the target's balance would
+/// overflow.
 pub fn transfer(
     src: &Address,
     dest: &Address,
     token: &Address,
     amount: Amount,
-) {
+) -> Result<(), token::Error> {
     use crate::imports::tx;
 
     let src_key = token::balance_key(token, src);
     let dest_key = token::balance_key(token, dest);
-    let src_bal: Option<Amount> = tx::read(&src_key.to_string());
-    match src_bal {
-        None => {
-            tx::log_string(format!("src {} has no balance", src));
-            unreachable!()
-        }
-        Some(mut src_bal) => {
-            src_bal.spend(&amount);
-            let mut dest_bal: Amount =
-                tx::read(&dest_key.to_string()).unwrap_or_default();
-            dest_bal.receive(&amount);
-            tx::write(&src_key.to_string(), src_bal);
-            tx::write(&dest_key.to_string(), dest_bal);
-        }
+    let mut src_bal: Amount =
+        tx::read(&src_key.to_string()).unwrap_or_default();
+    src_bal.spend(&amount)?;
+    let mut dest_bal: Amount =
+        tx::read(&dest_key.to_string()).unwrap_or_default();
+    // a transfer to self leaves the balance unchanged
+    if src != dest {
+        dest_bal.receive(&amount)?;
+        tx::write(&src_key.to_string(), src_bal);
+        tx::write(&dest_key.to_string(), dest_bal);
     }
+    Ok(())
+}
+
+/// Issue new tokens to the target, increasing the token's supply.
+pub fn mint(
+    token: &Address,
+    target: &Address,
+    amount: Amount,
+) -> Result<(), token::Error> {
+    use crate::imports::tx;
+
+    let supply_key = token::supply_key(token);
+    let target_key = token::balance_key(token, target);
+    let mut supply: Amount =
+        tx::read(&supply_key.to_string()).unwrap_or_default();
+    supply.receive(&amount)?;
+    let mut target_bal: Amount =
+        tx::read(&target_key.to_string()).unwrap_or_default();
+    target_bal.receive(&amount)?;
+    tx::write(&supply_key.to_string(), supply);
+    tx::write(&target_key.to_string(), target_bal);
+    Ok(())
+}
+
+/// Destroy the source's tokens, decreasing the token's supply.
+pub fn burn(
+    token: &Address,
+    source: &Address,
+    amount: Amount,
+) -> Result<(), token::Error> {
+    use crate::imports::tx;
+
+    let supply_key = token::supply_key(token);
+    let source_key = token::balance_key(token, source);
+    let mut source_bal: Amount =
+        tx::read(&source_key.to_string()).unwrap_or_default();
+    source_bal.spend(&amount)?;
+    let mut supply: Amount =
+        tx::read(&supply_key.to_string()).unwrap_or_default();
+    supply.spend(&amount)?;
+    tx::write(&source_key.to_string(), source_bal);
+    tx::write(&supply_key.to_string(), supply);
+    Ok(())
+}
+
+/// Initialize a new token account with zero supply and the given mint
+/// authority and metadata, and add it to the token registry.
+pub fn init_token(
+    mint_authority: &Address,
+    metadata: &TokenMetadata,
+    vp_code: &[u8],
+) -> Address {
+    use crate::imports::tx;
+
+    let token = tx::init_account(vp_code);
+    tx::write(&token::metadata_key(&token).to_string(), metadata);
+    tx::write(&token::registry_key(&token).to_string(), metadata);
+    tx::write(&token::supply_key(&token).to_string(), Amount::default());
+    tx::write(
+        &token::mint_authority_key(&token).to_string(),
+        mint_authority,
+    );
+    token
 }
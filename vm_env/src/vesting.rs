@@ -0,0 +1,64 @@
+use anoma_shared::types::token::{self, Amount};
+use anoma_shared::types::vesting::{self, VestingSchedule};
+use anoma_shared::types::Address;
+
+/// Set up a vesting schedule for the owner's balance of the given token. This
+/// can be used in a transaction that initializes a vesting account.
+pub fn init_schedule(
+    owner: &Address,
+    token: &Address,
+    schedule: &VestingSchedule,
+) {
+    use crate::imports::tx;
+
+    let key = vesting::vesting_key(owner, token);
+    tx::write(&key.to_string(), schedule)
+}
+
+/// Get the vesting schedule of the owner's balance of the given token, as it
+/// was before the transaction.
+pub fn get_schedule(
+    owner: &Address,
+    token: &Address,
+) -> Option<VestingSchedule> {
+    use crate::imports::vp;
+
+    let key = vesting::vesting_key(owner, token);
+    vp::read_pre(&key.to_string())
+}
+
+/// Get the amount of the given token that is still locked in the owner's
+/// balance at the current block height.
+pub fn locked_amount(owner: &Address, token: &Address) -> Amount {
+    use crate::imports::vp;
+
+    match get_schedule(owner, token) {
+        Some(schedule) => schedule.locked_at(vp::get_block_height()),
+        None => Amount::default(),
+    }
+}
+
+/// Check that a debit of the owner's balance of the given token doesn't spend
+/// any of the locked amount.
+pub fn vp(owner: &Address, token: &Address) -> bool {
+    use crate::imports::vp;
+
+    let locked = locked_amount(owner, token);
+    let key = token::balance_key(token, owner).to_string();
+    let post: Amount = vp::read_post(&key).unwrap_or_default();
+    post >= locked
+}
+
+/// Check if all the owner's vesting schedules are fully vested at the current
+/// block height.
+pub fn is_fully_vested(owner: &Address) -> bool {
+    use crate::imports::vp;
+
+    let height = vp::get_block_height();
+    let prefix = vesting::vesting_prefix(owner).to_string();
+    vp::iter_prefix_pre(&prefix).all(
+        |(_key, schedule): (String, VestingSchedule)| {
+            schedule.is_fully_vested(height)
+        },
+    )
+}
@@ -0,0 +1,28 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let batch = token::BatchTransfer::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!("apply_tx called with batch transfer: {:#?}", batch));
+    // the first source pays the storage deposits of the whole batch
+    if let Some(transfer) = batch.transfers.first() {
+        storage_usage::pay_deposit(&transfer.source);
+    }
+
+    for token::Transfer {
+        source,
+        target,
+        token,
+        amount,
+    } in batch.transfers
+    {
+        // a failure of any of the transfers aborts the whole transaction
+        if let Err(err) = token::transfer(&source, &target, &token, amount) {
+            abort(format!(
+                "transfer of {} {} from {} to {} failed: {}",
+                amount, token, source, target, err
+            ))
+        }
+    }
+}
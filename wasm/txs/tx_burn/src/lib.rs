@@ -0,0 +1,17 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let burn = token::Burn::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!("apply_tx called with burn: {:#?}", burn));
+    let token::Burn {
+        token,
+        source,
+        amount,
+    } = burn;
+    storage_usage::pay_deposit(&source);
+    if let Err(err) = token::burn(&token, &source, amount) {
+        abort(format!("burn failed: {}", err))
+    }
+}
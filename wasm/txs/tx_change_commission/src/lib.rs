@@ -0,0 +1,20 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let change =
+        pos::ChangeCommission::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!(
+        "apply_tx called with commission change: {:#?}",
+        change
+    ));
+    let pos::ChangeCommission {
+        validator,
+        commission_rate,
+    } = change;
+    storage_usage::pay_deposit(&validator);
+    if let Err(err) = pos::change_commission(&validator, commission_rate) {
+        abort(format!("commission change failed: {}", err))
+    }
+}
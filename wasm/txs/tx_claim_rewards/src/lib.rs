@@ -0,0 +1,14 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let claim = pos::ClaimRewards::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!("apply_tx called with rewards claim: {:#?}", claim));
+    let pos::ClaimRewards { owner } = claim;
+    storage_usage::pay_deposit(&owner);
+    match pos::claim_rewards(&owner) {
+        Ok(amount) => log_string(format!("claimed {}", amount)),
+        Err(err) => abort(format!("rewards claim failed: {}", err)),
+    }
+}
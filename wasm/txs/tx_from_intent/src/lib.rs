@@ -21,7 +21,9 @@ fn apply_tx(tx_data: Vec<u8>) {
         amount,
     } in tx_data.transfers
     {
-        token::transfer(&source, &target, &token, amount);
+        if let Err(err) = token::transfer(&source, &target, &token, amount) {
+            abort(format!("transfer failed: {}", err))
+        }
     }
 
     tx_data
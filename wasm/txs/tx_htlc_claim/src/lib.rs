@@ -0,0 +1,13 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let claim = htlc::ClaimHtlc::try_from_slice(&tx_data[..]).unwrap();
+    log_string(format!(
+        "apply_tx called with HTLC claim of {}",
+        claim.escrow
+    ));
+    if let Err(err) = htlc::claim(&claim.escrow, &claim.preimage) {
+        abort(format!("claim failed: {}", err))
+    }
+}
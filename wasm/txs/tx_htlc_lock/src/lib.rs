@@ -0,0 +1,15 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let lock = htlc::LockHtlc::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!("apply_tx called with HTLC: {:#?}", lock.htlc));
+    if lock.htlc.deadline <= get_block_height() {
+        abort("the HTLC deadline has already passed")
+    }
+    match htlc::lock(&lock.htlc, &lock.vp_code) {
+        Ok(escrow) => log_string(format!("initialized escrow: {}", escrow)),
+        Err(err) => abort(format!("locking the tokens failed: {}", err)),
+    }
+}
@@ -0,0 +1,13 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let refund = htlc::RefundHtlc::try_from_slice(&tx_data[..]).unwrap();
+    log_string(format!(
+        "apply_tx called with HTLC refund of {}",
+        refund.escrow
+    ));
+    if let Err(err) = htlc::refund(&refund.escrow) {
+        abort(format!("refund failed: {}", err))
+    }
+}
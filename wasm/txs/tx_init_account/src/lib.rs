@@ -0,0 +1,12 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let tx_data = InitAccount::try_from_slice(&signed_data[..]).unwrap();
+    storage_usage::pay_deposit(&tx_data.source);
+    let address = init_account(&tx_data.vp_code);
+    let pk_key = key::ed25519::pk_key(&address);
+    write(&pk_key.to_string(), &tx_data.public_key);
+    log_string(format!("initialized account: {}", address));
+}
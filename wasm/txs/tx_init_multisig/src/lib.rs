@@ -0,0 +1,11 @@
+use anoma_vm_env::tx_prelude::key::multisig::InitMultisigAccount;
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let tx_data = InitMultisigAccount::try_from_slice(&tx_data[..]).unwrap();
+    let address = init_account(&tx_data.vp_code);
+    let multisig_key = key::multisig::multisig_key(&address);
+    write(&multisig_key.to_string(), &tx_data.multisig);
+    log_string(format!("initialized multisig account: {}", address));
+}
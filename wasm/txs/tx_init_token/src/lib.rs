@@ -0,0 +1,12 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let tx_data = token::InitToken::try_from_slice(&tx_data[..]).unwrap();
+    let address = token::init_token(
+        &tx_data.mint_authority,
+        &tx_data.metadata,
+        &tx_data.vp_code,
+    );
+    log_string(format!("initialized token: {}", address));
+}
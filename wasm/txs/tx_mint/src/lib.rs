@@ -0,0 +1,16 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let mint = token::Mint::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!("apply_tx called with mint: {:#?}", mint));
+    let token::Mint {
+        token,
+        target,
+        amount,
+    } = mint;
+    if let Err(err) = token::mint(&token, &target, amount) {
+        abort(format!("mint failed: {}", err))
+    }
+}
@@ -0,0 +1,23 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let registration =
+        pos::RegisterValidator::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!(
+        "apply_tx called with validator registration: {:#?}",
+        registration
+    ));
+    let pos::RegisterValidator {
+        validator,
+        consensus_key,
+        commission_rate,
+    } = registration;
+    storage_usage::pay_deposit(&validator);
+    if let Err(err) =
+        pos::register_validator(&validator, &consensus_key, commission_rate)
+    {
+        abort(format!("validator registration failed: {}", err))
+    }
+}
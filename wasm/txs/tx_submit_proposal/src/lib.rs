@@ -0,0 +1,19 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let submit =
+        governance::SubmitProposal::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!("apply_tx called with proposal: {:#?}", submit));
+    let governance::SubmitProposal {
+        author,
+        title,
+        content,
+    } = submit;
+    storage_usage::pay_deposit(&author);
+    match governance::submit_proposal(&author, title, content) {
+        Ok(id) => log_string(format!("submitted proposal {}", id)),
+        Err(err) => abort(format!("proposal submission failed: {}", err)),
+    }
+}
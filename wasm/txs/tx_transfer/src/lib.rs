@@ -2,9 +2,8 @@ use anoma_vm_env::tx_prelude::*;
 
 #[transaction]
 fn apply_tx(tx_data: Vec<u8>) {
-    let signed =
-        key::ed25519::SignedTxData::try_from_slice(&tx_data[..]).unwrap();
-    let transfer = token::Transfer::try_from_slice(&signed.data[..]).unwrap();
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let transfer = token::Transfer::try_from_slice(&signed_data[..]).unwrap();
     log_string(format!("apply_tx called with transfer: {:#?}", transfer));
     let token::Transfer {
         source,
@@ -12,5 +11,8 @@ fn apply_tx(tx_data: Vec<u8>) {
         token,
         amount,
     } = transfer;
-    token::transfer(&source, &target, &token, amount)
+    storage_usage::pay_deposit(&source);
+    if let Err(err) = token::transfer(&source, &target, &token, amount) {
+        abort(format!("transfer failed: {}", err))
+    }
 }
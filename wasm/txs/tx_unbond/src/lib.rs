@@ -0,0 +1,17 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let unbond = pos::Unbond::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!("apply_tx called with unbond: {:#?}", unbond));
+    let pos::Unbond {
+        source,
+        validator,
+        amount,
+    } = unbond;
+    storage_usage::pay_deposit(&source);
+    if let Err(err) = pos::unbond(&source, &validator, amount) {
+        abort(format!("unbond failed: {}", err))
+    }
+}
@@ -0,0 +1,13 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let unjail = pos::Unjail::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!("apply_tx called with unjail: {:#?}", unjail));
+    let pos::Unjail { validator } = unjail;
+    storage_usage::pay_deposit(&validator);
+    if let Err(err) = pos::unjail(&validator) {
+        abort(format!("unjail failed: {}", err))
+    }
+}
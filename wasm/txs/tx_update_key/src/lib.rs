@@ -0,0 +1,23 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let update_key =
+        key::ed25519::UpdateKey::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!("update key for: {:#?}", update_key.addr));
+    let key::ed25519::UpdateKey {
+        addr,
+        pk,
+        recovery_pk,
+    } = update_key;
+    if let Some(pk) = pk {
+        write(key::ed25519::pk_key(&addr).to_string(), pk);
+    }
+    if let Some(recovery_pk) = recovery_pk {
+        write(
+            key::ed25519::recovery_pk_key(&addr).to_string(),
+            recovery_pk,
+        );
+    }
+}
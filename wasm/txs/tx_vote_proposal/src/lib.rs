@@ -0,0 +1,18 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let vote =
+        governance::VoteProposal::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!("apply_tx called with vote: {:#?}", vote));
+    let governance::VoteProposal {
+        voter,
+        proposal_id,
+        vote,
+    } = vote;
+    storage_usage::pay_deposit(&voter);
+    if let Err(err) = governance::vote(&voter, proposal_id, vote) {
+        abort(format!("vote failed: {}", err))
+    }
+}
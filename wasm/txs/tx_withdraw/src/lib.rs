@@ -0,0 +1,14 @@
+use anoma_vm_env::tx_prelude::*;
+
+#[transaction]
+fn apply_tx(tx_data: Vec<u8>) {
+    let signed_data = key::multisig::signed_data(&tx_data[..]).unwrap();
+    let withdraw = pos::Withdraw::try_from_slice(&signed_data[..]).unwrap();
+    log_string(format!("apply_tx called with withdrawal: {:#?}", withdraw));
+    let pos::Withdraw { source, validator } = withdraw;
+    storage_usage::pay_deposit(&source);
+    match pos::withdraw(&source, &validator) {
+        Ok(amount) => log_string(format!("withdrawn {}", amount)),
+        Err(err) => abort(format!("withdrawal failed: {}", err)),
+    }
+}
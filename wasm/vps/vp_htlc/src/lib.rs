@@ -0,0 +1,310 @@
+use std::collections::HashSet;
+
+use anoma_vm_env::vp_prelude::htlc::{ClaimHtlc, Htlc};
+use anoma_vm_env::vp_prelude::*;
+
+enum KeyType<'a> {
+    Token {
+        token: &'a Address,
+        owner: &'a Address,
+    },
+    Htlc(&'a Address),
+    Preimage(&'a Address),
+    Unknown,
+}
+
+impl<'a> From<&'a Key> for KeyType<'a> {
+    fn from(key: &'a Key) -> KeyType<'a> {
+        if let Some(owner) = token::is_any_token_balance_key(key) {
+            match key.segments.first() {
+                Some(DbKeySeg::AddressSeg(token)) => {
+                    Self::Token { token, owner }
+                }
+                _ => Self::Unknown,
+            }
+        } else if let Some(escrow) = htlc::is_htlc_key(key) {
+            Self::Htlc(escrow)
+        } else if let Some(escrow) = htlc::is_preimage_key(key) {
+            Self::Preimage(escrow)
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+#[validity_predicate]
+fn validate_tx(
+    tx_data: Vec<u8>,
+    addr: Address,
+    keys_changed: Vec<Key>,
+    verifiers: HashSet<Address>,
+) -> bool {
+    log_string(format!(
+        "validate_tx called with escrow addr: {}, key_changed: {:#?}, \
+         verifiers: {:?}",
+        addr, keys_changed, verifiers
+    ));
+
+    // The transaction that initializes the account sets up its terms and
+    // locks the tokens. An existing account without terms cannot release
+    // anything.
+    if is_new_account(&addr) {
+        log_string("a new escrow account".to_string());
+        return true;
+    }
+    let htlc = match htlc::get(&addr) {
+        Some(htlc) => htlc,
+        None => {
+            log_string("an escrow account without terms".to_string());
+            return false;
+        }
+    };
+
+    let height = get_block_height();
+    let valid_claim = is_valid_claim(&addr, &htlc, &tx_data[..], height);
+    // The escrowed tokens can only be released to the beneficiary with a
+    // valid claim, or to the sender from the deadline height on
+    let recipient = if valid_claim {
+        Some(&htlc.beneficiary)
+    } else if htlc.can_refund_at(height) {
+        Some(&htlc.sender)
+    } else {
+        None
+    };
+
+    for key in keys_changed.iter() {
+        let is_valid = match KeyType::from(key) {
+            KeyType::Token { token, owner } if owner == &addr => {
+                let key = key.to_string();
+                let pre: token::Amount = read_pre(&key).unwrap_or_default();
+                let post: token::Amount = read_post(&key).unwrap_or_default();
+                let change = post.change() - pre.change();
+                // anyone can credit the escrow, but a debit has to be
+                // released in full to the recipient
+                let is_valid = change >= 0
+                    || recipient
+                        .map(|recipient| {
+                            htlc::is_released_to(&addr, recipient, token)
+                        })
+                        .unwrap_or(false);
+                log_string(format!(
+                    "token key: {}, change: {}, height: {}, valid claim: {}, \
+                     recipient: {:?}, valid modification: {}",
+                    key, change, height.0, valid_claim, recipient, is_valid
+                ));
+                is_valid
+            }
+            KeyType::Htlc(escrow) if escrow == &addr => {
+                log_string(format!("HTLC key {} cannot be modified", key));
+                false
+            }
+            KeyType::Preimage(escrow) if escrow == &addr => {
+                // the preimage can only be revealed by a valid claim
+                valid_claim
+            }
+            _ => {
+                log_string(format!("Unknown key {} modified", key));
+                false
+            }
+        };
+        if !is_valid {
+            log_string(format!("key {} modification failed vp", key));
+            return false;
+        }
+    }
+    true
+}
+
+/// Check that the transaction is a claim of this escrow before the deadline
+/// with the preimage of its hash lock, which is also the revealed preimage.
+fn is_valid_claim(
+    addr: &Address,
+    htlc: &Htlc,
+    tx_data: &[u8],
+    height: BlockHeight,
+) -> bool {
+    let claim = match ClaimHtlc::try_from_slice(tx_data) {
+        Ok(claim) => claim,
+        Err(_) => return false,
+    };
+    let revealed: Option<Vec<u8>> =
+        read_post(htlc::preimage_key(addr).to_string());
+    &claim.escrow == addr
+        && htlc.can_claim_at(height)
+        && htlc.is_valid_preimage(&claim.preimage)
+        && revealed.as_ref() == Some(&claim.preimage)
+}
+
+#[cfg(test)]
+mod tests {
+    use anoma_tests::vp::*;
+
+    use super::*;
+
+    /// Test that no-op transaction (i.e. no storage modifications) is deemed
+    /// valid.
+    #[test]
+    fn test_no_op_transaction() {
+        let mut env = TestVpEnv::default();
+        init_vp_env(&mut env);
+
+        let tx_data: Vec<u8> = vec![];
+        let addr: Address = env.addr.clone();
+        let keys_changed: Vec<Key> = vec![];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        let valid = validate_tx(tx_data, addr, keys_changed, verifiers);
+
+        assert!(valid);
+    }
+
+    /// Set up an existing escrow account in storage with the given terms and
+    /// the locked amount. Returns the keys of the escrow's balance and of its
+    /// preimage.
+    fn init_escrow(env: &mut TestVpEnv, htlc: &Htlc) -> (Key, Key) {
+        let addr = env.addr.clone();
+        let balance_key = token::balance_key(&htlc.token, &addr);
+        env.storage
+            .write(&Key::validity_predicate(&addr).unwrap(), vec![])
+            .expect("write failed");
+        env.storage
+            .write(&htlc::htlc_key(&addr), htlc.try_to_vec().unwrap())
+            .expect("write failed");
+        env.storage
+            .write(&balance_key, htlc.amount.try_to_vec().unwrap())
+            .expect("write failed");
+        (balance_key, htlc::preimage_key(&addr))
+    }
+
+    fn escrow_terms(preimage: &[u8]) -> Htlc {
+        Htlc {
+            sender: address::testing::established_address_2(),
+            beneficiary: address::testing::established_address_3(),
+            token: address::xan(),
+            amount: token::Amount::from(100),
+            hash_lock: htlc::hash_lock(preimage),
+            deadline: BlockHeight(10),
+        }
+    }
+
+    /// Test that the tokens can only be claimed with the preimage of the hash
+    /// lock.
+    #[test]
+    fn test_claim() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let preimage = b"secret".to_vec();
+        let wrong_preimage = b"guess".to_vec();
+        let htlc = escrow_terms(&preimage);
+        let (balance_key, preimage_key) = init_escrow(&mut env, &htlc);
+        env.storage.block.height = BlockHeight(5);
+        // the tokens are released to the beneficiary
+        env.write_log.write(
+            &balance_key,
+            token::Amount::default().try_to_vec().unwrap(),
+        );
+        env.write_log.write(
+            &token::balance_key(&htlc.token, &htlc.beneficiary),
+            htlc.amount.try_to_vec().unwrap(),
+        );
+        env.write_log
+            .write(&preimage_key, wrong_preimage.try_to_vec().unwrap());
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![balance_key, preimage_key.clone()];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        // a claim without the preimage is rejected
+        let tx_data = ClaimHtlc {
+            escrow: addr.clone(),
+            preimage: wrong_preimage,
+        }
+        .try_to_vec()
+        .unwrap();
+        let valid = validate_tx(
+            tx_data,
+            addr.clone(),
+            keys_changed.clone(),
+            verifiers.clone(),
+        );
+        assert!(!valid);
+
+        // a claim with the preimage is accepted
+        env.write_log
+            .write(&preimage_key, preimage.try_to_vec().unwrap());
+        init_vp_env(&mut env);
+        let tx_data = ClaimHtlc {
+            escrow: addr.clone(),
+            preimage,
+        }
+        .try_to_vec()
+        .unwrap();
+        let valid = validate_tx(tx_data, addr, keys_changed, verifiers);
+        assert!(valid);
+    }
+
+    /// Test that the tokens can only be refunded from the deadline height on.
+    #[test]
+    fn test_refund() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let htlc = escrow_terms(b"secret");
+        let (balance_key, _preimage_key) = init_escrow(&mut env, &htlc);
+        env.storage.block.height = BlockHeight(5);
+        // the tokens are released to the sender
+        env.write_log.write(
+            &balance_key,
+            token::Amount::default().try_to_vec().unwrap(),
+        );
+        env.write_log.write(
+            &token::balance_key(&htlc.token, &htlc.sender),
+            htlc.amount.try_to_vec().unwrap(),
+        );
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![balance_key];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        // a refund before the deadline is rejected
+        let valid = validate_tx(
+            vec![],
+            addr.clone(),
+            keys_changed.clone(),
+            verifiers.clone(),
+        );
+        assert!(!valid);
+
+        // a refund at the deadline is accepted
+        env.storage.block.height = htlc.deadline;
+        init_vp_env(&mut env);
+        let valid = validate_tx(vec![], addr, keys_changed, verifiers);
+        assert!(valid);
+    }
+
+    /// Test that the balance of an existing account without terms cannot be
+    /// debited.
+    #[test]
+    fn test_existing_account_without_terms() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let balance_key = token::balance_key(&address::xan(), &addr);
+        env.storage
+            .write(&Key::validity_predicate(&addr).unwrap(), vec![])
+            .expect("write failed");
+        env.storage
+            .write(&balance_key, token::Amount::from(100).try_to_vec().unwrap())
+            .expect("write failed");
+        env.write_log.write(
+            &balance_key,
+            token::Amount::default().try_to_vec().unwrap(),
+        );
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![balance_key];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        let valid = validate_tx(vec![], addr, keys_changed, verifiers);
+
+        assert!(!valid);
+    }
+}
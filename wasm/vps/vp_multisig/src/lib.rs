@@ -0,0 +1,261 @@
+use std::collections::HashSet;
+
+use anoma_vm_env::vp_prelude::key::multisig::Multisig;
+use anoma_vm_env::vp_prelude::*;
+
+enum KeyType<'a> {
+    Token(&'a Address),
+    Multisig(&'a Address),
+    Unknown,
+}
+
+impl<'a> From<&'a Key> for KeyType<'a> {
+    fn from(key: &'a Key) -> KeyType<'a> {
+        if let Some(address) = token::is_any_token_balance_key(key) {
+            Self::Token(address)
+        } else if let Some(address) = key::multisig::is_multisig_key(key) {
+            Self::Multisig(address)
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+#[validity_predicate]
+fn validate_tx(
+    tx_data: Vec<u8>,
+    addr: Address,
+    keys_changed: Vec<Key>,
+    verifiers: HashSet<Address>,
+) -> bool {
+    log_string(format!(
+        "validate_tx called with multisig addr: {}, key_changed: {:#?}, \
+         verifiers: {:?}",
+        addr, keys_changed, verifiers
+    ));
+
+    let multisig_key = key::multisig::multisig_key(&addr).to_string();
+
+    // The transaction that initializes the account has to set up its keys
+    // set and it can write the rest of its storage freely. An existing account
+    // without a keys set cannot authorize anything.
+    if key::multisig::get(&addr).is_none() {
+        let is_new_account = is_new_account(&addr);
+        let keys: Option<Multisig> = read_post(&multisig_key);
+        let valid_keys =
+            keys.map(|keys| keys.validate().is_ok()).unwrap_or(false);
+        log_string(format!(
+            "no multisig keys set, new account {}, valid keys {}",
+            is_new_account, valid_keys
+        ));
+        return is_new_account && valid_keys;
+    }
+
+    let valid_sig = key::multisig::verify_tx(&addr, &tx_data[..]);
+
+    for key in keys_changed.iter() {
+        let is_valid = match KeyType::from(key) {
+            KeyType::Token(owner) if owner == &addr => {
+                let key = key.to_string();
+                let pre: token::Amount = read_pre(&key).unwrap_or_default();
+                let post: token::Amount = read_post(&key).unwrap_or_default();
+                let change = post.change() - pre.change();
+                log_string(format!(
+                    "token key: {}, change: {}, valid_sig: {}, valid \
+                     modification: {}",
+                    key,
+                    change,
+                    valid_sig,
+                    change >= 0 || valid_sig
+                ));
+                // debit has to be signed, credit doesn't
+                change >= 0 || valid_sig
+            }
+            KeyType::Multisig(owner) if owner == &addr => {
+                // the new keys set has to be signed by the current keys and it
+                // must be valid
+                let keys: Option<Multisig> = read_post(&multisig_key);
+                let valid_keys =
+                    keys.map(|keys| keys.validate().is_ok()).unwrap_or(false);
+                log_string(format!(
+                    "multisig keys update, valid_sig {}, valid keys {}",
+                    valid_sig, valid_keys
+                ));
+                valid_sig && valid_keys
+            }
+            KeyType::Token(_owner) | KeyType::Multisig(_owner) => {
+                log_string(format!(
+                    "key {} is not of owner, valid_sig {}",
+                    key, valid_sig
+                ));
+                valid_sig
+            }
+            KeyType::Unknown => {
+                log_string(format!(
+                    "Unknown key modified, valid sig {}",
+                    valid_sig
+                ));
+                valid_sig
+            }
+        };
+        if !is_valid {
+            log_string(format!("key {} modification failed vp", key));
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use anoma_tests::vp::*;
+    use anoma_vm_env::vp_prelude::key::multisig::{
+        MultiSignedTxData, PartialSignature,
+    };
+
+    use super::*;
+
+    /// Test that no-op transaction (i.e. no storage modifications) is deemed
+    /// valid.
+    #[test]
+    fn test_no_op_transaction() {
+        let mut env = TestVpEnv::default();
+        init_vp_env(&mut env);
+
+        let tx_data: Vec<u8> = vec![];
+        let addr: Address = env.addr.clone();
+        let keys_changed: Vec<Key> = vec![];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        let valid = validate_tx(tx_data, addr, keys_changed, verifiers);
+
+        assert!(valid);
+    }
+
+    /// Test that a debit has to be signed by at least the threshold of the
+    /// account's keys.
+    #[test]
+    fn test_signatures_threshold() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let keypair_1 = key::ed25519::testing::keypair_1();
+        let keypair_2 = key::ed25519::testing::keypair_2();
+        let multisig = Multisig::new(
+            vec![
+                key::ed25519::PublicKey::from(keypair_1.public),
+                key::ed25519::PublicKey::from(keypair_2.public),
+            ],
+            2,
+        )
+        .unwrap();
+        let balance_key = token::balance_key(&address::xan(), &addr);
+        env.storage
+            .write(&Key::validity_predicate(&addr).unwrap(), vec![])
+            .expect("write failed");
+        env.storage
+            .write(
+                &key::multisig::multisig_key(&addr),
+                multisig.try_to_vec().unwrap(),
+            )
+            .expect("write failed");
+        env.storage
+            .write(&balance_key, token::Amount::from(100).try_to_vec().unwrap())
+            .expect("write failed");
+        env.write_log
+            .write(&balance_key, token::Amount::from(50).try_to_vec().unwrap());
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![balance_key];
+        let verifiers: HashSet<Address> = HashSet::default();
+        let data: Vec<u8> = vec![];
+        let sig_1 = PartialSignature::new(&keypair_1, &data, &env.tx_code);
+        let sig_2 = PartialSignature::new(&keypair_2, &data, &env.tx_code);
+
+        // a signature below the threshold is rejected, even if repeated
+        let tx_data = MultiSignedTxData::new(
+            data.clone(),
+            vec![sig_1.clone(), sig_1.clone()],
+        )
+        .try_to_vec()
+        .unwrap();
+        let valid = validate_tx(
+            tx_data,
+            addr.clone(),
+            keys_changed.clone(),
+            verifiers.clone(),
+        );
+        assert!(!valid);
+
+        // with the threshold of signatures, the debit is accepted
+        let tx_data = MultiSignedTxData::new(data, vec![sig_1, sig_2])
+            .try_to_vec()
+            .unwrap();
+        let valid = validate_tx(tx_data, addr, keys_changed, verifiers);
+        assert!(valid);
+    }
+
+    /// Test that the transaction that initializes the account has to set up a
+    /// valid keys set.
+    #[test]
+    fn test_new_account_keys() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let multisig_key = key::multisig::multisig_key(&addr);
+        let pk = key::ed25519::PublicKey::from(
+            key::ed25519::testing::keypair_1().public,
+        );
+        let invalid_multisig = Multisig {
+            public_keys: vec![pk.clone()],
+            threshold: 0,
+        };
+        env.write_log
+            .write(&multisig_key, invalid_multisig.try_to_vec().unwrap());
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![multisig_key.clone()];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        // an invalid keys set is rejected
+        let valid = validate_tx(
+            vec![],
+            addr.clone(),
+            keys_changed.clone(),
+            verifiers.clone(),
+        );
+        assert!(!valid);
+
+        // a valid keys set is accepted
+        let multisig = Multisig::new(vec![pk], 1).unwrap();
+        env.write_log
+            .write(&multisig_key, multisig.try_to_vec().unwrap());
+        init_vp_env(&mut env);
+        let valid = validate_tx(vec![], addr, keys_changed, verifiers);
+        assert!(valid);
+    }
+
+    /// Test that the storage of an existing account without a keys set cannot
+    /// be modified.
+    #[test]
+    fn test_existing_account_without_keys() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let multisig_key = key::multisig::multisig_key(&addr);
+        let pk = key::ed25519::PublicKey::from(
+            key::ed25519::testing::keypair_1().public,
+        );
+        let multisig = Multisig::new(vec![pk], 1).unwrap();
+        env.storage
+            .write(&Key::validity_predicate(&addr).unwrap(), vec![])
+            .expect("write failed");
+        env.write_log
+            .write(&multisig_key, multisig.try_to_vec().unwrap());
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![multisig_key];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        let valid = validate_tx(vec![], addr, keys_changed, verifiers);
+
+        assert!(!valid);
+    }
+}
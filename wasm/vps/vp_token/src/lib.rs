@@ -15,5 +15,5 @@ fn validate_tx(
         addr, keys_changed, tx_data, verifiers
     ));
 
-    token::vp(&addr, &keys_changed, &verifiers)
+    token::vp(&addr, &tx_data, &keys_changed, &verifiers)
 }
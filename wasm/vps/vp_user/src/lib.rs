@@ -7,6 +7,9 @@ use anoma_vm_env::vp_prelude::*;
 enum KeyType<'a> {
     Token(&'a Address),
     InvalidIntentSet(&'a Address),
+    Pk(&'a Address),
+    RecoveryPk(&'a Address),
+    Vp(&'a Address),
     Unknown,
 }
 
@@ -16,6 +19,12 @@ impl<'a> From<&'a Key> for KeyType<'a> {
             Self::Token(address)
         } else if let Some(address) = intent::is_invalid_intent_key(key) {
             Self::InvalidIntentSet(address)
+        } else if let Some(address) = key::ed25519::is_pk_key(key) {
+            Self::Pk(address)
+        } else if let Some(address) = key::ed25519::is_recovery_pk_key(key) {
+            Self::RecoveryPk(address)
+        } else if let Some(address) = key.is_validity_predicate() {
+            Self::Vp(address)
         } else {
             Self::Unknown
         }
@@ -35,15 +44,26 @@ fn validate_tx(
         addr, keys_changed, verifiers
     ));
 
+    let signed = SignedTxData::try_from_slice(&tx_data[..]).ok();
+
+    let pk = key::ed25519::get(&addr);
+    // The key of an account that is being initialized by this transaction can
+    // be set without a signature. An existing account without a public key
+    // cannot authorize anything.
+    let is_new_account = is_new_account(&addr);
+
     // TODO memoize?
-    let valid_sig = match SignedTxData::try_from_slice(&tx_data[..]) {
-        Ok(tx) => {
-            let pk = key::ed25519::get(&addr);
-            match pk {
-                None => false,
-                Some(pk) => verify_tx_signature(&pk, &tx.data, &tx.sig),
-            }
-        }
+    let valid_sig = match (&signed, pk) {
+        (Some(tx), Some(pk)) => verify_tx_signature(&pk, &tx.data, &tx.sig),
+        // the account may be one of multiple signers
+        (None, Some(pk)) => key::multisig::is_signed_by(&pk, &tx_data[..]),
+        _ => false,
+    };
+
+    let recovery_pk = key::ed25519::get_recovery(&addr);
+    let has_recovery_pk = recovery_pk.is_some();
+    let valid_recovery_sig = match (&signed, recovery_pk) {
+        (Some(tx), Some(pk)) => verify_tx_signature(&pk, &tx.data, &tx.sig),
         _ => false,
     };
 
@@ -83,7 +103,48 @@ fn validate_tx(
                 ));
                 pre.len() + 1 == post.len()
             }
-            KeyType::Token(_owner) | KeyType::InvalidIntentSet(_owner) => {
+            KeyType::Pk(owner) if owner == &addr => {
+                // the key can be set on a new account or rotated with a
+                // signature of the current key or of the recovery key, but it
+                // cannot be removed
+                let post: Option<key::ed25519::PublicKey> =
+                    read_post(key.to_string());
+                log_string(format!(
+                    "public key rotation, new account {}, valid_sig {}, \
+                     valid_recovery_sig {}, has new key {}",
+                    is_new_account,
+                    valid_sig,
+                    valid_recovery_sig,
+                    post.is_some()
+                ));
+                post.is_some()
+                    && (is_new_account || valid_sig || valid_recovery_sig)
+            }
+            KeyType::RecoveryPk(owner) if owner == &addr => {
+                // once set, the recovery key can only be changed with its own
+                // signature
+                log_string(format!(
+                    "recovery key update, valid_sig {}, valid_recovery_sig {}",
+                    valid_sig, valid_recovery_sig
+                ));
+                if has_recovery_pk {
+                    valid_recovery_sig
+                } else {
+                    valid_sig
+                }
+            }
+            KeyType::Vp(owner) if owner == &addr => {
+                log_string(format!(
+                    "VP update, new account {}, valid_sig {}",
+                    is_new_account, valid_sig
+                ));
+                is_new_account || valid_sig
+            }
+            KeyType::Token(_owner)
+            | KeyType::InvalidIntentSet(_owner)
+            | KeyType::Pk(_owner)
+            | KeyType::RecoveryPk(_owner)
+            | KeyType::Vp(_owner) => {
                 log_string(format!(
                     "key {} is not of owner, valid_sig {}",
                     key, valid_sig
@@ -206,4 +267,192 @@ mod tests {
 
         assert!(valid);
     }
+
+    /// Test that the public key can only be rotated with a signature of the
+    /// current key.
+    #[test]
+    fn test_pk_rotation() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let pk_key = key::ed25519::pk_key(&addr);
+        let keypair = key::ed25519::testing::keypair_1();
+        let old_pk = key::ed25519::PublicKey::from(keypair.public);
+        let new_pk = key::ed25519::PublicKey::from(
+            key::ed25519::testing::keypair_2().public,
+        );
+        env.storage
+            .write(&Key::validity_predicate(&addr).unwrap(), vec![])
+            .expect("write failed");
+        env.storage
+            .write(&pk_key, old_pk.try_to_vec().unwrap())
+            .expect("write failed");
+        env.write_log.write(&pk_key, new_pk.try_to_vec().unwrap());
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![pk_key];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        // without a signature, the rotation is rejected
+        let tx_data: Vec<u8> = vec![];
+        let valid = validate_tx(
+            tx_data,
+            addr.clone(),
+            keys_changed.clone(),
+            verifiers.clone(),
+        );
+        assert!(!valid);
+
+        // signed with the current key, the rotation is accepted
+        let tx_data = SignedTxData::new(&keypair, vec![], &env.tx_code)
+            .try_to_vec()
+            .unwrap();
+        let valid = validate_tx(tx_data, addr, keys_changed, verifiers);
+        assert!(valid);
+    }
+
+    /// Test that the public key can be rotated with a signature of the
+    /// recovery key, e.g. when the current key has been lost.
+    #[test]
+    fn test_pk_rotation_with_recovery_key() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let pk_key = key::ed25519::pk_key(&addr);
+        let recovery_pk_key = key::ed25519::recovery_pk_key(&addr);
+        let keypair = key::ed25519::testing::keypair_1();
+        let recovery_keypair = key::ed25519::testing::keypair_2();
+        let old_pk = key::ed25519::PublicKey::from(keypair.public);
+        let recovery_pk =
+            key::ed25519::PublicKey::from(recovery_keypair.public);
+        env.storage
+            .write(&Key::validity_predicate(&addr).unwrap(), vec![])
+            .expect("write failed");
+        env.storage
+            .write(&pk_key, old_pk.try_to_vec().unwrap())
+            .expect("write failed");
+        env.storage
+            .write(&recovery_pk_key, recovery_pk.try_to_vec().unwrap())
+            .expect("write failed");
+        // the recovery key becomes the new current key
+        env.write_log
+            .write(&pk_key, recovery_pk.try_to_vec().unwrap());
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![pk_key];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        // without a signature, the rotation is rejected
+        let valid = validate_tx(
+            vec![],
+            addr.clone(),
+            keys_changed.clone(),
+            verifiers.clone(),
+        );
+        assert!(!valid);
+
+        // signed with the recovery key, the rotation is accepted
+        let tx_data =
+            SignedTxData::new(&recovery_keypair, vec![], &env.tx_code)
+                .try_to_vec()
+                .unwrap();
+        let valid = validate_tx(tx_data, addr, keys_changed, verifiers);
+        assert!(valid);
+    }
+
+    /// Test that once set, the recovery key can only be replaced with its own
+    /// signature.
+    #[test]
+    fn test_recovery_pk_update() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let pk_key = key::ed25519::pk_key(&addr);
+        let recovery_pk_key = key::ed25519::recovery_pk_key(&addr);
+        let keypair = key::ed25519::testing::keypair_1();
+        let recovery_keypair = key::ed25519::testing::keypair_2();
+        let pk = key::ed25519::PublicKey::from(keypair.public);
+        let recovery_pk =
+            key::ed25519::PublicKey::from(recovery_keypair.public);
+        env.storage
+            .write(&Key::validity_predicate(&addr).unwrap(), vec![])
+            .expect("write failed");
+        env.storage
+            .write(&pk_key, pk.try_to_vec().unwrap())
+            .expect("write failed");
+        env.storage
+            .write(&recovery_pk_key, recovery_pk.try_to_vec().unwrap())
+            .expect("write failed");
+        env.write_log
+            .write(&recovery_pk_key, pk.try_to_vec().unwrap());
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![recovery_pk_key];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        // signed with the current key, the update is rejected
+        let tx_data = SignedTxData::new(&keypair, vec![], &env.tx_code)
+            .try_to_vec()
+            .unwrap();
+        let valid = validate_tx(
+            tx_data,
+            addr.clone(),
+            keys_changed.clone(),
+            verifiers.clone(),
+        );
+        assert!(!valid);
+
+        // signed with the recovery key, the update is accepted
+        let tx_data =
+            SignedTxData::new(&recovery_keypair, vec![], &env.tx_code)
+                .try_to_vec()
+                .unwrap();
+        let valid = validate_tx(tx_data, addr, keys_changed, verifiers);
+        assert!(valid);
+    }
+
+    /// Test that the public key of a new account can be set without a
+    /// signature.
+    #[test]
+    fn test_new_account_pk() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let pk_key = key::ed25519::pk_key(&addr);
+        let pk = key::ed25519::PublicKey::from(
+            key::ed25519::testing::keypair_1().public,
+        );
+        env.write_log.write(&pk_key, pk.try_to_vec().unwrap());
+        init_vp_env(&mut env);
+
+        let tx_data: Vec<u8> = vec![];
+        let keys_changed: Vec<Key> = vec![pk_key];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        let valid = validate_tx(tx_data, addr, keys_changed, verifiers);
+
+        assert!(valid);
+    }
+
+    /// Test that the public key and the validity predicate of an existing
+    /// account without a public key cannot be set without a signature.
+    #[test]
+    fn test_existing_account_without_pk() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let pk_key = key::ed25519::pk_key(&addr);
+        let vp_key = Key::validity_predicate(&addr).unwrap();
+        let pk = key::ed25519::PublicKey::from(
+            key::ed25519::testing::keypair_1().public,
+        );
+        env.storage.write(&vp_key, vec![]).expect("write failed");
+        env.write_log.write(&pk_key, pk.try_to_vec().unwrap());
+        env.write_log.write(&vp_key, vec![1]);
+        init_vp_env(&mut env);
+
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        let valid =
+            validate_tx(vec![], addr.clone(), vec![pk_key], verifiers.clone());
+        assert!(!valid);
+
+        let valid = validate_tx(vec![], addr, vec![vp_key], verifiers);
+        assert!(!valid);
+    }
 }
@@ -0,0 +1,284 @@
+use std::collections::HashSet;
+
+use anoma_vm_env::vp_prelude::key::ed25519::SignedTxData;
+use anoma_vm_env::vp_prelude::*;
+
+enum KeyType<'a> {
+    Token {
+        token: &'a Address,
+        owner: &'a Address,
+    },
+    Vesting(&'a Address),
+    Vp(&'a Address),
+    Unknown,
+}
+
+impl<'a> From<&'a Key> for KeyType<'a> {
+    fn from(key: &'a Key) -> KeyType<'a> {
+        if let Some(owner) = token::is_any_token_balance_key(key) {
+            match key.segments.first() {
+                Some(DbKeySeg::AddressSeg(token)) => {
+                    Self::Token { token, owner }
+                }
+                _ => Self::Unknown,
+            }
+        } else if let Some((owner, _token)) = vesting::is_vesting_key(key) {
+            Self::Vesting(owner)
+        } else if let Some(address) = key.is_validity_predicate() {
+            Self::Vp(address)
+        } else {
+            Self::Unknown
+        }
+    }
+}
+
+#[validity_predicate]
+fn validate_tx(
+    tx_data: Vec<u8>,
+    addr: Address,
+    keys_changed: Vec<Key>,
+    verifiers: HashSet<Address>,
+) -> bool {
+    log_string(format!(
+        "validate_tx called with vesting addr: {}, key_changed: {:#?}, \
+         verifiers: {:?}",
+        addr, keys_changed, verifiers
+    ));
+
+    // The transaction that initializes the account sets up its storage,
+    // including the public key and the vesting schedules
+    if is_new_account(&addr) {
+        log_string("a new vesting account".to_string());
+        return true;
+    }
+
+    // An existing account without a public key cannot authorize anything
+    let pk = key::ed25519::get(&addr);
+    let valid_sig = match (SignedTxData::try_from_slice(&tx_data[..]), pk) {
+        (Ok(tx), Some(pk)) => verify_tx_signature(&pk, &tx.data, &tx.sig),
+        _ => false,
+    };
+
+    for key in keys_changed.iter() {
+        let is_valid = match KeyType::from(key) {
+            KeyType::Token { token, owner } if owner == &addr => {
+                let key = key.to_string();
+                let pre: token::Amount = read_pre(&key).unwrap_or_default();
+                let post: token::Amount = read_post(&key).unwrap_or_default();
+                let change = post.change() - pre.change();
+                // debit has to be signed and it cannot spend the locked amount,
+                // credit doesn't
+                let is_valid =
+                    change >= 0 || (valid_sig && vesting::vp(&addr, token));
+                log_string(format!(
+                    "token key: {}, change: {}, valid_sig: {}, locked: {:?}, \
+                     valid modification: {}",
+                    key,
+                    change,
+                    valid_sig,
+                    vesting::locked_amount(&addr, token),
+                    is_valid
+                ));
+                is_valid
+            }
+            KeyType::Vesting(owner) if owner == &addr => {
+                log_string(format!(
+                    "vesting schedule key {} cannot be modified",
+                    key
+                ));
+                false
+            }
+            KeyType::Vp(owner) if owner == &addr => {
+                // the VP cannot be replaced until everything is vested
+                let fully_vested = vesting::is_fully_vested(&addr);
+                log_string(format!(
+                    "VP update, valid_sig {}, fully vested {}",
+                    valid_sig, fully_vested
+                ));
+                valid_sig && fully_vested
+            }
+            KeyType::Token { .. } | KeyType::Vesting(_) | KeyType::Vp(_) => {
+                log_string(format!(
+                    "key {} is not of owner, valid_sig {}",
+                    key, valid_sig
+                ));
+                valid_sig
+            }
+            KeyType::Unknown => {
+                log_string(format!(
+                    "Unknown key modified, valid sig {}",
+                    valid_sig
+                ));
+                valid_sig
+            }
+        };
+        if !is_valid {
+            log_string(format!("key {} modification failed vp", key));
+            return false;
+        }
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use anoma_tests::vp::*;
+
+    use super::*;
+
+    /// Test that no-op transaction (i.e. no storage modifications) is deemed
+    /// valid.
+    #[test]
+    fn test_no_op_transaction() {
+        let mut env = TestVpEnv::default();
+        init_vp_env(&mut env);
+
+        let tx_data: Vec<u8> = vec![];
+        let addr: Address = env.addr.clone();
+        let keys_changed: Vec<Key> = vec![];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        let valid = validate_tx(tx_data, addr, keys_changed, verifiers);
+
+        assert!(valid);
+    }
+
+    /// Set up an existing vesting account in storage with the given public
+    /// key and a balance of the given token vesting from height 10 to 20.
+    /// Returns the key of the account's balance.
+    fn init_vesting_account(
+        env: &mut TestVpEnv,
+        pk: &key::ed25519::PublicKey,
+        token: &Address,
+    ) -> Key {
+        let addr = env.addr.clone();
+        let amount = token::Amount::from(100);
+        let schedule = vesting::VestingSchedule::new(
+            BlockHeight(10),
+            BlockHeight(10),
+            BlockHeight(20),
+            amount,
+        )
+        .unwrap();
+        let balance_key = token::balance_key(token, &addr);
+        env.storage
+            .write(&Key::validity_predicate(&addr).unwrap(), vec![])
+            .expect("write failed");
+        env.storage
+            .write(&key::ed25519::pk_key(&addr), pk.try_to_vec().unwrap())
+            .expect("write failed");
+        env.storage
+            .write(
+                &vesting::vesting_key(&addr, token),
+                schedule.try_to_vec().unwrap(),
+            )
+            .expect("write failed");
+        env.storage
+            .write(&balance_key, amount.try_to_vec().unwrap())
+            .expect("write failed");
+        balance_key
+    }
+
+    /// Test that a signed debit can only spend the unlocked amount.
+    #[test]
+    fn test_early_withdrawal() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let keypair = key::ed25519::testing::keypair_1();
+        let pk = key::ed25519::PublicKey::from(keypair.public);
+        let token = address::xan();
+        let balance_key = init_vesting_account(&mut env, &pk, &token);
+        // half of the amount is unlocked
+        env.storage.block.height = BlockHeight(15);
+        env.write_log
+            .write(&balance_key, token::Amount::from(40).try_to_vec().unwrap());
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![balance_key.clone()];
+        let verifiers: HashSet<Address> = HashSet::default();
+        let tx_data = SignedTxData::new(&keypair, vec![], &env.tx_code)
+            .try_to_vec()
+            .unwrap();
+
+        // spending some of the locked amount is rejected
+        let valid = validate_tx(
+            tx_data.clone(),
+            addr.clone(),
+            keys_changed.clone(),
+            verifiers.clone(),
+        );
+        assert!(!valid);
+
+        // spending only the unlocked amount is accepted
+        env.write_log
+            .write(&balance_key, token::Amount::from(50).try_to_vec().unwrap());
+        init_vp_env(&mut env);
+        let valid = validate_tx(
+            tx_data,
+            addr.clone(),
+            keys_changed.clone(),
+            verifiers.clone(),
+        );
+        assert!(valid);
+
+        // but not without a signature
+        let valid = validate_tx(vec![], addr, keys_changed, verifiers);
+        assert!(!valid);
+    }
+
+    /// Test that the transaction that initializes the account can set up its
+    /// storage without a signature.
+    #[test]
+    fn test_new_account_setup() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let pk = key::ed25519::PublicKey::from(
+            key::ed25519::testing::keypair_1().public,
+        );
+        let token = address::xan();
+        let pk_key = key::ed25519::pk_key(&addr);
+        let vesting_key = vesting::vesting_key(&addr, &token);
+        let schedule = vesting::VestingSchedule::new(
+            BlockHeight(10),
+            BlockHeight(10),
+            BlockHeight(20),
+            token::Amount::from(100),
+        )
+        .unwrap();
+        env.write_log.write(&pk_key, pk.try_to_vec().unwrap());
+        env.write_log
+            .write(&vesting_key, schedule.try_to_vec().unwrap());
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![pk_key, vesting_key];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        let valid = validate_tx(vec![], addr, keys_changed, verifiers);
+
+        assert!(valid);
+    }
+
+    /// Test that the storage of an existing account without a public key
+    /// cannot be modified without a signature.
+    #[test]
+    fn test_existing_account_without_pk() {
+        let mut env = TestVpEnv::default();
+        let addr: Address = env.addr.clone();
+        let pk_key = key::ed25519::pk_key(&addr);
+        let pk = key::ed25519::PublicKey::from(
+            key::ed25519::testing::keypair_1().public,
+        );
+        env.storage
+            .write(&Key::validity_predicate(&addr).unwrap(), vec![])
+            .expect("write failed");
+        env.write_log.write(&pk_key, pk.try_to_vec().unwrap());
+        init_vp_env(&mut env);
+
+        let keys_changed: Vec<Key> = vec![pk_key];
+        let verifiers: HashSet<Address> = HashSet::default();
+
+        let valid = validate_tx(vec![], addr, keys_changed, verifiers);
+
+        assert!(!valid);
+    }
+}